@@ -0,0 +1,32 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use data_uri_utils::{bytes_to_data_uri, DataUri};
+
+/// 4 MiB of noise, the size of a losslessly encoded full HD screenshot.
+fn screenshot_sized_png() -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    let mut bytes = b"\x89PNG\r\n\x1a\n".to_vec();
+    bytes.extend((0..4 * 1024 * 1024).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    }));
+    bytes
+}
+
+fn base64_payloads(c: &mut Criterion) {
+    let png = screenshot_sized_png();
+    let uri = bytes_to_data_uri(&png, &mime::IMAGE_PNG);
+    let mut group = c.benchmark_group("base64");
+    group.throughput(Throughput::Bytes(png.len() as u64));
+    group.bench_function("encode", |b| {
+        b.iter(|| bytes_to_data_uri(&png, &mime::IMAGE_PNG))
+    });
+    group.bench_function("decode", |b| {
+        b.iter(|| DataUri::parse(&uri).unwrap().decode_payload().unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, base64_payloads);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use data_uri_utils::encoding::{encode_svg_payload, EncodeSet};
+use data_uri_utils::{collapse_whitespace, svg_str_to_data_uri_with, SvgEncodeOptions};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+/// A few hundred KB of indented paths, like an exported icon sheet.
+fn icon_sheet() -> String {
+    let mut svg =
+        String::from("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 24 24\">\n");
+    for index in 0..3000 {
+        svg.push_str(&format!(
+            "    <path fill=\"#{:06x}\" d=\"M{} 2L22 12 12 22 2 12z\"/>\n",
+            index * 97 % 0xffffff,
+            index % 24
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn svg_encoding(c: &mut Criterion) {
+    let svg = icon_sheet();
+    let mut group = c.benchmark_group("svg_str_to_data_uri");
+    group.throughput(Throughput::Bytes(svg.len() as u64));
+    // The collapse then collect pipeline the encoder replaced.
+    group.bench_function("collect_baseline", |b| {
+        b.iter(|| {
+            let collapsed = collapse_whitespace(svg.trim());
+            let payload: String = utf8_percent_encode(&collapsed, NON_ALPHANUMERIC).collect();
+            format!("data:image/svg+xml,{}", payload)
+        })
+    });
+    for (name, options) in [
+        ("default", SvgEncodeOptions::default()),
+        ("mini", SvgEncodeOptions::mini()),
+    ] {
+        group.bench_with_input(
+            BenchmarkId::new("single_pass", name),
+            &options,
+            |b, options| b.iter(|| svg_str_to_data_uri_with(&svg, options)),
+        );
+    }
+    for (name, encode_set) in [
+        ("default", EncodeSet::NonAlphanumeric),
+        ("mini", EncodeSet::Minimal),
+    ] {
+        group.bench_with_input(
+            BenchmarkId::new("encode_svg_payload", name),
+            &encode_set,
+            |b, &encode_set| {
+                b.iter(|| {
+                    let mut payload = String::with_capacity(svg.len() * 2);
+                    encode_svg_payload::<()>(&svg, encode_set, false, |part| {
+                        payload.push_str(part);
+                        Ok(())
+                    })
+                    .unwrap();
+                    payload
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, svg_encoding);
+criterion_main!(benches);
@@ -0,0 +1,119 @@
+//! Compile-time counterparts of the `data-uri-utils` encoders, re-exported
+//! there behind its `macros` feature.
+
+use base64::Engine;
+use proc_macro::{Literal, TokenStream, TokenTree};
+use std::path::{Path, PathBuf};
+
+// The SVG encoder of `data-uri-utils`, compiled from its own sources so both
+// crates agree on the output.
+#[allow(dead_code)]
+#[path = "../../src/escape.rs"]
+mod escape;
+#[allow(dead_code)]
+#[path = "../../src/markup.rs"]
+mod markup;
+#[path = "../../src/svg/protected.rs"]
+mod protected;
+
+/// Encodes a file as a data URI at compile time, yielding a `&'static str`.
+///
+/// The path is relative to the crate root, the directory of its `Cargo.toml`.
+/// The media type is guessed from the extension. SVG files are
+/// percent-encoded like `svg_str_to_data_uri` does, anything else is
+/// base64-encoded. The crate is rebuilt whenever the file changes.
+///
+/// ```
+/// const MANIFEST: &str = data_uri_utils_macros::include_data_uri!("Cargo.toml");
+/// assert!(MANIFEST.starts_with("data:"));
+/// ```
+#[proc_macro]
+pub fn include_data_uri(input: TokenStream) -> TokenStream {
+    let path = syn::parse_macro_input!(input as syn::LitStr);
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+    let bytes = match std::fs::read(&full_path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            let message = format!("couldn't read {}: {}", full_path.display(), error);
+            return syn::Error::new(path.span(), message)
+                .to_compile_error()
+                .into();
+        }
+    };
+    let uri = encode(&full_path, &bytes);
+    // `include_bytes!` makes cargo track the file, the bytes themselves are unused.
+    let Some(full_path) = full_path.to_str() else {
+        return TokenStream::from(TokenTree::Literal(Literal::string(&uri)));
+    };
+    format!(
+        "{{ const _: &[u8] = ::core::include_bytes!({:?}); {} }}",
+        full_path,
+        Literal::string(&uri)
+    )
+    .parse()
+    .unwrap()
+}
+
+fn encode(path: &Path, bytes: &[u8]) -> String {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    match std::str::from_utf8(bytes) {
+        Ok(svg) if mime == mime_guess::mime::IMAGE_SVG => {
+            let svg = svg.strip_prefix('\u{FEFF}').unwrap_or(svg).trim();
+            let mut uri = String::from("data:image/svg+xml,");
+            protected::for_each_region::<()>(svg, true, |segment, collapse| {
+                let mask = escape::NON_ALPHANUMERIC_MASK;
+                escape::encode_svg_segment(segment, mask, false, collapse, |part| {
+                    uri.push_str(part);
+                    Ok(())
+                })
+            })
+            .unwrap();
+            uri
+        }
+        _ => format!(
+            "data:{};base64,{}",
+            mime.essence_str(),
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use std::path::Path;
+
+    #[test]
+    fn encode_by_extension() {
+        assert_eq!(
+            encode(
+                Path::new("logo.svg"),
+                b"\xef\xbb\xbf <svg>\n  <g/>\n</svg>\n"
+            ),
+            "data:image/svg+xml,%3Csvg%3E%20%3Cg%2F%3E%20%3C%2Fsvg%3E"
+        );
+        assert_eq!(
+            encode(Path::new("icon.png"), b"\x89PNG"),
+            "data:image/png;base64,iVBORw=="
+        );
+        assert_eq!(
+            encode(Path::new("blob"), b"\xff"),
+            "data:application/octet-stream;base64,/w=="
+        );
+    }
+
+    #[test]
+    fn encode_like_runtime() {
+        for svg in [
+            "<svg>\n  <text xml:space=\"preserve\">a    b</text>\n</svg>",
+            "\u{FEFF}\t<svg:svg><svg:text> a  b </svg:text>\u{a0} <pre> c  d </pre></svg:svg>\n",
+            "<svg><style><![CDATA[ a  { } ]]></style><g>  <path d=\"M 0 0\"/></g></svg>",
+        ] {
+            assert_eq!(
+                encode(Path::new("icon.svg"), svg.as_bytes()),
+                data_uri_utils::svg_str_to_data_uri(svg).as_str()
+            );
+        }
+    }
+}
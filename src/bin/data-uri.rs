@@ -0,0 +1,172 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use data_uri_utils::{
+    bytes_to_data_uri, sniff_mime, strip_bom, svg_str_to_data_uri_with, transcode_data_uri,
+    Charset, DataUri, EncodeOptions, Format, JpegOptions, PngOptions, SvgEncodeOptions,
+    WebPQuality,
+};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+/// Converts files to data URIs and back.
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Encodes a file, or `-` for stdin, as a data URI.
+    Encode {
+        input: PathBuf,
+        /// Converts the image to this format first.
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+        /// JPEG quality, from 1 to 100.
+        #[arg(short, long, default_value_t = 80)]
+        quality: u8,
+        /// Color transparent pixels become in JPEG output, as `RRGGBB`.
+        #[arg(long, default_value = "ffffff", value_parser = parse_color)]
+        background: image::Rgb<u8>,
+        /// Minifies SVG input before encoding it.
+        #[arg(short, long)]
+        minify: bool,
+        /// Media type to use instead of guessing it.
+        #[arg(long)]
+        mime: Option<mime::Mime>,
+        /// Copies the data URI to the clipboard instead of printing it.
+        #[arg(short, long)]
+        clipboard: bool,
+    },
+    /// Decodes a data URI, or `-` to read it from stdin.
+    Decode {
+        uri: String,
+        /// Writes the payload to this file instead of stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+    Bmp,
+    Tiff,
+    Ico,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("data-uri: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Encode {
+            input,
+            format,
+            quality,
+            background,
+            minify,
+            mime,
+            clipboard,
+        } => {
+            let bytes = read_input(&input)?;
+            let jpeg = JpegOptions {
+                background: Some(background),
+                ..JpegOptions::new(quality)
+            };
+            let uri = encode(&input, &bytes, format, jpeg, minify, mime)?;
+            if clipboard {
+                arboard::Clipboard::new()?.set_text(uri.into_string())?;
+            } else {
+                println!("{}", uri);
+            }
+        }
+        Command::Decode { uri, output } => {
+            let uri = if uri == "-" {
+                String::from_utf8(read_input(Path::new("-"))?)?
+            } else {
+                uri
+            };
+            let payload = DataUri::parse(&uri)?.decode_payload()?;
+            match output {
+                Some(path) => std::fs::write(path, payload)?,
+                None => std::io::stdout().write_all(&payload)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_input(path: &Path) -> Result<Vec<u8>> {
+    if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        Ok(std::fs::read(path)?)
+    }
+}
+
+fn parse_color(hex: &str) -> Result<image::Rgb<u8>, String> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let channel = |at: usize| {
+        hex.get(at..at + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok(image::Rgb([red, green, blue])),
+        _ => Err(format!("`{}` isn't a RRGGBB color", hex)),
+    }
+}
+
+fn encode(
+    path: &Path,
+    bytes: &[u8],
+    format: Option<OutputFormat>,
+    jpeg: JpegOptions,
+    minify: bool,
+    mime: Option<mime::Mime>,
+) -> Result<DataUri> {
+    let mime = mime
+        .or_else(|| sniff_mime(bytes))
+        .or_else(|| mime_guess::from_path(path).first())
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    if let Some(format) = format {
+        let format = match format {
+            OutputFormat::Png => Format::Png(PngOptions::default()),
+            OutputFormat::Jpeg => Format::Jpeg(jpeg),
+            OutputFormat::Webp => Format::WebP(WebPQuality::Lossless),
+            OutputFormat::Gif => Format::Gif,
+            OutputFormat::Bmp => Format::Bmp,
+            OutputFormat::Tiff => Format::Tiff,
+            OutputFormat::Ico => Format::Ico,
+        };
+        let uri = bytes_to_data_uri(bytes, &mime);
+        return Ok(transcode_data_uri(&uri, format, &EncodeOptions::default())?);
+    }
+    let (text, charset) = strip_bom(bytes);
+    match charset.unwrap_or(Charset::Utf8).decode(text) {
+        Ok(svg) if mime == mime::IMAGE_SVG => {
+            let options = SvgEncodeOptions {
+                minify,
+                ..SvgEncodeOptions::default()
+            };
+            Ok(svg_str_to_data_uri_with(svg, &options))
+        }
+        _ => Ok(bytes_to_data_uri(bytes, &mime)),
+    }
+}
@@ -0,0 +1,254 @@
+use crate::data_uri::parse_header;
+use crate::encoding::{push_header_end, push_payload, wrap_data_uri};
+use crate::{Base64Alphabet, DataUri, EncodeSet, Encoding, MediaType};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+/// Characters escaped in parameter names and values: anything that isn't a
+/// MIME token, along with `%` and `#` which have a meaning in URIs.
+const PARAMETER: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'(')
+    .add(b')')
+    .add(b',')
+    .add(b'/')
+    .add(b':')
+    .add(b';')
+    .add(b'<')
+    .add(b'=')
+    .add(b'>')
+    .add(b'?')
+    .add(b'@')
+    .add(b'[')
+    .add(b'\\')
+    .add(b']')
+    .add(b'{')
+    .add(b'}');
+
+/// Assembles a data URI from a media type, RFC 2397 parameters and a payload.
+///
+/// ```
+/// use data_uri_utils::DataUriBuilder;
+///
+/// let uri = DataUriBuilder::new()
+///     .media_type(&mime::TEXT_PLAIN)
+///     .charset("utf-8")
+///     .build_str("hello");
+/// assert_eq!(uri, "data:text/plain;charset=utf-8;base64,aGVsbG8=");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUriBuilder {
+    media_type: Option<String>,
+    parameters: Vec<(String, String)>,
+    encoding: Encoding,
+    encode_set: EncodeSet,
+    alphabet: Base64Alphabet,
+    omit_padding: bool,
+    line_width: Option<usize>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+}
+
+impl Default for DataUriBuilder {
+    fn default() -> Self {
+        DataUriBuilder {
+            media_type: None,
+            parameters: Vec::new(),
+            encoding: Encoding::Base64,
+            encode_set: EncodeSet::NonAlphanumeric,
+            alphabet: Base64Alphabet::Standard,
+            omit_padding: false,
+            line_width: None,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+        }
+    }
+}
+
+impl DataUriBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A builder for `media_type` with its
+    /// [default encoding](MediaType::default_encoding).
+    pub fn for_media_type(media_type: impl Into<MediaType>) -> Self {
+        let media_type = media_type.into();
+        let encoding = media_type.default_encoding();
+        Self::new().media_type(media_type).encoding(encoding)
+    }
+
+    /// Sets the media type, any parameter it carries is added to the URI.
+    ///
+    /// When no media type is set the header is left empty, which readers
+    /// interpret as `text/plain;charset=US-ASCII`.
+    pub fn media_type(mut self, media_type: impl Into<MediaType>) -> Self {
+        let mime = mime::Mime::from(media_type.into());
+        self.media_type = Some(mime.essence_str().to_string());
+        for (name, value) in mime.params() {
+            self = self.parameter(name.as_str(), value.as_str());
+        }
+        self
+    }
+
+    pub fn charset(self, charset: &str) -> Self {
+        self.parameter(mime::CHARSET.as_str(), charset)
+    }
+
+    /// Adds a parameter, replacing any previous value with the same name.
+    pub fn parameter(mut self, name: &str, value: &str) -> Self {
+        let value = value.to_string();
+        match self
+            .parameters
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.parameters.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    pub fn encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Characters escaped in [`Encoding::Percent`] payloads.
+    pub fn encode_set(mut self, encode_set: EncodeSet) -> Self {
+        self.encode_set = encode_set;
+        self
+    }
+
+    /// Alphabet of [`Encoding::Base64`] payloads.
+    pub fn alphabet(mut self, alphabet: Base64Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Drop the trailing `=` of [`Encoding::Base64`] payloads, which browsers
+    /// don't need.
+    pub fn omit_padding(mut self, omit_padding: bool) -> Self {
+        self.omit_padding = omit_padding;
+        self
+    }
+
+    /// Break the URI into lines of at most `width` characters, see
+    /// [`wrap_data_uri`](crate::wrap_data_uri).
+    pub fn line_width(mut self, width: usize) -> Self {
+        self.line_width = Some(width);
+        self
+    }
+
+    /// Gzip the payload before encoding it, for consumers that decompress it
+    /// themselves. [`data_uri_to_bytes`](crate::data_uri_to_bytes) does for
+    /// SVGs, browsers don't, so the media type is left as is.
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    pub fn build(&self, bytes: &[u8]) -> DataUri {
+        #[cfg(feature = "gzip")]
+        let compressed;
+        #[cfg(feature = "gzip")]
+        let bytes = if self.gzip {
+            compressed = crate::gzip::compress(bytes);
+            &compressed
+        } else {
+            bytes
+        };
+        let mut uri = self.header();
+        let mime = parse_header(&uri[5..uri.len() - 1]).map_or_else(
+            // Only empty parameter names or values can't be parsed back.
+            |_| match &self.media_type {
+                Some(media_type) => media_type.parse().unwrap_or(mime::TEXT_PLAIN),
+                None => mime::TEXT_PLAIN,
+            },
+            |(mime, _)| mime,
+        );
+        push_payload(
+            &mut uri,
+            bytes,
+            self.encoding,
+            self.encode_set,
+            self.alphabet,
+            !self.omit_padding,
+        );
+        if let Some(width) = self.line_width {
+            uri = wrap_data_uri(&uri, width);
+        }
+        DataUri::from_parts(uri, mime, self.encoding)
+    }
+
+    pub fn build_str(&self, text: &str) -> DataUri {
+        self.build(text.as_bytes())
+    }
+
+    pub(crate) fn header(&self) -> String {
+        let mut header = String::from("data:");
+        if let Some(media_type) = &self.media_type {
+            header.push_str(media_type);
+        }
+        for (name, value) in &self.parameters {
+            header.push(';');
+            header.extend(utf8_percent_encode(name, PARAMETER));
+            header.push('=');
+            header.extend(utf8_percent_encode(value, PARAMETER));
+        }
+        push_header_end(&mut header, self.encoding);
+        header
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn build_with_parameters() {
+        let builder = DataUriBuilder::new()
+            .media_type(&mime::TEXT_PLAIN_UTF_8)
+            .parameter("name", "notes, v2.txt")
+            .charset("utf-8");
+        let uri = builder.build_str("hi");
+        assert_eq!(
+            uri,
+            "data:text/plain;charset=utf-8;name=notes%2C%20v2.txt;base64,aGk="
+        );
+        let parsed = DataUri::parse(&uri).unwrap();
+        assert_eq!(
+            parsed.mime().get_param("name").unwrap(),
+            "notes%2C%20v2.txt"
+        );
+        assert_eq!(parsed.decode_payload().unwrap(), b"hi");
+        assert_eq!(parsed, uri);
+
+        let uri = DataUriBuilder::new()
+            .parameter("profile", "https://example.com/a")
+            .build_str("");
+        assert_eq!(uri, "data:;profile=https%3A%2F%2Fexample.com%2Fa;base64,");
+        assert_eq!(DataUri::parse(&uri).unwrap(), uri);
+    }
+
+    #[test]
+    fn build_percent_encoded() {
+        let uri = DataUriBuilder::new()
+            .encoding(Encoding::Percent)
+            .build_str("a b,c");
+        assert_eq!(uri, "data:,a%20b%2Cc");
+        let parsed = DataUri::parse(&uri).unwrap();
+        assert_eq!(parsed.encoding(), Encoding::Percent);
+        assert_eq!(parsed.decode_payload().unwrap(), b"a b,c");
+
+        let uri = DataUriBuilder::new()
+            .media_type(&mime::TEXT_CSS)
+            .encoding(Encoding::Percent)
+            .encode_set(EncodeSet::Minimal)
+            .build_str("a{b:c}");
+        assert_eq!(uri, "data:text/css,a%7Bb:c%7D");
+    }
+}
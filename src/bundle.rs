@@ -0,0 +1,140 @@
+//! Generates Rust constants from asset files, for build scripts.
+//!
+//! ```no_run
+//! // build.rs
+//! use data_uri_utils::bundle::DataUriBundle;
+//!
+//! let out_dir = std::env::var_os("OUT_DIR").unwrap();
+//! DataUriBundle::new()
+//!     .add_file("logo", "assets/logo.svg")
+//!     .add_file("favicon", "assets/favicon.png")
+//!     .write_rust_module(out_dir)
+//!     .unwrap();
+//! ```
+//!
+//! The crate then includes the generated constants with
+//! `include!(concat!(env!("OUT_DIR"), "/data_uris.rs"));`.
+
+use crate::file::file_bytes_to_data_uri;
+use crate::{DataUri, Result};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Name of the file [`DataUriBundle::write_rust_module`] writes.
+pub const MODULE_FILE_NAME: &str = "data_uris.rs";
+
+#[derive(Debug, Clone)]
+enum Asset {
+    File(PathBuf),
+    DataUri(DataUri),
+}
+
+/// Named assets turned into `pub const NAME: &str = "data:...";` items.
+#[derive(Debug, Clone, Default)]
+pub struct DataUriBundle {
+    assets: Vec<(String, Asset)>,
+}
+
+impl DataUriBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, encoded like [`file_to_data_uri`](crate::file_to_data_uri)
+    /// does when the module is generated.
+    ///
+    /// The name is turned into an upper snake case identifier, so `"logo"`
+    /// becomes `LOGO` and `"icon-24"` becomes `ICON_24`.
+    pub fn add_file(mut self, name: &str, path: impl AsRef<Path>) -> Self {
+        let asset = Asset::File(path.as_ref().to_path_buf());
+        self.assets.push((name.to_string(), asset));
+        self
+    }
+
+    /// Adds an already encoded data URI.
+    pub fn add_data_uri(mut self, name: &str, uri: DataUri) -> Self {
+        self.assets.push((name.to_string(), Asset::DataUri(uri)));
+        self
+    }
+
+    /// The Rust source declaring one constant per asset, in insertion order.
+    pub fn to_rust_module(&self) -> Result<String> {
+        let mut module = String::from("// @generated by data-uri-utils, do not edit.\n");
+        for (name, asset) in &self.assets {
+            let uri = match asset {
+                Asset::File(path) => file_bytes_to_data_uri(path, &std::fs::read(path)?),
+                Asset::DataUri(uri) => uri.clone(),
+            };
+            writeln!(
+                module,
+                "pub const {}: &str = {:?};",
+                const_name(name),
+                uri.as_str()
+            )
+            .unwrap();
+        }
+        Ok(module)
+    }
+
+    /// Writes [`to_rust_module`](Self::to_rust_module) to
+    /// [`MODULE_FILE_NAME`] in `out_dir` and returns its path.
+    ///
+    /// Also prints `cargo:rerun-if-changed` for every file, so the build
+    /// script runs again when an asset changes.
+    pub fn write_rust_module(&self, out_dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let module = self.to_rust_module()?;
+        let path = out_dir.as_ref().join(MODULE_FILE_NAME);
+        std::fs::write(&path, module)?;
+        for (_, asset) in &self.assets {
+            if let Asset::File(file) = asset {
+                println!("cargo:rerun-if-changed={}", file.display());
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Upper snake case identifier, prefixed with `_` if it would start with a digit.
+fn const_name(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bundle::DataUriBundle;
+    use crate::*;
+
+    #[test]
+    fn rust_module() {
+        let dir = std::env::temp_dir().join("data-uri-utils-bundle");
+        std::fs::create_dir_all(&dir).unwrap();
+        let svg = dir.join("logo.svg");
+        std::fs::write(&svg, "<svg/>").unwrap();
+        let module = DataUriBundle::new()
+            .add_file("logo", &svg)
+            .add_data_uri("3d-icon", bytes_to_data_uri(b"\"", &mime::TEXT_PLAIN))
+            .to_rust_module()
+            .unwrap();
+        assert_eq!(
+            module,
+            "// @generated by data-uri-utils, do not edit.\n\
+             pub const LOGO: &str = \"data:image/svg+xml,%3Csvg%2F%3E\";\n\
+             pub const _3D_ICON: &str = \"data:text/plain;base64,Ig==\";\n"
+        );
+        let missing = DataUriBundle::new().add_file("missing", dir.join("missing.png"));
+        assert!(matches!(missing.to_rust_module(), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{DataUri, DataUriBuilder, Encoding, Error, MediaType, Result};
+use std::borrow::Cow;
+
+/// Character set of a textual payload.
+///
+/// UTF-8, UTF-16, US-ASCII and ISO-8859-1 are always available, other
+/// charsets need the `encoding_rs` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    UsAscii,
+    /// ISO-8859-1, where every byte is the code point of the same value.
+    Latin1,
+    /// Any charset supported by `encoding_rs`, looked up by its WHATWG label.
+    #[cfg(feature = "encoding_rs")]
+    Other(&'static encoding_rs::Encoding),
+}
+
+impl Charset {
+    /// Looks a charset up by name, case-insensitively.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.trim();
+        match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Some(Charset::Utf8),
+            "utf-16le" => Some(Charset::Utf16Le),
+            "utf-16be" => Some(Charset::Utf16Be),
+            "us-ascii" | "ascii" => Some(Charset::UsAscii),
+            "iso-8859-1" | "iso_8859-1" | "latin1" => Some(Charset::Latin1),
+            #[cfg(feature = "encoding_rs")]
+            _ => encoding_rs::Encoding::for_label(label.as_bytes()).map(Charset::Other),
+            #[cfg(not(feature = "encoding_rs"))]
+            _ => None,
+        }
+    }
+
+    /// Name used in the `charset` parameter.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Charset::Utf8 => "utf-8",
+            Charset::Utf16Le => "UTF-16LE",
+            Charset::Utf16Be => "UTF-16BE",
+            Charset::UsAscii => "US-ASCII",
+            Charset::Latin1 => "ISO-8859-1",
+            #[cfg(feature = "encoding_rs")]
+            Charset::Other(encoding) => encoding.name(),
+        }
+    }
+
+    /// Fails with [`Error::InvalidText`] when `text` has characters the
+    /// charset can't represent.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let invalid = || Error::InvalidText {
+            charset: self.label().to_string(),
+        };
+        match self {
+            Charset::Utf8 => Ok(Cow::Borrowed(text.as_bytes())),
+            Charset::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Charset::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Charset::UsAscii if text.is_ascii() => Ok(Cow::Borrowed(text.as_bytes())),
+            Charset::UsAscii => Err(invalid()),
+            Charset::Latin1 => text
+                .chars()
+                .map(|c| u8::try_from(c).map_err(|_| invalid()))
+                .collect::<Result<Vec<u8>>>()
+                .map(Cow::Owned),
+            #[cfg(feature = "encoding_rs")]
+            Charset::Other(encoding) => {
+                let mut encoder = encoding.new_encoder();
+                let capacity = encoder
+                    .max_buffer_length_from_utf8_without_replacement(text.len())
+                    .ok_or_else(invalid)?;
+                let mut bytes = Vec::with_capacity(capacity);
+                let (result, _) =
+                    encoder.encode_from_utf8_to_vec_without_replacement(text, &mut bytes, true);
+                match result {
+                    encoding_rs::EncoderResult::InputEmpty => Ok(Cow::Owned(bytes)),
+                    _ => Err(invalid()),
+                }
+            }
+        }
+    }
+
+    /// Fails with [`Error::InvalidText`] on byte sequences that aren't valid
+    /// in the charset.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>> {
+        let invalid = || Error::InvalidText {
+            charset: self.label().to_string(),
+        };
+        match self {
+            Charset::Utf8 => std::str::from_utf8(bytes)
+                .map(Cow::Borrowed)
+                .map_err(|_| invalid()),
+            Charset::Utf16Le | Charset::Utf16Be => {
+                if bytes.len() % 2 != 0 {
+                    return Err(invalid());
+                }
+                let units = bytes.chunks_exact(2).map(|unit| {
+                    let unit = [unit[0], unit[1]];
+                    match self {
+                        Charset::Utf16Le => u16::from_le_bytes(unit),
+                        _ => u16::from_be_bytes(unit),
+                    }
+                });
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map(Cow::Owned)
+                    .map_err(|_| invalid())
+            }
+            Charset::UsAscii if bytes.is_ascii() => Ok(Cow::Borrowed(
+                std::str::from_utf8(bytes).map_err(|_| invalid())?,
+            )),
+            Charset::UsAscii => Err(invalid()),
+            Charset::Latin1 => Ok(bytes.iter().map(|&byte| char::from(byte)).collect()),
+            #[cfg(feature = "encoding_rs")]
+            Charset::Other(encoding) => encoding
+                .decode_without_bom_handling_and_without_replacement(bytes)
+                .ok_or_else(invalid),
+        }
+    }
+
+    /// Like [`Charset::decode`], replacing invalid sequences with U+FFFD.
+    pub fn decode_lossy<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
+        match self {
+            Charset::Utf8 => String::from_utf8_lossy(bytes),
+            Charset::Utf16Le | Charset::Utf16Be => {
+                let units = bytes.chunks(2).map(|unit| match (self, unit) {
+                    (Charset::Utf16Le, [low, high]) => u16::from_le_bytes([*low, *high]),
+                    (_, [high, low]) => u16::from_be_bytes([*high, *low]),
+                    // A trailing odd byte.
+                    _ => 0xfffd,
+                });
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect()
+            }
+            Charset::UsAscii if bytes.is_ascii() => String::from_utf8_lossy(bytes),
+            Charset::UsAscii => bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0..=0x7f => char::from(byte),
+                    _ => char::REPLACEMENT_CHARACTER,
+                })
+                .collect(),
+            Charset::Latin1 => bytes.iter().map(|&byte| char::from(byte)).collect(),
+            #[cfg(feature = "encoding_rs")]
+            Charset::Other(encoding) => encoding.decode_without_bom_handling(bytes).0,
+        }
+    }
+}
+
+/// Splits a UTF-8, UTF-16LE or UTF-16BE byte order mark off `bytes`,
+/// returning the rest along with the charset it announces.
+pub fn strip_bom(bytes: &[u8]) -> (&[u8], Option<Charset>) {
+    match bytes {
+        [0xef, 0xbb, 0xbf, rest @ ..] => (rest, Some(Charset::Utf8)),
+        [0xff, 0xfe, rest @ ..] => (rest, Some(Charset::Utf16Le)),
+        [0xfe, 0xff, rest @ ..] => (rest, Some(Charset::Utf16Be)),
+        _ => (bytes, None),
+    }
+}
+
+/// Decodes text files: by their byte order mark when they have one, as UTF-8
+/// otherwise.
+pub(crate) fn decode_text_file(bytes: &[u8]) -> Option<Cow<'_, str>> {
+    match strip_bom(bytes) {
+        (text, Some(charset)) => charset.decode(text).ok(),
+        (text, None) => std::str::from_utf8(text).ok().map(Cow::Borrowed),
+    }
+}
+
+/// A percent-encoded data URI for `text`, converted to `charset` and declaring
+/// it in the `charset` parameter, in place of any the media type carries.
+pub fn text_to_data_uri(
+    text: &str,
+    media_type: impl Into<MediaType>,
+    charset: Charset,
+) -> Result<DataUri> {
+    let bytes = charset.encode(text)?;
+    Ok(DataUriBuilder::new()
+        .media_type(media_type)
+        .charset(charset.label())
+        .encoding(Encoding::Percent)
+        .build(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn text_charsets() {
+        let uri = text_to_data_uri("café", &mime::TEXT_PLAIN_UTF_8, Charset::Latin1).unwrap();
+        assert_eq!(uri, "data:text/plain;charset=ISO-8859-1,caf%E9");
+        assert_eq!(uri.charset(), Some(Charset::Latin1));
+        assert_eq!(uri.decode_text().unwrap(), "café");
+
+        let uri = text_to_data_uri("café", &mime::TEXT_CSS, Charset::Utf8).unwrap();
+        assert_eq!(uri, "data:text/css;charset=utf-8,caf%C3%A9");
+        assert_eq!(uri.decode_text().unwrap(), "café");
+
+        assert!(matches!(
+            text_to_data_uri("café", &mime::TEXT_PLAIN, Charset::UsAscii),
+            Err(Error::InvalidText { .. })
+        ));
+        let uri = DataUri::parse("data:,caf%E9").unwrap();
+        assert_eq!(uri.charset(), Some(Charset::UsAscii));
+        assert!(uri.decode_text().is_err());
+    }
+
+    #[test]
+    fn byte_order_marks() {
+        assert_eq!(
+            strip_bom(b"\xef\xbb\xbf<svg/>"),
+            (&b"<svg/>"[..], Some(Charset::Utf8))
+        );
+        assert_eq!(strip_bom(b"<svg/>"), (&b"<svg/>"[..], None));
+        let (text, charset) = strip_bom(b"\xfe\xff\0<\0a\0>");
+        assert_eq!(charset, Some(Charset::Utf16Be));
+        assert_eq!(charset.unwrap().decode(text).unwrap(), "<a>");
+        assert!(Charset::Utf16Le.decode(b"<\0a").is_err());
+        assert_eq!(Charset::Utf16Le.encode("a").unwrap(), &b"a\0"[..]);
+    }
+
+    #[cfg(feature = "encoding_rs")]
+    #[test]
+    fn encoding_rs_charsets() {
+        let shift_jis = Charset::from_label("Shift_JIS").unwrap();
+        let uri = text_to_data_uri("日本", &mime::TEXT_PLAIN, shift_jis).unwrap();
+        assert_eq!(uri, "data:text/plain;charset=Shift_JIS,%93%FA%96%7B");
+        assert_eq!(uri.decode_text().unwrap(), "日本");
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{bytes_to_data_uri_with, sniff_mime, svg_str_to_data_uri, DataUri, EncodeOptions};
+use percent_encoding::{utf8_percent_encode, AsciiSet};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Characters that would end a quoted CSS `url()`, or that older parsers
+/// choke on even inside quotes. Percent-encoding them keeps the data URI
+/// equivalent, unlike CSS backslash escapes.
+const CSS_URL: &AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'\'')
+    .add(b'(')
+    .add(b')')
+    .add(b'\\');
+
+/// Wraps a data URI in a CSS `url("...")`.
+pub fn css_url(data_uri: &str) -> String {
+    format!("url(\"{}\")", utf8_percent_encode(data_uri, CSS_URL))
+}
+
+/// A `background-image` declaration for the given data URI.
+pub fn css_background_image(data_uri: &str) -> String {
+    format!("background-image: {};", css_url(data_uri))
+}
+
+pub fn svg_str_to_css_url(svg: impl AsRef<str>) -> String {
+    css_url(&svg_str_to_data_uri(svg))
+}
+
+#[cfg(feature = "image")]
+pub fn image_to_css_background<T>(image: &T, format: crate::Format) -> crate::Result<String>
+where
+    T: crate::EncodableImage,
+{
+    let uri = crate::image_to_data_uri(image, format)?;
+    Ok(css_background_image(&uri))
+}
+
+/// Replaces the `url()` references of a stylesheet with data URIs.
+///
+/// `resolver` receives each URL with CSS escapes decoded and returns its
+/// content, or `None` to leave the reference untouched. It isn't called for
+/// existing data URIs and `#fragment` references. Assets whose data URI would
+/// exceed [`EncodeOptions::max_len`] are left as is.
+pub fn inline_css_urls(
+    css: &str,
+    resolver: impl Fn(&str) -> Option<Vec<u8>>,
+    options: &EncodeOptions,
+) -> String {
+    let mut inlined = String::with_capacity(css.len());
+    let mut position = 0;
+    for range in url_ranges(css) {
+        let url = unescape(&css[range.clone()]);
+        let Some(uri) = resolve_data_uri(&url, &resolver, options) else {
+            continue;
+        };
+        inlined.push_str(&css[position..range.start]);
+        inlined.extend(utf8_percent_encode(&uri, CSS_URL));
+        position = range.end;
+    }
+    inlined.push_str(&css[position..]);
+    inlined
+}
+
+/// Same as [`inline_css_urls`], except that assets referenced more than once
+/// are emitted a single time, as custom properties of a `:root` rule at the
+/// top of the stylesheet, and referenced with `var()`.
+///
+/// References in `@font-face` rules, where custom properties don't apply, are
+/// inlined as usual. `resolver` is called once per distinct URL.
+///
+/// ```
+/// let css = ".a { background: url(icon.svg) } .b { background: url('icon.svg') }";
+/// let inlined = data_uri_utils::inline_css_urls_deduped(
+///     css,
+///     |_| Some(b"<svg/>".to_vec()),
+///     &Default::default(),
+/// );
+/// assert_eq!(
+///     inlined,
+///     ":root { --data-uri-1: url(\"data:image/svg+xml;base64,PHN2Zy8+\"); }\n\
+///      .a { background: var(--data-uri-1) } .b { background: var(--data-uri-1) }"
+/// );
+/// ```
+pub fn inline_css_urls_deduped(
+    css: &str,
+    resolver: impl Fn(&str) -> Option<Vec<u8>>,
+    options: &EncodeOptions,
+) -> String {
+    let references: Vec<_> = url_ranges(css)
+        .into_iter()
+        .map(|range| (unescape(&css[range.clone()]).into_owned(), range))
+        .collect();
+    let font_faces = font_face_ranges(css);
+    let in_font_face = |position: usize| {
+        let index = font_faces.partition_point(|range| range.end <= position);
+        font_faces
+            .get(index)
+            .is_some_and(|range| range.start <= position)
+    };
+    let mut uris = HashMap::new();
+    let mut shared_counts = HashMap::new();
+    for (url, range) in &references {
+        if !uris.contains_key(url) {
+            uris.insert(url.clone(), resolve_data_uri(url, &resolver, options));
+        }
+        if !in_font_face(range.start) {
+            *shared_counts.entry(url.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    let mut properties: Vec<(&str, &str)> = Vec::new();
+    let mut inlined = String::with_capacity(css.len());
+    let mut position = 0;
+    for (url, range) in &references {
+        let Some(uri) = &uris[url] else {
+            continue;
+        };
+        if shared_counts.get(url.as_str()) > Some(&1) && !in_font_face(range.start) {
+            let index = match properties.iter().position(|(shared, _)| shared == url) {
+                Some(index) => index,
+                None => {
+                    properties.push((url, uri));
+                    properties.len() - 1
+                }
+            };
+            let function = url_function_range(css, range.clone());
+            inlined.push_str(&css[position..function.start]);
+            inlined.push_str(&format!("var(--data-uri-{})", index + 1));
+            position = function.end;
+        } else {
+            inlined.push_str(&css[position..range.start]);
+            inlined.extend(utf8_percent_encode(uri, CSS_URL));
+            position = range.end;
+        }
+    }
+    inlined.push_str(&css[position..]);
+
+    if properties.is_empty() {
+        return inlined;
+    }
+    let mut root = String::from(":root {");
+    for (index, (_, uri)) in properties.iter().enumerate() {
+        root.push_str(&format!(" --data-uri-{}: {};", index + 1, css_url(uri)));
+    }
+    root.push_str(" }\n");
+    let start = leading_statements_len(&inlined);
+    inlined.insert_str(start, &root);
+    inlined
+}
+
+/// Byte ranges of the blocks of the `@font-face` rules, in order.
+fn font_face_ranges(css: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut position = 0;
+    while let Some(found) = find_ignore_ascii_case(&css[position..], "@font-face") {
+        let rule = position + found;
+        let Some(open) = css[rule..].find('{') else {
+            break;
+        };
+        let start = rule + open;
+        let mut depth = 0;
+        let mut end = css.len();
+        for (index, c) in css[start..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                end = start + index + 1;
+                break;
+            }
+        }
+        ranges.push(start..end);
+        position = end;
+    }
+    ranges
+}
+
+/// Widens the range of a URL found by [`url_ranges`] to its `url()` function.
+fn url_function_range(css: &str, url: Range<usize>) -> Range<usize> {
+    let start = rfind_ignore_ascii_case(&css[..url.start], "url(").unwrap_or(url.start);
+    let end = css[url.end..]
+        .find(')')
+        .map_or(css.len(), |end| url.end + end + 1);
+    start..end
+}
+
+/// Length of the `@charset` and `@import` statements, which must come first.
+fn leading_statements_len(css: &str) -> usize {
+    let mut position = 0;
+    loop {
+        let rest = &css[position..];
+        let statement = rest.trim_start();
+        let is_leading = ["@charset", "@import"].iter().any(|keyword| {
+            statement
+                .get(..keyword.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(keyword))
+        });
+        match statement.find(';').filter(|_| is_leading) {
+            Some(end) => position += rest.len() - statement.len() + end + 1,
+            None => return position + rest.len() - rest.trim_start().len(),
+        }
+    }
+}
+
+/// Fetches `url` through `resolver` and encodes it, shared by the inliners.
+pub(crate) fn resolve_data_uri(
+    url: &str,
+    resolver: &impl Fn(&str) -> Option<Vec<u8>>,
+    options: &EncodeOptions,
+) -> Option<String> {
+    let url = url.trim();
+    let is_data_uri = url
+        .get(..5)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"));
+    if url.is_empty() || url.starts_with('#') || is_data_uri {
+        return None;
+    }
+    let bytes = resolver(url)?;
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let mime = sniff_mime(&bytes)
+        .or_else(|| mime_guess::from_path(path).first())
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    bytes_to_data_uri_with(&bytes, &mime, options)
+        .ok()
+        .map(DataUri::into_string)
+}
+
+/// Decodes CSS backslash escapes.
+fn unescape(css: &str) -> Cow<'_, str> {
+    if !css.contains('\\') {
+        return Cow::Borrowed(css);
+    }
+    let mut unescaped = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        let mut code = String::new();
+        while code.len() < 6 && chars.peek().is_some_and(char::is_ascii_hexdigit) {
+            code.extend(chars.next());
+        }
+        if code.is_empty() {
+            unescaped.extend(chars.next());
+        } else {
+            let code = u32::from_str_radix(&code, 16).unwrap_or_default();
+            unescaped.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+            // A single whitespace terminates a hexadecimal escape.
+            chars.next_if(|c| c.is_whitespace());
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// Byte ranges of the URLs referenced with `url()`, excluding any quotes.
+pub(crate) fn url_ranges(css: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut position = 0;
+    while let Some(found) = find_ignore_ascii_case(&css[position..], "url(") {
+        let function = position + found;
+        position = function + 4;
+        let is_identifier = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+        if css[..function].ends_with(is_identifier) {
+            continue;
+        }
+        let rest = &css[position..];
+        let url = rest.trim_start();
+        let start = position + rest.len() - url.len();
+        let range = match url.chars().next() {
+            Some(quote @ ('"' | '\'')) => match url[1..].find(quote) {
+                Some(end) => start + 1..start + 1 + end,
+                None => break,
+            },
+            _ => match url.find(')') {
+                Some(end) => start..start + url[..end].trim_end().len(),
+                None => break,
+            },
+        };
+        position = range.end;
+        ranges.push(range);
+    }
+    ranges
+}
+
+fn rfind_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .rposition(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn svg_css_url() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><text x='1'>(a) \ b</text></svg>"#;
+        let css = svg_str_to_css_url(svg);
+        assert!(css.starts_with("url(\"data:image/svg+xml,"));
+        assert!(css.ends_with("\")"));
+        let inner = &css[5..css.len() - 2];
+        assert!(!inner.contains(['"', '\'', '(', ')', '\\', ' ']));
+
+        let mini = svg_str_to_data_uri_with(svg, &SvgEncodeOptions::mini());
+        let css = css_url(&mini);
+        assert!(!css[5..css.len() - 2].contains(['"', '\'', '(', ')', '\\', ' ']));
+        let decoded = DataUri::parse(&css[5..css.len() - 2])
+            .unwrap()
+            .decode_payload()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            DataUri::parse(&mini).unwrap().decode_payload().unwrap()
+        );
+    }
+
+    #[test]
+    fn find_urls() {
+        let css = r#"a { background: URL( "a.png" ) } b { src: url(b.woff)format("woff"), url('c d.svg'); myurl(x) }"#;
+        let urls: Vec<_> = super::url_ranges(css)
+            .into_iter()
+            .map(|range| &css[range])
+            .collect();
+        assert_eq!(urls, ["a.png", "b.woff", "c d.svg"]);
+        assert!(super::url_ranges("url(\"unterminated").is_empty());
+        let css = "@FONT-FACE { a { } } b { } @font-face {";
+        let faces: Vec<_> = super::font_face_ranges(css)
+            .into_iter()
+            .map(|range| &css[range])
+            .collect();
+        assert_eq!(faces, ["{ a { } }", "{"]);
+    }
+
+    #[test]
+    fn inline_stylesheet() {
+        let css = r#"@font-face { src: url("fonts/a\ b.woff2") format("woff2") }
+.logo { background: url(logo.svg?v=1), url(big.png) }
+.filtered { filter: url(#shadow) } .x { background: url(data:,kept) }"#;
+        let inlined = inline_css_urls(
+            css,
+            |url| match url {
+                "fonts/a b.woff2" => Some(b"wOF2".to_vec()),
+                "logo.svg?v=1" => Some(b"<svg/>".to_vec()),
+                "big.png" => Some(vec![0; 100]),
+                url => panic!("unexpected url {}", url),
+            },
+            &EncodeOptions {
+                max_len: Some(50),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            inlined,
+            r#"@font-face { src: url("data:font/woff2;base64,d09GMg==") format("woff2") }
+.logo { background: url(data:image/svg+xml;base64,PHN2Zy8+), url(big.png) }
+.filtered { filter: url(#shadow) } .x { background: url(data:,kept) }"#
+        );
+    }
+
+    #[test]
+    fn inline_stylesheet_deduped() {
+        let css = r#"@charset "utf-8";
+@import url(print.css) print;
+@font-face { src: url(a.woff2) } @font-face { src: url(a.woff2) }
+.a { background: url(bg.png), URL( "bg.png" ) } .b { mask: url(one.svg) }"#;
+        let inlined = inline_css_urls_deduped(
+            css,
+            |url| match url {
+                "a.woff2" => Some(b"wOF2".to_vec()),
+                "bg.png" => Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+                "one.svg" => Some(b"<svg/>".to_vec()),
+                _ => None,
+            },
+            &EncodeOptions::default(),
+        );
+        assert_eq!(
+            inlined,
+            r#"@charset "utf-8";
+@import url(print.css) print;
+:root { --data-uri-1: url("data:image/png;base64,iVBORw0KGgo="); }
+@font-face { src: url(data:font/woff2;base64,d09GMg==) } @font-face { src: url(data:font/woff2;base64,d09GMg==) }
+.a { background: var(--data-uri-1), var(--data-uri-1) } .b { mask: url(data:image/svg+xml;base64,PHN2Zy8+) }"#
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_background() {
+        let image = image::RgbImage::new(1, 1);
+        let css = image_to_css_background(&image, Format::Png(PngOptions::default())).unwrap();
+        assert!(css.starts_with("background-image: url(\"data:image/png;base64,"));
+        assert!(css.ends_with("\");"));
+    }
+}
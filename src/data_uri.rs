@@ -0,0 +1,750 @@
+use crate::encoding::decode_base45_into;
+use crate::{Base64Alphabet, Encoding};
+use base64::engine::GeneralPurpose;
+use base64::Engine;
+use mime::Mime;
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use std::fmt;
+
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    MissingScheme,
+    MissingComma,
+    InvalidMediaType(String),
+    InvalidBase64(base64::DecodeError),
+    InvalidBase45,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingScheme => write!(f, "data URI must start with `data:`"),
+            ParseError::MissingComma => {
+                write!(f, "data URI must separate its header and payload with `,`")
+            }
+            ParseError::InvalidMediaType(media_type) => {
+                write!(f, "invalid media type `{}`", media_type)
+            }
+            ParseError::InvalidBase64(error) => write!(f, "invalid base64 payload: {}", error),
+            ParseError::InvalidBase45 => write!(f, "invalid base45 payload"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::InvalidBase64(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// An RFC 2397 data URI, as returned by the encoding functions or parsed from
+/// a string.
+///
+/// The header is validated up front, the payload is only decoded on demand.
+/// It derefs to `str` so it can be used wherever the URI text is expected.
+///
+/// Two data URIs are equal, and hash the same, when their decoded payloads
+/// and normalized media types are, whatever their encoding and parameter
+/// order. Comparing with a string compares the URI text.
+///
+/// The alternate `{:#}` and `{:#?}` forms print a [`summarize`](crate::summarize)d
+/// URI, to keep payloads out of logs.
+#[derive(Clone)]
+pub struct DataUri {
+    uri: String,
+    mime: Mime,
+    encoding: Encoding,
+    payload_start: usize,
+}
+
+impl DataUri {
+    pub fn parse(uri: &str) -> Result<Self, ParseError> {
+        let uri = uri.trim();
+        let scheme = uri.get(..5).ok_or(ParseError::MissingScheme)?;
+        if !scheme.eq_ignore_ascii_case("data:") {
+            return Err(ParseError::MissingScheme);
+        }
+        let comma = uri.find(',').ok_or(ParseError::MissingComma)?;
+        let (mime, encoding) = parse_header(&uri[5..comma])?;
+        Ok(DataUri {
+            uri: uri.to_string(),
+            mime,
+            encoding,
+            payload_start: comma + 1,
+        })
+    }
+
+    /// Wraps a URI assembled by the crate, whose header is already known.
+    pub(crate) fn from_parts(uri: String, mime: Mime, encoding: Encoding) -> Self {
+        let payload_start = uri.find(',').map_or(uri.len(), |comma| comma + 1);
+        DataUri {
+            uri,
+            mime,
+            encoding,
+            payload_start,
+        }
+    }
+
+    /// The media type, including its parameters such as `charset`.
+    pub fn mime(&self) -> &Mime {
+        &self.mime
+    }
+
+    /// The media type as a [`MediaType`](crate::MediaType), parameters included.
+    pub fn media_type(&self) -> crate::MediaType {
+        crate::MediaType::from(&self.mime)
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// The payload as it appears in the URI, before any decoding.
+    pub fn payload(&self) -> &str {
+        &self.uri[self.payload_start..]
+    }
+
+    pub fn decode_payload(&self) -> Result<Vec<u8>, ParseError> {
+        self.decode_payload_prefix(usize::MAX)
+    }
+
+    /// Decodes at least the first `len` bytes of the payload, all of it when
+    /// it is shorter.
+    pub(crate) fn decode_payload_prefix(&self, len: usize) -> Result<Vec<u8>, ParseError> {
+        let payload = self.payload();
+        match self.encoding {
+            Encoding::Base64 => {
+                // Whole quanta are taken so that a prefix decodes like the
+                // full payload.
+                let digits_len = len.div_ceil(3).saturating_mul(4);
+                let mut bytes = Vec::new();
+                decode_base64_payload(payload, digits_len, &mut bytes)
+                    .map_err(ParseError::InvalidBase64)?;
+                Ok(bytes)
+            }
+            Encoding::Percent => Ok(decoded_bytes(payload).take(len).collect()),
+            Encoding::Base45 => {
+                let payload = unwrapped(payload);
+                let payload = payload
+                    .get(..len.div_ceil(2).saturating_mul(3))
+                    .unwrap_or(&payload);
+                let mut bytes = Vec::new();
+                decode_base45_into(payload, &mut bytes).ok_or(ParseError::InvalidBase45)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decodes the payload a chunk at a time, so that multi-megabyte payloads
+    /// can be streamed to a file or a hasher without holding them decoded.
+    ///
+    /// Chunks are at most [`PayloadChunks::CHUNK_LEN`] bytes. The iterator
+    /// ends after the first error.
+    ///
+    /// ```
+    /// let uri = data_uri_utils::DataUri::parse("data:application/pdf;base64,JVBERi0=")?;
+    /// let mut pdf = Vec::new();
+    /// for chunk in uri.payload_chunks() {
+    ///     pdf.extend(chunk?);
+    /// }
+    /// assert_eq!(pdf, b"%PDF-");
+    /// # Ok::<_, data_uri_utils::ParseError>(())
+    /// ```
+    pub fn payload_chunks(&self) -> PayloadChunks<'_> {
+        PayloadChunks::new(self.payload(), self.encoding)
+    }
+
+    /// The charset declared by the media type, defaulting to US-ASCII for
+    /// `text/*` types. `None` when it is missing or unknown.
+    pub fn charset(&self) -> Option<crate::Charset> {
+        match self.mime.get_param(mime::CHARSET) {
+            Some(charset) => crate::Charset::from_label(charset.as_str()),
+            None => (self.mime.type_() == mime::TEXT).then_some(crate::Charset::UsAscii),
+        }
+    }
+
+    /// Decodes the payload as text in its declared charset, or UTF-8 for
+    /// non-text types that don't declare one.
+    pub fn decode_text(&self) -> crate::Result<String> {
+        let charset = match self.mime.get_param(mime::CHARSET) {
+            Some(charset) => crate::Charset::from_label(charset.as_str())
+                .ok_or_else(|| crate::Error::UnsupportedCharset(charset.to_string()))?,
+            None => self.charset().unwrap_or(crate::Charset::Utf8),
+        };
+        let bytes = self.decode_payload()?;
+        Ok(charset.decode(&bytes)?.into_owned())
+    }
+
+    pub fn is_base64(&self) -> bool {
+        self.encoding == Encoding::Base64
+    }
+
+    /// Length of the decoded payload, computed without decoding it.
+    pub fn payload_len(&self) -> usize {
+        decoded_payload_len(self.payload(), self.encoding)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.uri
+    }
+
+    /// The media type with its type, subtype, parameter names and charset in
+    /// lower case and its parameters sorted, e.g. `text/plain;charset=utf-8`.
+    pub fn normalized_mime(&self) -> String {
+        let mut params: Vec<(String, String)> = self
+            .mime
+            .params()
+            .map(|(name, value)| {
+                let name = name.as_str().to_ascii_lowercase();
+                let value = if name == "charset" {
+                    value.as_str().to_ascii_lowercase()
+                } else {
+                    value.to_string()
+                };
+                (name, value)
+            })
+            .collect();
+        params.sort();
+        let mut normalized = self.mime.essence_str().to_ascii_lowercase();
+        for (name, value) in params {
+            normalized.push(';');
+            normalized.push_str(&name);
+            normalized.push('=');
+            normalized.push_str(&value);
+        }
+        normalized
+    }
+
+    /// What equality and hashing compare. Payloads that fail to decode are
+    /// compared as written.
+    fn content(&self) -> (String, Cow<'_, [u8]>) {
+        let payload = match self.decode_payload() {
+            Ok(payload) => payload.into(),
+            Err(_) => self.payload().as_bytes().into(),
+        };
+        (self.normalized_mime(), payload)
+    }
+
+    pub fn into_string(self) -> String {
+        self.uri
+    }
+}
+
+/// Decodes the payload of `uri` and appends it to `out`, for hot paths that
+/// reuse one buffer for many URIs.
+///
+/// Unlike [`DataUri::parse`] the media type isn't validated, and nothing is
+/// allocated beyond growing `out`, unless the payload is wrapped or base64
+/// with percent escapes. `out` is left as it was on error.
+///
+/// ```
+/// let mut buffer = Vec::with_capacity(64);
+/// for uri in ["data:,a%20b", "data:text/plain;base64,aGk="] {
+///     buffer.clear();
+///     data_uri_utils::decode_payload_into(uri, &mut buffer)?;
+///     assert_eq!(buffer.len(), data_uri_utils::decoded_len(uri)?);
+/// }
+/// assert_eq!(buffer, b"hi");
+/// # Ok::<_, data_uri_utils::ParseError>(())
+/// ```
+pub fn decode_payload_into(uri: &str, out: &mut Vec<u8>) -> Result<(), ParseError> {
+    let (payload, encoding) = split_payload(uri)?;
+    let len = out.len();
+    let decoded = match encoding {
+        Encoding::Base64 => {
+            decode_base64_payload(payload, usize::MAX, out).map_err(ParseError::InvalidBase64)
+        }
+        Encoding::Percent => {
+            out.extend(decoded_bytes(payload));
+            Ok(())
+        }
+        Encoding::Base45 => {
+            decode_base45_into(&unwrapped(payload), out).ok_or(ParseError::InvalidBase45)
+        }
+    };
+    if decoded.is_err() {
+        out.truncate(len);
+    }
+    decoded
+}
+
+/// Length of the payload of `uri` once decoded, to size buffers for
+/// [`decode_payload_into`]. Like it, only the scheme and comma are checked.
+pub fn decoded_len(uri: &str) -> Result<usize, ParseError> {
+    let (payload, encoding) = split_payload(uri)?;
+    Ok(decoded_payload_len(payload, encoding))
+}
+
+/// Iterator over the decoded payload of a [`DataUri`], returned by
+/// [`DataUri::payload_chunks`].
+pub struct PayloadChunks<'a> {
+    digits: Box<dyn Iterator<Item = u8> + 'a>,
+    encoding: Encoding,
+    base64_engine: GeneralPurpose,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl<'a> PayloadChunks<'a> {
+    /// Upper bound of the decoded length of a chunk.
+    pub const CHUNK_LEN: usize = 48 * 1024;
+
+    fn new(payload: &'a str, encoding: Encoding) -> Self {
+        let digits: Box<dyn Iterator<Item = u8>> = match encoding {
+            Encoding::Base64 => {
+                Box::new(decoded_bytes(payload).filter(|byte| !byte.is_ascii_whitespace()))
+            }
+            Encoding::Percent => Box::new(decoded_bytes(payload)),
+            Encoding::Base45 => Box::new(payload.bytes().filter(|b| !matches!(b, b'\r' | b'\n'))),
+        };
+        PayloadChunks {
+            digits,
+            encoding,
+            // Picked on the digits, which may be percent-encoded.
+            base64_engine: base64_engine(decoded_bytes(payload)),
+            buffer: Vec::new(),
+            done: false,
+        }
+    }
+}
+
+impl Iterator for PayloadChunks<'_> {
+    type Item = Result<Vec<u8>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = match self.encoding {
+            Encoding::Percent => Ok(self.digits.by_ref().take(Self::CHUNK_LEN).collect()),
+            // Whole quanta of digits, so each chunk decodes on its own.
+            Encoding::Base64 => {
+                self.buffer.clear();
+                self.buffer
+                    .extend(self.digits.by_ref().take(Self::CHUNK_LEN / 3 * 4));
+                self.base64_engine
+                    .decode(&self.buffer)
+                    .map_err(ParseError::InvalidBase64)
+            }
+            Encoding::Base45 => {
+                self.buffer.clear();
+                self.buffer
+                    .extend(self.digits.by_ref().take(Self::CHUNK_LEN / 2 * 3));
+                let mut bytes = Vec::new();
+                std::str::from_utf8(&self.buffer)
+                    .ok()
+                    .and_then(|digits| decode_base45_into(digits, &mut bytes))
+                    .map(|()| bytes)
+                    .ok_or(ParseError::InvalidBase45)
+            }
+        };
+        match chunk {
+            Ok(chunk) if chunk.is_empty() => {
+                self.done = true;
+                None
+            }
+            Ok(chunk) => Some(Ok(chunk)),
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for PayloadChunks<'_> {}
+
+/// The payload and its encoding, without parsing the media type.
+fn split_payload(uri: &str) -> Result<(&str, Encoding), ParseError> {
+    let uri = uri.trim();
+    let scheme = uri.get(..5).ok_or(ParseError::MissingScheme)?;
+    if !scheme.eq_ignore_ascii_case("data:") {
+        return Err(ParseError::MissingScheme);
+    }
+    let comma = uri.find(',').ok_or(ParseError::MissingComma)?;
+    let (_, encoding) = split_encoding_marker(&uri[5..comma]);
+    Ok((&uri[comma + 1..], encoding))
+}
+
+/// Splits the `;base64` or `;base45` marker off the header.
+fn split_encoding_marker(header: &str) -> (&str, Encoding) {
+    if let Some(split) = header.rfind(';') {
+        let marker = header[split + 1..].trim();
+        if marker.eq_ignore_ascii_case("base64") {
+            return (&header[..split], Encoding::Base64);
+        } else if marker.eq_ignore_ascii_case("base45") {
+            return (&header[..split], Encoding::Base45);
+        }
+    }
+    (header, Encoding::Percent)
+}
+
+/// The percent-decoded payload, skipping the line breaks of
+/// [`wrap_data_uri`](crate::wrap_data_uri), which are never part of it.
+fn decoded_bytes(payload: &str) -> impl Iterator<Item = u8> + '_ {
+    payload.split(['\r', '\n']).flat_map(percent_decode_str)
+}
+
+/// The payload without line breaks, for base45 whose digits are taken as is.
+fn unwrapped(payload: &str) -> Cow<'_, str> {
+    if payload.contains(['\r', '\n']) {
+        Cow::Owned(payload.replace(['\r', '\n'], ""))
+    } else {
+        Cow::Borrowed(payload)
+    }
+}
+
+fn decoded_payload_len(payload: &str, encoding: Encoding) -> usize {
+    match encoding {
+        Encoding::Base64 => {
+            let digits = decoded_bytes(payload)
+                .filter(|byte| !byte.is_ascii_whitespace() && *byte != b'=')
+                .count();
+            digits * 3 / 4
+        }
+        Encoding::Percent => decoded_bytes(payload).count(),
+        Encoding::Base45 => {
+            let digits = payload
+                .bytes()
+                .filter(|b| !matches!(b, b'\r' | b'\n'))
+                .count();
+            digits / 3 * 2 + usize::from(digits % 3 == 2)
+        }
+    }
+}
+
+/// Base64url when the digits use its alphabet, this crate decodes both.
+fn base64_engine(mut digits: impl Iterator<Item = u8>) -> GeneralPurpose {
+    if digits.any(|byte| matches!(byte, b'-' | b'_')) {
+        Base64Alphabet::UrlSafe.engine(false)
+    } else {
+        Base64Alphabet::Standard.engine(true)
+    }
+}
+
+/// Appends the first `digits_len` digits of `payload` decoded to `out`,
+/// percent-decoding it and skipping whitespace when needed.
+fn decode_base64_payload(
+    payload: &str,
+    digits_len: usize,
+    out: &mut Vec<u8>,
+) -> Result<(), base64::DecodeError> {
+    let plain = &payload.as_bytes()[..digits_len.min(payload.len())];
+    #[cfg(feature = "simd")]
+    if let Some(decoded) = crate::simd::decode_standard(plain, out) {
+        return decoded;
+    }
+    if payload
+        .bytes()
+        .all(|byte| byte != b'%' && !byte.is_ascii_whitespace())
+    {
+        return base64_engine(plain.iter().copied()).decode_vec(plain, out);
+    }
+    // Copied URIs are often wrapped or indented.
+    let digits: Vec<u8> = decoded_bytes(payload)
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .take(digits_len)
+        .collect();
+    base64_engine(digits.iter().copied()).decode_vec(&digits, out)
+}
+
+/// Parses the header between `data:` and the comma.
+pub(crate) fn parse_header(header: &str) -> Result<(Mime, Encoding), ParseError> {
+    let (header, encoding) = split_encoding_marker(header);
+    let header = header.trim();
+    let mime = if header.is_empty() {
+        DEFAULT_MEDIA_TYPE.parse()
+    } else if header.starts_with(';') {
+        format!("text/plain{}", header).parse()
+    } else {
+        header.parse()
+    }
+    .map_err(|_| ParseError::InvalidMediaType(header.to_string()))?;
+    Ok((mime, encoding))
+}
+
+impl PartialEq for DataUri {
+    fn eq(&self, other: &DataUri) -> bool {
+        self.uri == other.uri || self.content() == other.content()
+    }
+}
+
+impl Eq for DataUri {}
+
+impl std::hash::Hash for DataUri {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.content().hash(state);
+    }
+}
+
+impl fmt::Display for DataUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return crate::summary::write_summary(self, f);
+        }
+        f.write_str(&self.uri)
+    }
+}
+
+impl fmt::Debug for DataUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return write!(f, "DataUri({:?})", format!("{:#}", self));
+        }
+        f.debug_struct("DataUri")
+            .field("uri", &self.uri)
+            .field("mime", &self.mime)
+            .field("encoding", &self.encoding)
+            .field("payload_start", &self.payload_start)
+            .finish()
+    }
+}
+
+impl std::ops::Deref for DataUri {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.uri
+    }
+}
+
+impl AsRef<str> for DataUri {
+    fn as_ref(&self) -> &str {
+        &self.uri
+    }
+}
+
+impl std::str::FromStr for DataUri {
+    type Err = ParseError;
+
+    fn from_str(uri: &str) -> Result<Self, ParseError> {
+        DataUri::parse(uri)
+    }
+}
+
+impl TryFrom<&str> for DataUri {
+    type Error = ParseError;
+
+    fn try_from(uri: &str) -> Result<Self, ParseError> {
+        DataUri::parse(uri)
+    }
+}
+
+impl TryFrom<String> for DataUri {
+    type Error = ParseError;
+
+    fn try_from(uri: String) -> Result<Self, ParseError> {
+        DataUri::parse(&uri)
+    }
+}
+
+impl From<DataUri> for String {
+    fn from(uri: DataUri) -> String {
+        uri.uri
+    }
+}
+
+impl PartialEq<str> for DataUri {
+    fn eq(&self, other: &str) -> bool {
+        self.uri == other
+    }
+}
+
+impl PartialEq<&str> for DataUri {
+    fn eq(&self, other: &&str) -> bool {
+        self.uri == *other
+    }
+}
+
+impl PartialEq<String> for DataUri {
+    fn eq(&self, other: &String) -> bool {
+        self.uri == *other
+    }
+}
+
+impl PartialEq<DataUri> for str {
+    fn eq(&self, other: &DataUri) -> bool {
+        self == other.uri
+    }
+}
+
+impl PartialEq<DataUri> for &str {
+    fn eq(&self, other: &DataUri) -> bool {
+        *self == other.uri
+    }
+}
+
+impl PartialEq<DataUri> for String {
+    fn eq(&self, other: &DataUri) -> bool {
+        *self == other.uri
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DataUri {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DataUri {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uri = String::deserialize(deserializer)?;
+        DataUri::parse(&uri).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn parse_svg_round_trip() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="M0 0h1"/></svg>"#;
+        let uri = DataUri::parse(&svg_str_to_data_uri(svg)).unwrap();
+        assert_eq!(uri.mime().essence_str(), "image/svg+xml");
+        assert_eq!(uri.encoding(), Encoding::Percent);
+        assert_eq!(uri.decode_payload().unwrap(), svg.as_bytes());
+    }
+
+    #[test]
+    fn parse_base64_with_parameters() {
+        let uri = DataUri::parse("data:text/plain;charset=utf-8;BASE64,aGVsbG8=").unwrap();
+        assert_eq!(uri.mime().essence_str(), "text/plain");
+        assert_eq!(uri.mime().get_param(mime::CHARSET).unwrap(), "utf-8");
+        assert_eq!(uri.encoding(), Encoding::Base64);
+        assert_eq!(uri.payload(), "aGVsbG8=");
+        assert_eq!(uri.decode_payload().unwrap(), b"hello");
+
+        let wrapped = DataUri::parse("data:;base64,aGVs\r\n  bG8%0A%09h").unwrap();
+        assert_eq!(wrapped.decode_payload().unwrap(), b"hello!");
+        assert_eq!(wrapped.payload_len(), 6);
+    }
+
+    #[test]
+    fn parse_default_media_type() {
+        let uri = DataUri::parse("data:,hello%20world").unwrap();
+        assert_eq!(uri.mime().essence_str(), "text/plain");
+        assert_eq!(uri.mime().get_param(mime::CHARSET).unwrap(), "US-ASCII");
+        assert_eq!(uri.decode_payload().unwrap(), b"hello world");
+
+        let uri = DataUri::parse("data:;charset=utf-8,").unwrap();
+        assert_eq!(uri.mime().get_param(mime::CHARSET).unwrap(), "utf-8");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            DataUri::parse("http://example.com"),
+            Err(ParseError::MissingScheme)
+        );
+        assert_eq!(
+            DataUri::parse("data:text/plain"),
+            Err(ParseError::MissingComma)
+        );
+        assert!(matches!(
+            DataUri::parse("data:not a mime,"),
+            Err(ParseError::InvalidMediaType(_))
+        ));
+        assert!(matches!(
+            DataUri::parse("data:;base64,@@@").unwrap().decode_payload(),
+            Err(ParseError::InvalidBase64(_))
+        ));
+    }
+
+    #[test]
+    fn string_like_accessors() {
+        let uri: DataUri = "data:text/plain;base64,aGVsbG8=".parse().unwrap();
+        assert!(uri.is_base64());
+        assert_eq!(uri.payload_len(), 5);
+        assert_eq!(uri, "data:text/plain;base64,aGVsbG8=");
+        assert_eq!(uri.to_string(), uri.as_str());
+        assert!(uri.starts_with("data:text/plain"));
+        assert_eq!(DataUri::parse("data:,a%20b").unwrap().payload_len(), 3);
+        assert_eq!(String::from(uri), "data:text/plain;base64,aGVsbG8=");
+        assert_eq!(DataUri::try_from("data:,a").unwrap(), "data:,a");
+        assert_eq!(
+            DataUri::try_from("a".to_string()),
+            Err(ParseError::MissingScheme)
+        );
+    }
+
+    #[test]
+    fn content_equality() {
+        use std::collections::HashSet;
+
+        let uris: HashSet<DataUri> = [
+            "data:text/plain;charset=UTF-8;format=flowed;base64,aGk=",
+            "data:TEXT/Plain;format=flowed;charset=utf-8,hi",
+            "data:text/plain;format=flowed;Charset=utf-8;base64,aGk",
+        ]
+        .into_iter()
+        .map(|uri| DataUri::parse(uri).unwrap())
+        .collect();
+        assert_eq!(uris.len(), 1);
+        assert_eq!(
+            uris.iter().next().unwrap().normalized_mime(),
+            "text/plain;charset=utf-8;format=flowed"
+        );
+        assert_ne!(
+            DataUri::parse("data:text/plain,hi").unwrap(),
+            DataUri::parse("data:text/css,hi").unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_in_chunks() {
+        let pdf: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let uri = bytes_to_data_uri(&pdf, MediaType::Pdf);
+        let chunks: Vec<Vec<u8>> = uri.payload_chunks().map(Result::unwrap).collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), pdf);
+        let uri = DataUri::parse("data:;base45,BB8").unwrap();
+        assert_eq!(uri.payload_chunks().next(), Some(Ok(b"AB".to_vec())));
+
+        let uri = DataUri::parse("data:;base64,aGk=aGk=").unwrap();
+        let mut chunks = uri.payload_chunks();
+        assert!(matches!(
+            chunks.next(),
+            Some(Err(ParseError::InvalidBase64(_)))
+        ));
+        assert_eq!(chunks.next(), None);
+        assert_eq!(
+            DataUri::parse("data:,").unwrap().payload_chunks().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn decode_into_buffer() {
+        let mut buffer = b"kept".to_vec();
+        decode_payload_into("DATA:;base64,aGVs\nbG8%3D", &mut buffer).unwrap();
+        assert_eq!(buffer, b"kepthello");
+        assert!(decode_payload_into("data:;base64,a", &mut buffer).is_err());
+        assert!(decode_payload_into("data:;base45,:::", &mut buffer).is_err());
+        assert_eq!(buffer, b"kepthello");
+        buffer.clear();
+        decode_payload_into("data:;base45,BB8", &mut buffer).unwrap();
+        assert_eq!(buffer, b"AB");
+        assert_eq!(decoded_len("data:;base64,aGVs\nbG8%3D"), Ok(5));
+        assert_eq!(decoded_len("data:,%3Csvg%3E"), Ok(5));
+        assert_eq!(decoded_len("http:,a"), Err(ParseError::MissingScheme));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_string() {
+        let uri = DataUri::parse("data:text/plain;base64,aGk=").unwrap();
+        let json = serde_json::to_string(&uri).unwrap();
+        assert_eq!(json, "\"data:text/plain;base64,aGk=\"");
+        assert_eq!(serde_json::from_str::<DataUri>(&json).unwrap(), uri);
+        assert!(serde_json::from_str::<DataUri>("\"hi\"").is_err());
+    }
+}
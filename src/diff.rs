@@ -0,0 +1,170 @@
+use crate::{DataUri, Result};
+use mime::Mime;
+use std::fmt;
+
+/// How two data URIs differ, see [`diff_data_uris`]. Its `Display` lists the
+/// differences one per line, for assertion messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUriDiff {
+    /// Both media types, when they differ.
+    pub mime: Option<(Mime, Mime)>,
+    /// How the decoded payloads differ, `None` when they are identical.
+    pub payload: Option<PayloadDiff>,
+    /// How the pixels differ, when both payloads differ and decode as images.
+    #[cfg(feature = "image")]
+    pub pixels: Option<PixelDiff>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PayloadDiff {
+    /// Decoded lengths of both payloads.
+    pub len: (usize, usize),
+    /// Offset of the first differing byte.
+    pub first_difference: usize,
+}
+
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelDiff {
+    /// Width and height of both images.
+    pub dimensions: ((u32, u32), (u32, u32)),
+    /// Pixels whose RGBA value differs, pixels covered by only one image
+    /// included.
+    pub differing_pixels: u64,
+    /// `x`, `y`, width and height of the smallest rectangle holding every
+    /// differing pixel.
+    pub bounds: Option<(u32, u32, u32, u32)>,
+}
+
+impl DataUriDiff {
+    /// Whether both data URIs have the same media type and content.
+    pub fn is_empty(&self) -> bool {
+        self.mime.is_none() && self.payload.is_none()
+    }
+}
+
+impl fmt::Display for DataUriDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "identical");
+        }
+        let mut lines = Vec::new();
+        if let Some((a, b)) = &self.mime {
+            lines.push(format!("media type: `{}` != `{}`", a, b));
+        }
+        if let Some(payload) = &self.payload {
+            lines.push(format!(
+                "payload: {} != {} bytes, first difference at byte {}",
+                payload.len.0, payload.len.1, payload.first_difference
+            ));
+        }
+        #[cfg(feature = "image")]
+        if let Some(pixels) = &self.pixels {
+            let ((width_a, height_a), (width_b, height_b)) = pixels.dimensions;
+            if pixels.dimensions.0 != pixels.dimensions.1 {
+                lines.push(format!(
+                    "dimensions: {}x{} != {}x{}",
+                    width_a, height_a, width_b, height_b
+                ));
+            }
+            match pixels.bounds {
+                Some((x, y, width, height)) => lines.push(format!(
+                    "pixels: {} differ, within {}x{} at ({}, {})",
+                    pixels.differing_pixels, width, height, x, y
+                )),
+                None => lines.push("pixels: identical".to_string()),
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Compares two data URIs by media type and decoded payload, so differently
+/// encoded but equal ones compare equal, and by pixels when both are images.
+///
+/// ```
+/// let a = "data:text/plain,hello";
+/// let diff = data_uri_utils::diff_data_uris(a, "data:text/plain;base64,aGVsbG8=")?;
+/// assert!(diff.is_empty());
+/// let diff = data_uri_utils::diff_data_uris(a, "data:text/css,help")?;
+/// assert_eq!(
+///     diff.to_string(),
+///     "media type: `text/plain` != `text/css`\npayload: 5 != 4 bytes, first difference at byte 3"
+/// );
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn diff_data_uris(a: &str, b: &str) -> Result<DataUriDiff> {
+    let (a, b) = (DataUri::parse(a)?, DataUri::parse(b)?);
+    let mime = (a.mime() != b.mime()).then(|| (a.mime().clone(), b.mime().clone()));
+    let (bytes_a, bytes_b) = (a.decode_payload()?, b.decode_payload()?);
+    let payload = (bytes_a != bytes_b).then(|| PayloadDiff {
+        len: (bytes_a.len(), bytes_b.len()),
+        first_difference: bytes_a
+            .iter()
+            .zip(&bytes_b)
+            .position(|(a, b)| a != b)
+            .unwrap_or(bytes_a.len().min(bytes_b.len())),
+    });
+    Ok(DataUriDiff {
+        mime,
+        #[cfg(feature = "image")]
+        pixels: payload.and_then(|_| pixel_diff(&a, &b)),
+        payload,
+    })
+}
+
+#[cfg(feature = "image")]
+fn pixel_diff(a: &DataUri, b: &DataUri) -> Option<PixelDiff> {
+    let a = crate::data_uri_to_image(a).ok()?.into_rgba8();
+    let b = crate::data_uri_to_image(b).ok()?.into_rgba8();
+    let mut differing_pixels = 0;
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for y in 0..a.height().max(b.height()) {
+        for x in 0..a.width().max(b.width()) {
+            if a.get_pixel_checked(x, y) == b.get_pixel_checked(x, y) {
+                continue;
+            }
+            differing_pixels += 1;
+            let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+            bounds = Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+        }
+    }
+    Some(PixelDiff {
+        dimensions: (a.dimensions(), b.dimensions()),
+        differing_pixels,
+        bounds: bounds.map(|(min_x, min_y, max_x, max_y)| {
+            (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+        }),
+    })
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn diff_images() {
+        let a = image::RgbImage::from_pixel(8, 8, image::Rgb([10, 20, 30]));
+        let mut b = a.clone();
+        b.put_pixel(2, 5, image::Rgb([0, 0, 0]));
+        b.put_pixel(4, 6, image::Rgb([0, 0, 0]));
+        let (a, b) = (
+            image_to_png_data_uri(&a).unwrap(),
+            image_to_png_data_uri(&b).unwrap(),
+        );
+        let diff = diff_data_uris(&a, &b).unwrap();
+        assert_eq!(diff.mime, None);
+        let pixels = diff.pixels.unwrap();
+        assert_eq!(pixels.differing_pixels, 2);
+        assert_eq!(pixels.bounds, Some((2, 5, 3, 2)));
+        assert!(diff
+            .to_string()
+            .ends_with("pixels: 2 differ, within 3x2 at (2, 5)"));
+
+        let bmp = transcode_data_uri(&a, Format::Bmp, &EncodeOptions::default()).unwrap();
+        let diff = diff_data_uris(&a, &bmp).unwrap();
+        assert!(diff.mime.is_some() && diff.payload.is_some());
+        assert_eq!(diff.pixels.unwrap().bounds, None);
+        assert!(diff_data_uris(&a, &a).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{DataUri, Result};
+use sha2::{Digest, Sha256};
+
+/// SHA-256 of the normalized media type and decoded payload, a cache key
+/// that stays the same however the data URI is encoded.
+///
+/// ```
+/// use data_uri_utils::data_uri_digest;
+///
+/// assert_eq!(
+///     data_uri_digest("data:image/svg+xml,%3Csvg%2F%3E")?,
+///     data_uri_digest("data:Image/SVG+XML;base64,PHN2Zy8+")?,
+/// );
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn data_uri_digest(uri: &str) -> Result<[u8; 32]> {
+    let uri = DataUri::parse(uri)?;
+    let mut hasher = Sha256::new();
+    hasher.update(uri.normalized_mime());
+    // The media type can't contain a NUL, so the boundary is unambiguous.
+    hasher.update([0]);
+    hasher.update(uri.decode_payload()?);
+    Ok(hasher.finalize().into())
+}
+
+/// SHA-256 of the decoded payload alone, the same as hashing the original file.
+pub fn data_uri_payload_digest(uri: &str) -> Result<[u8; 32]> {
+    let payload = DataUri::parse(uri)?.decode_payload()?;
+    Ok(Sha256::digest(payload).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn digests() {
+        let uri = "data:text/plain;base64,aGk=";
+        assert_eq!(
+            data_uri_payload_digest(uri).unwrap()[..4],
+            [0x8f, 0x43, 0x43, 0x46]
+        );
+        assert_ne!(
+            data_uri_digest(uri).unwrap(),
+            data_uri_digest("data:text/css;base64,aGk=").unwrap()
+        );
+        assert_eq!(
+            data_uri_payload_digest(uri).unwrap(),
+            data_uri_payload_digest("data:text/css,hi").unwrap()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::svg::svg_dimensions;
+use crate::{sniff_mime, DataUri, Error, Result};
+
+/// Size of the first decoded prefix, doubled each time the header is longer.
+const PREFIX_LEN: usize = 512;
+
+/// Width and height of the PNG, JPEG, GIF or SVG image in the data URI.
+///
+/// Only a prefix of the payload large enough for the image header is
+/// decoded, which makes it cheap to emit `width` and `height` attributes for
+/// many embedded images. SVG sizes are rounded up to whole pixels.
+///
+/// ```
+/// let png = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAAD";
+/// assert_eq!(data_uri_utils::data_uri_dimensions(png)?, (2, 3));
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn data_uri_dimensions(uri: &str) -> Result<(u32, u32)> {
+    let uri = DataUri::parse(uri)?;
+    let payload_len = uri.payload_len();
+    let mut len = PREFIX_LEN;
+    loop {
+        let prefix = uri.decode_payload_prefix(len)?;
+        let complete = len >= payload_len;
+        match probe(&prefix) {
+            Probe::Found(dimensions) => return Ok(dimensions),
+            Probe::Truncated if !complete => len = len.saturating_mul(2),
+            Probe::Truncated => return Err(Error::MissingDimensions),
+            Probe::Unknown => return Err(Error::UnsupportedMediaType(uri.mime().clone())),
+        }
+    }
+}
+
+enum Probe {
+    Found((u32, u32)),
+    /// The header is incomplete or doesn't give a size.
+    Truncated,
+    Unknown,
+}
+
+fn probe(bytes: &[u8]) -> Probe {
+    match sniff_mime(bytes).as_ref().map(mime::Mime::essence_str) {
+        Some("image/png") => found(png_dimensions(bytes)),
+        Some("image/gif") => found(gif_dimensions(bytes)),
+        Some("image/jpeg") => found(jpeg_dimensions(bytes)),
+        Some("image/svg+xml") => {
+            let svg = String::from_utf8_lossy(bytes);
+            found(
+                svg_dimensions(&svg)
+                    .map(|(width, height)| (width.ceil() as u32, height.ceil() as u32)),
+            )
+        }
+        // A prefix may cut an SVG before its root element, only the full
+        // payload is known not to be one.
+        _ if bytes.trim_ascii_start().starts_with(b"<")
+            || bytes.starts_with("\u{FEFF}".as_bytes()) =>
+        {
+            Probe::Truncated
+        }
+        _ => Probe::Unknown,
+    }
+}
+
+fn found(dimensions: Option<(u32, u32)>) -> Probe {
+    dimensions.map_or(Probe::Truncated, Probe::Found)
+}
+
+/// From the `IHDR` chunk, which comes first.
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let header = bytes.get(12..24)?;
+    let be_u32 = |bytes: &[u8]| u32::from_be_bytes(bytes.try_into().unwrap());
+    header
+        .starts_with(b"IHDR")
+        .then(|| (be_u32(&header[4..8]), be_u32(&header[8..12])))
+}
+
+/// From the logical screen descriptor following the signature.
+fn gif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let screen = bytes.get(6..10)?;
+    Some((
+        u16::from_le_bytes([screen[0], screen[1]]).into(),
+        u16::from_le_bytes([screen[2], screen[3]]).into(),
+    ))
+}
+
+/// From the first start of frame segment, skipping the others by length.
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut position = 2;
+    loop {
+        while *bytes.get(position)? == 0xff && *bytes.get(position + 1)? == 0xff {
+            position += 1;
+        }
+        let [0xff, marker] = *bytes.get(position..position + 2)? else {
+            return None;
+        };
+        match marker {
+            // Standalone markers without a length.
+            0x01 | 0xd0..=0xd8 => position += 2,
+            // SOF0 to SOF15, except DHT, JPG and DAC.
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let frame = bytes.get(position + 5..position + 9)?;
+                let height = u16::from_be_bytes([frame[0], frame[1]]);
+                let width = u16::from_be_bytes([frame[2], frame[3]]);
+                return Some((width.into(), height.into()));
+            }
+            _ => {
+                let segment = bytes.get(position + 2..position + 4)?;
+                position += 2 + usize::from(u16::from_be_bytes([segment[0], segment[1]]));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn header_dimensions() {
+        let gif = bytes_to_data_uri(b"GIF89a\x20\x01\x10\0\0\0\0", &mime::IMAGE_GIF);
+        assert_eq!(data_uri_dimensions(&gif).unwrap(), (288, 16));
+
+        // An APP1 segment larger than the first prefix, then SOF2.
+        let mut jpeg = b"\xff\xd8\xff\xe1\x04\x02".to_vec();
+        jpeg.resize(jpeg.len() + 0x400, 0);
+        jpeg.extend_from_slice(b"\xff\xc2\0\x11\x08\x01\xe0\x02\x80\x03");
+        let uri = bytes_to_data_uri(&jpeg, &mime::IMAGE_JPEG);
+        assert_eq!(data_uri_dimensions(&uri).unwrap(), (640, 480));
+        assert!(matches!(
+            data_uri_dimensions(&bytes_to_data_uri(&jpeg[..100], &mime::IMAGE_JPEG)),
+            Err(Error::MissingDimensions)
+        ));
+
+        let svg = format!(
+            "<!-- {} --><svg width=\"10.5\" viewBox=\"0 0 2 1\"/>",
+            "x".repeat(2000)
+        );
+        let uri = svg_str_to_data_uri(&svg);
+        assert_eq!(data_uri_dimensions(&uri).unwrap(), (11, 6));
+        assert!(matches!(
+            data_uri_dimensions("data:,hello"),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{file_to_data_uri, inline_html_images, inline_markdown_images, DataUri, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The files of an asset directory as data URIs, to make generated
+/// documentation self-contained.
+///
+/// ```no_run
+/// use data_uri_utils::DocAssets;
+///
+/// let assets = DocAssets::from_dir("docs/images")?;
+/// let guide = assets.inline_markdown(&std::fs::read_to_string("docs/guide.md")?);
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DocAssets {
+    uris: BTreeMap<String, DataUri>,
+}
+
+impl DocAssets {
+    /// Reads every file under `dir`, keyed by its path relative to `dir`
+    /// with `/` separators.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut assets = DocAssets::default();
+        assets.add_dir(dir.as_ref(), "")?;
+        Ok(assets)
+    }
+
+    fn add_dir(&mut self, dir: &Path, prefix: &str) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            if entry.file_type()?.is_dir() {
+                self.add_dir(&entry.path(), &format!("{}/", name))?;
+            } else {
+                self.uris.insert(name, file_to_data_uri(entry.path())?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Relative path to data URI.
+    pub fn uris(&self) -> &BTreeMap<String, DataUri> {
+        &self.uris
+    }
+
+    /// The data URI of a link destination. It matches a file by its
+    /// relative path, or by its trailing components so that `./logo.png` and
+    /// `../images/logo.png` both match `logo.png`; the longest match wins.
+    pub fn get(&self, destination: &str) -> Option<&DataUri> {
+        let path = destination.split(['?', '#']).next().unwrap_or(destination);
+        let path = path.trim_start_matches("./");
+        self.uris.get(path).or_else(|| {
+            self.uris
+                .iter()
+                .filter(|(name, _)| {
+                    path.strip_suffix(name.as_str())
+                        .is_some_and(|rest| rest.ends_with('/'))
+                })
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, uri)| uri)
+        })
+    }
+
+    /// [`inline_markdown_images`] with these assets.
+    pub fn inline_markdown(&self, md: &str) -> String {
+        inline_markdown_images(md, |destination| self.bytes(destination))
+    }
+
+    /// [`inline_html_images`] with these assets.
+    pub fn inline_html(&self, html: &str) -> String {
+        inline_html_images(html, |destination| self.bytes(destination))
+    }
+
+    fn bytes(&self, destination: &str) -> Option<Vec<u8>> {
+        self.get(destination)?.decode_payload().ok()
+    }
+}
+
+/// An mdBook preprocessor inlining the local images of every chapter, read
+/// relative to the chapter source. It reads the `[context, book]` JSON mdBook
+/// gives on `input` and writes the book back to `output`.
+///
+/// The preprocessor binary runs it, and exits successfully when called as
+/// `supports <renderer>`:
+///
+/// ```no_run
+/// fn main() -> Result<(), data_uri_utils::Error> {
+///     if std::env::args().nth(1).as_deref() == Some("supports") {
+///         return Ok(());
+///     }
+///     data_uri_utils::run_mdbook_preprocessor(std::io::stdin(), std::io::stdout())
+/// }
+/// ```
+#[cfg(feature = "mdbook")]
+pub fn run_mdbook_preprocessor(
+    input: impl std::io::Read,
+    mut output: impl std::io::Write,
+) -> Result<()> {
+    use serde_json::Value;
+
+    fn inline_chapters(items: &mut [Value], src: &Path) {
+        for item in items {
+            let Some(chapter) = item.get_mut("Chapter") else {
+                continue;
+            };
+            let dir = chapter
+                .get("source_path")
+                .or_else(|| chapter.get("path"))
+                .and_then(Value::as_str)
+                .and_then(|path| Path::new(path).parent())
+                .map_or_else(|| src.to_path_buf(), |parent| src.join(parent));
+            if let Some(Value::String(content)) = chapter.get_mut("content") {
+                *content = inline_markdown_images(content, |destination| {
+                    let path = destination.split(['?', '#']).next()?;
+                    if path.contains("://") || path.starts_with('/') {
+                        return None;
+                    }
+                    std::fs::read(dir.join(path)).ok()
+                });
+            }
+            if let Some(Value::Array(sub_items)) = chapter.get_mut("sub_items") {
+                inline_chapters(sub_items, src);
+            }
+        }
+    }
+
+    let (context, mut book): (Value, Value) =
+        serde_json::from_reader(input).map_err(std::io::Error::from)?;
+    let root = context["root"].as_str().unwrap_or(".");
+    let src = Path::new(root).join(context["config"]["book"]["src"].as_str().unwrap_or("src"));
+    if let Some(Value::Array(sections)) = book.get_mut("sections") {
+        inline_chapters(sections, &src);
+    }
+    serde_json::to_writer(&mut output, &book).map_err(std::io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn doc_assets() {
+        let dir = std::env::temp_dir().join(format!("data-uri-docs-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("src/images")).unwrap();
+        std::fs::write(dir.join("src/images/logo.svg"), "<svg/>").unwrap();
+
+        let assets = DocAssets::from_dir(dir.join("src")).unwrap();
+        assert_eq!(
+            assets.uris().keys().collect::<Vec<_>>(),
+            ["images/logo.svg"]
+        );
+        assert!(assets.get("../images/logo.svg?v=2").is_some());
+        assert!(assets.get("logo.svg").is_none());
+        assert_eq!(
+            assets.inline_html(r#"<img src="./images/logo.svg">"#),
+            r#"<img src="data:image/svg+xml;base64,PHN2Zy8+">"#
+        );
+
+        #[cfg(feature = "mdbook")]
+        {
+            let input = serde_json::json!([
+                {"root": dir, "config": {"book": {"src": "src"}}},
+                {"sections": [{"Chapter": {
+                    "content": "![logo](../src/images/logo.svg) ![remote](https://example.com/a.png)",
+                    "path": "intro.md",
+                    "sub_items": [],
+                }}, "Separator"]},
+            ]);
+            let mut output = Vec::new();
+            run_mdbook_preprocessor(input.to_string().as_bytes(), &mut output).unwrap();
+            let book: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            assert_eq!(
+                book["sections"][0]["Chapter"]["content"],
+                "![logo](data:image/svg+xml;base64,PHN2Zy8+) ![remote](https://example.com/a.png)"
+            );
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+use crate::{bytes_to_data_uri, DataUri, DataUriBuilder, Encoding};
+
+/// A base64 `application/pdf` data URI, suitable for `<iframe>` and `<embed>`.
+///
+/// Browsers refuse to navigate to data URIs at the top level, so these only
+/// display when embedded.
+pub fn pdf_to_data_uri(bytes: &[u8]) -> DataUri {
+    bytes_to_data_uri(bytes, &mime::APPLICATION_PDF)
+}
+
+/// A percent-encoded `text/html;charset=utf-8` data URI.
+///
+/// The charset parameter takes precedence over any `<meta charset>` in the
+/// document, which matches the UTF-8 the payload is encoded with.
+pub fn html_to_data_uri(html: &str) -> DataUri {
+    DataUriBuilder::new()
+        .media_type(&mime::TEXT_HTML)
+        .charset("utf-8")
+        .encoding(Encoding::Percent)
+        .build_str(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn documents() {
+        assert_eq!(
+            pdf_to_data_uri(b"%PDF-1.7"),
+            "data:application/pdf;base64,JVBERi0xLjc="
+        );
+        let uri = html_to_data_uri("<p>Café</p>");
+        assert_eq!(
+            uri,
+            "data:text/html;charset=utf-8,%3Cp%3ECaf%C3%A9%3C%2Fp%3E"
+        );
+        assert_eq!(
+            DataUri::parse(&uri).unwrap().decode_payload().unwrap(),
+            "<p>Café</p>".as_bytes()
+        );
+    }
+}
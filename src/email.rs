@@ -0,0 +1,119 @@
+use crate::html::reference_values;
+use crate::{bytes_to_data_uri, DataUri, MediaType};
+use mime::Mime;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+
+/// An inline part of a `multipart/related` email, referenced from the HTML
+/// part as `cid:` followed by its content ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    /// The `Content-ID` header value, without its angle brackets.
+    pub content_id: String,
+    pub filename: String,
+    pub mime: Mime,
+    pub bytes: Vec<u8>,
+}
+
+/// Moves the data URIs of an HTML email into attachments, which many email
+/// clients require, replacing them with `cid:` references.
+///
+/// Identical data URIs share an attachment. Content IDs are unique within
+/// the message only, as `part1@data-uri-utils`, `part2@data-uri-utils`…
+///
+/// ```
+/// let html = r#"<img src="data:image/gif;base64,R0lGODlh"><img src="data:image/gif;base64,R0lGODlh">"#;
+/// let (html, attachments) = data_uri_utils::html_data_uris_to_cid(html);
+/// assert_eq!(html, r#"<img src="cid:part1@data-uri-utils"><img src="cid:part1@data-uri-utils">"#);
+/// assert_eq!(attachments[0].filename, "part1.gif");
+/// assert_eq!(attachments[0].bytes, b"GIF89a");
+/// ```
+pub fn html_data_uris_to_cid(html: &str) -> (String, Vec<Attachment>) {
+    let mut attachments: Vec<Attachment> = Vec::new();
+    let mut content_ids = HashMap::new();
+    let mut converted = String::with_capacity(html.len());
+    let mut position = 0;
+    for (range, value) in reference_values(html) {
+        let Ok(uri) = DataUri::parse(&value) else {
+            continue;
+        };
+        let Ok(bytes) = uri.decode_payload() else {
+            continue;
+        };
+        let content_id = content_ids.entry(uri.clone()).or_insert_with(|| {
+            let part = attachments.len() + 1;
+            let extension = uri.media_type().extension().unwrap_or("bin");
+            let content_id = format!("part{}@data-uri-utils", part);
+            attachments.push(Attachment {
+                content_id: content_id.clone(),
+                filename: format!("part{}.{}", part, extension),
+                mime: uri.mime().clone(),
+                bytes,
+            });
+            content_id
+        });
+        converted.push_str(&html[position..range.start]);
+        converted.push_str("cid:");
+        converted.push_str(content_id);
+        position = range.end;
+    }
+    converted.push_str(&html[position..]);
+    (converted, attachments)
+}
+
+/// Replaces the `cid:` references of an HTML email with data URIs of the
+/// matching attachments, to display it outside of a mail client. References
+/// to unknown content IDs are kept.
+pub fn html_cid_to_data_uris(html: &str, attachments: &[Attachment]) -> String {
+    let mut converted = String::with_capacity(html.len());
+    let mut position = 0;
+    for (range, value) in reference_values(html) {
+        let value = value.trim();
+        let Some(content_id) = value
+            .get(..4)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("cid:"))
+            .map(|_| percent_decode_str(&value[4..]).decode_utf8_lossy())
+        else {
+            continue;
+        };
+        let Some(attachment) = attachments.iter().find(|attachment| {
+            let id = attachment.content_id.trim_start_matches('<');
+            id.trim_end_matches('>') == content_id
+        }) else {
+            continue;
+        };
+        let media_type = MediaType::from(&attachment.mime);
+        converted.push_str(&html[position..range.start]);
+        converted.push_str(&bytes_to_data_uri(&attachment.bytes, media_type));
+        position = range.end;
+    }
+    converted.push_str(&html[position..]);
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn cid_round_trip() {
+        let png = bytes_to_data_uri(b"\x89PNG\r\n\x1a\n", &mime::IMAGE_PNG);
+        let html = format!(
+            "<style>p {{ background: url({png}) }}</style><p style=\"background: url(&quot;data:font/woff2;base64,d09GMg==&quot;)\"><img src='{png}'><a href=\"cid:other\">"
+        );
+        let (email, attachments) = html_data_uris_to_cid(&html);
+        assert_eq!(
+            email,
+            "<style>p { background: url(cid:part1@data-uri-utils) }</style><p style=\"background: url(&quot;cid:part2@data-uri-utils&quot;)\"><img src='cid:part1@data-uri-utils'><a href=\"cid:other\">"
+        );
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0].filename, "part1.png");
+        assert_eq!(attachments[0].mime, mime::IMAGE_PNG);
+        assert_eq!(attachments[1].filename, "part2.woff2");
+        assert_eq!(attachments[1].bytes, b"wOF2");
+
+        assert_eq!(html_cid_to_data_uris(&email, &attachments), html);
+        let partial = html_cid_to_data_uris(&email, &attachments[1..]);
+        assert_eq!(partial.matches("cid:").count(), 3);
+    }
+}
@@ -0,0 +1,518 @@
+//! The building blocks of the encoders, for pipelines the high level
+//! functions don't cover. Everything here only needs `alloc`, so it also
+//! builds without the `std` feature.
+//!
+//! ```
+//! use data_uri_utils::encoding::{
+//!     collapse_whitespace, encode_percent, trim_byte_order_mark, EncodeSet,
+//! };
+//!
+//! let css = "\u{FEFF}a {\n  color: red;\n}\n";
+//! let css = collapse_whitespace(trim_byte_order_mark(css).trim());
+//! let uri = format!("data:text/css,{}", encode_percent(css.as_bytes(), EncodeSet::Minimal));
+//! assert_eq!(uri, "data:text/css,a %7B color: red; %7D");
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use percent_encoding::{percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
+
+use crate::escape::{self, NON_ALPHANUMERIC_MASK, SVG_MINIMAL_MASK, WHITESPACE_MASK};
+
+/// Characters that cannot appear literally in an SVG data URI.
+const SVG_MINIMAL: &AsciiSet = &CONTROLS
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// RFC 9285 alphabet, the characters of the QR code alphanumeric mode.
+const BASE45: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// How the payload of a data URI is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base64,
+    Percent,
+    /// RFC 9285 base45, which only uses characters of the QR code
+    /// alphanumeric mode.
+    ///
+    /// This is an extension understood by this crate alone, not browsers. The
+    /// payload keeps its spaces and `%` literally and is never percent-decoded.
+    Base45,
+}
+
+/// Alphabet of base64 payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Base64Alphabet {
+    /// RFC 4648 base64 with `+`, `/` and padding, what browsers expect.
+    #[default]
+    Standard,
+    /// RFC 4648 base64url with `-` and `_` and without padding, for systems
+    /// that choke on `+` and `/`. This crate decodes both alphabets.
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    /// The engine encoding with this alphabet, to encode other values like
+    /// the data URIs. [`Base64Alphabet::UrlSafe`] ignores `pad`. It decodes
+    /// with or without padding, as this crate does.
+    pub fn engine(self, pad: bool) -> GeneralPurpose {
+        let (alphabet, pad) = match self {
+            Base64Alphabet::Standard => (&base64::alphabet::STANDARD, pad),
+            Base64Alphabet::UrlSafe => (&base64::alphabet::URL_SAFE, false),
+        };
+        let config = GeneralPurposeConfig::new()
+            .with_encode_padding(pad)
+            .with_decode_padding_mode(DecodePaddingMode::Indifferent);
+        GeneralPurpose::new(alphabet, config)
+    }
+
+    /// Length of `payload_len` bytes once encoded by [`encode_base64`].
+    pub fn encoded_len(self, pad: bool, payload_len: usize) -> usize {
+        if pad && self == Base64Alphabet::Standard {
+            payload_len.div_ceil(3) * 4
+        } else {
+            (payload_len * 4).div_ceil(3)
+        }
+    }
+
+    /// Largest payload whose encoding fits in `encoded_len` characters.
+    #[cfg(feature = "std")]
+    pub(crate) fn max_payload_len(self, pad: bool, encoded_len: usize) -> usize {
+        if pad && self == Base64Alphabet::Standard {
+            encoded_len / 4 * 3
+        } else {
+            encoded_len * 3 / 4
+        }
+    }
+}
+
+/// Which characters get percent-encoded in textual payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncodeSet {
+    /// Everything but ASCII letters and digits.
+    #[default]
+    NonAlphanumeric,
+    /// Only the characters that would otherwise break the URI, this produces
+    /// much shorter SVG data URIs.
+    Minimal,
+    /// A caller provided set, e.g. to leave `/` and `:` readable:
+    ///
+    /// ```
+    /// use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+    ///
+    /// const READABLE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'/').remove(b':');
+    /// let uri = data_uri_utils::encode_svg_data_uri(
+    ///     "<a>http://b/</a>",
+    ///     data_uri_utils::EncodeSet::Custom(READABLE),
+    /// );
+    /// assert_eq!(uri, "data:image/svg+xml,%3Ca%3Ehttp://b/%3C/a%3E");
+    /// ```
+    Custom(&'static AsciiSet),
+}
+
+impl EncodeSet {
+    pub(crate) fn ascii_set(self) -> &'static AsciiSet {
+        match self {
+            EncodeSet::NonAlphanumeric => NON_ALPHANUMERIC,
+            EncodeSet::Minimal => SVG_MINIMAL,
+            EncodeSet::Custom(set) => set,
+        }
+    }
+
+    /// The ASCII bytes this set escapes, bit `n` standing for byte `n`.
+    pub(crate) fn ascii_mask(self) -> u128 {
+        match self {
+            EncodeSet::NonAlphanumeric => NON_ALPHANUMERIC_MASK,
+            EncodeSet::Minimal => SVG_MINIMAL_MASK,
+            EncodeSet::Custom(set) => probe_ascii_mask(set),
+        }
+    }
+}
+
+fn probe_ascii_mask(set: &'static AsciiSet) -> u128 {
+    (0..128u8)
+        .filter(|byte| {
+            percent_encode(&[*byte], set)
+                .next()
+                .is_some_and(|s| s.len() > 1)
+        })
+        .fold(0, |mask, byte| mask | 1 << byte)
+}
+
+/// Assembles `data:<media_type>[;base64],<payload>`.
+///
+/// The media type, parameters included, is written as is and percent payloads
+/// escape everything but ASCII letters and digits. Unlike `DataUriBuilder`
+/// this doesn't need the `std` feature.
+pub fn encode_data_uri(media_type: &str, bytes: &[u8], encoding: Encoding) -> String {
+    let header_len = "data:".len() + media_type.len() + ";base64,".len();
+    let payload_len = encoded_len_hint(bytes, encoding, EncodeSet::NonAlphanumeric);
+    let mut uri = String::with_capacity(header_len + payload_len);
+    uri.push_str("data:");
+    uri.push_str(media_type);
+    push_header_end(&mut uri, encoding);
+    push_payload(
+        &mut uri,
+        bytes,
+        encoding,
+        EncodeSet::NonAlphanumeric,
+        Base64Alphabet::Standard,
+        true,
+    );
+    uri
+}
+
+/// Percent-encoded SVG data URI, as built by `svg_str_to_data_uri_with`
+/// without minification, single quotes or charset, and with whitespace
+/// collapsed everywhere, text content included.
+pub fn encode_svg_data_uri(svg: &str, encode_set: EncodeSet) -> String {
+    let svg = trim_byte_order_mark(svg).trim();
+    let mut uri = String::with_capacity(19 + svg.len() * 3 / 2);
+    uri.push_str("data:image/svg+xml,");
+    encode_svg_payload::<()>(svg, encode_set, false, |part| {
+        uri.push_str(part);
+        Ok(())
+    })
+    .unwrap();
+    uri
+}
+
+/// Collapses whitespace runs, optionally swaps double quotes for single
+/// quotes, and percent-encodes `svg` in a single pass.
+///
+/// The output is gathered in a stack buffer handed to `emit` when full, so
+/// most bytes only cost a table lookup and a copy.
+///
+/// ```
+/// use data_uri_utils::encoding::{encode_svg_payload, EncodeSet};
+///
+/// let mut payload = String::new();
+/// encode_svg_payload("<svg>\n  <g id=\"a\"/>\n</svg>", EncodeSet::Minimal, true, |part| {
+///     payload.push_str(part);
+///     Ok::<_, ()>(())
+/// })
+/// .unwrap();
+/// assert_eq!(payload, "%3Csvg%3E %3Cg id='a'/%3E %3C/svg%3E");
+/// ```
+pub fn encode_svg_payload<E>(
+    svg: &str,
+    encode_set: EncodeSet,
+    swap_quotes: bool,
+    emit: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    encode_svg_segment(svg, encode_set, swap_quotes, true, emit)
+}
+
+/// [`encode_svg_payload`], leaving whitespace as is unless `collapse` is set.
+pub(crate) fn encode_svg_segment<E>(
+    svg: &str,
+    encode_set: EncodeSet,
+    swap_quotes: bool,
+    collapse: bool,
+    emit: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    escape::encode_svg_segment(svg, encode_set.ascii_mask(), swap_quotes, collapse, emit)
+}
+
+/// Percent-encodes `bytes`, non-ASCII bytes always being escaped.
+pub fn encode_percent(bytes: &[u8], encode_set: EncodeSet) -> String {
+    let mut encoded = String::with_capacity(percent_encoded_len(bytes, encode_set));
+    extend_percent(&mut encoded, bytes, encode_set);
+    encoded
+}
+
+/// Appends `bytes` percent-encoded, trailing whitespace included whatever
+/// the set, as parsers trim it off the end of URIs.
+fn extend_percent(uri: &mut String, bytes: &[u8], encode_set: EncodeSet) {
+    let (body, tail) = bytes.split_at(bytes.len() - trailing_whitespace_len(bytes));
+    uri.extend(percent_encode(body, encode_set.ascii_set()));
+    uri.extend(percent_encode(tail, NON_ALPHANUMERIC));
+}
+
+fn trailing_whitespace_len(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .rev()
+        .take_while(|&&byte| byte < 128 && WHITESPACE_MASK & 1 << byte != 0)
+        .count()
+}
+
+/// Length of the payload of `bytes` once encoded with `encoding`, to allocate
+/// the output once. It is exact for percent-encoding, which scans `bytes`,
+/// base45 and padded base64, and an upper bound for unpadded base64.
+///
+/// ```
+/// use data_uri_utils::{encoded_len_hint, EncodeSet, Encoding};
+///
+/// assert_eq!(encoded_len_hint(b"<svg/>", Encoding::Percent, EncodeSet::Minimal), 10);
+/// assert_eq!(encoded_len_hint(&[0; 1000], Encoding::Base64, EncodeSet::Minimal), 1336);
+/// ```
+pub fn encoded_len_hint(bytes: &[u8], encoding: Encoding, encode_set: EncodeSet) -> usize {
+    match encoding {
+        Encoding::Base64 => bytes.len().div_ceil(3) * 4,
+        Encoding::Percent => percent_encoded_len(bytes, encode_set),
+        Encoding::Base45 => bytes.len() / 2 * 3 + bytes.len() % 2 * 2,
+    }
+}
+
+/// Length of `bytes` once percent-encoded by [`encode_percent`].
+pub(crate) fn percent_encoded_len(bytes: &[u8], encode_set: EncodeSet) -> usize {
+    let escaped = encode_set.ascii_mask();
+    let tail = trailing_whitespace_len(bytes);
+    bytes[..bytes.len() - tail]
+        .iter()
+        .map(|&byte| {
+            if byte < 128 && escaped & 1 << byte == 0 {
+                1
+            } else {
+                3
+            }
+        })
+        .sum::<usize>()
+        + 3 * tail
+}
+
+/// Base64-encodes `bytes`, with padding unless `pad` is false or the alphabet
+/// is [`Base64Alphabet::UrlSafe`].
+pub fn encode_base64(bytes: &[u8], alphabet: Base64Alphabet, pad: bool) -> String {
+    alphabet.engine(pad).encode(bytes)
+}
+
+/// Appends the `;base64` marker if needed and the comma.
+pub(crate) fn push_header_end(uri: &mut String, encoding: Encoding) {
+    match encoding {
+        Encoding::Base64 => uri.push_str(";base64"),
+        Encoding::Base45 => uri.push_str(";base45"),
+        Encoding::Percent => {}
+    }
+    uri.push(',');
+}
+
+pub(crate) fn push_payload(
+    uri: &mut String,
+    bytes: &[u8],
+    encoding: Encoding,
+    set: EncodeSet,
+    alphabet: Base64Alphabet,
+    pad: bool,
+) {
+    uri.reserve(encoded_len_hint(bytes, encoding, set));
+    match encoding {
+        #[cfg(feature = "simd")]
+        Encoding::Base64 => crate::simd::encode_into(bytes, alphabet, pad, uri),
+        #[cfg(not(feature = "simd"))]
+        Encoding::Base64 => alphabet.engine(pad).encode_string(bytes, uri),
+        Encoding::Percent => extend_percent(uri, bytes, set),
+        Encoding::Base45 => {
+            for chunk in bytes.chunks(2) {
+                let (mut value, digits) = match *chunk {
+                    [high, low] => (usize::from(high) << 8 | usize::from(low), 3),
+                    [byte] => (usize::from(byte), 2),
+                    _ => unreachable!(),
+                };
+                for _ in 0..digits {
+                    uri.push(char::from(BASE45[value % 45]));
+                    value /= 45;
+                }
+            }
+        }
+    }
+}
+
+/// Appends a decoded base45 payload to `bytes`, `None` if it isn't valid
+/// RFC 9285 base45.
+#[cfg(feature = "std")]
+pub(crate) fn decode_base45_into(payload: &str, bytes: &mut Vec<u8>) -> Option<()> {
+    bytes.reserve(payload.len() / 3 * 2 + 1);
+    for chunk in payload.as_bytes().chunks(3) {
+        let mut value = 0;
+        for digit in chunk.iter().rev() {
+            value = value * 45 + BASE45.iter().position(|c| c == digit)?;
+        }
+        match chunk.len() {
+            3 if value <= 0xffff => bytes.extend_from_slice(&[(value >> 8) as u8, value as u8]),
+            2 if value <= 0xff => bytes.push(value as u8),
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// Breaks `uri` into lines of at most `width` characters with `\n`, for
+/// source files and emails that reject long lines. Only the payload is
+/// broken, never inside a `%XX` escape, so the header stays on the first
+/// line. Existing line breaks are kept, and a `width` of 0 leaves the URI
+/// as is.
+///
+/// The decoders of this crate skip the line breaks, browsers don't.
+///
+/// ```
+/// let uri = data_uri_utils::wrap_data_uri("data:;base64,aGVsbG8gd29ybGQ=", 20);
+/// assert_eq!(uri, "data:;base64,aGVsbG8\ngd29ybGQ=");
+/// ```
+pub fn wrap_data_uri(uri: &str, width: usize) -> String {
+    if width == 0 || uri.len() <= width {
+        return uri.to_string();
+    }
+    let payload_start = uri.find(',').map_or(uri.len(), |comma| comma + 1);
+    let mut wrapped = String::with_capacity(uri.len() + uri.len() / width + 1);
+    wrapped.push_str(&uri[..payload_start]);
+    let mut line_len = payload_start;
+    let mut rest = &uri[payload_start..];
+    while let Some(c) = rest.chars().next() {
+        let len = match rest.get(..3) {
+            Some(escape) if c == '%' && escape.bytes().skip(1).all(|b| b.is_ascii_hexdigit()) => 3,
+            _ => c.len_utf8(),
+        };
+        match c {
+            '\r' => {}
+            '\n' => line_len = 0,
+            _ => {
+                if line_len > 0 && line_len + len > width {
+                    wrapped.push('\n');
+                    line_len = 0;
+                }
+                line_len += len;
+            }
+        }
+        wrapped.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+    wrapped
+}
+
+/// Number of line breaks [`wrap_data_uri`] adds to a URI with a
+/// `header_len` header and a `payload_len` payload without escapes.
+#[cfg(feature = "std")]
+pub(crate) fn line_breaks(header_len: usize, payload_len: usize, width: usize) -> usize {
+    if width == 0 || header_len + payload_len <= width {
+        return 0;
+    }
+    let first_line = width.saturating_sub(header_len);
+    payload_len.saturating_sub(first_line).div_ceil(width)
+}
+
+/// Strips a leading U+FEFF, which editors on Windows like to add to text files.
+pub fn trim_byte_order_mark(string: &str) -> &str {
+    string.strip_prefix('\u{FEFF}').unwrap_or(string)
+}
+
+/// Replaces every run of whitespace with a single space.
+///
+/// Only allocates when the string actually contains such a run.
+pub fn collapse_whitespace(string: &str) -> Cow<'_, str> {
+    let mut collapsed: Option<String> = None;
+    let mut in_whitespace = false;
+    for (index, c) in string.char_indices() {
+        if c.is_whitespace() {
+            if in_whitespace || c != ' ' {
+                let collapsed = collapsed.get_or_insert_with(|| string[..index].to_string());
+                if !in_whitespace {
+                    collapsed.push(' ');
+                }
+            } else if let Some(collapsed) = &mut collapsed {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            if let Some(collapsed) = &mut collapsed {
+                collapsed.push(c);
+            }
+            in_whitespace = false;
+        }
+    }
+    match collapsed {
+        Some(collapsed) => Cow::Owned(collapsed),
+        None => Cow::Borrowed(string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_svg_payload, probe_ascii_mask, SVG_MINIMAL};
+    use crate::*;
+    use alloc::string::String;
+    use base64::Engine;
+    use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+    #[test]
+    fn alloc_only_encoding() {
+        assert_eq!(
+            encode_data_uri("text/plain;charset=utf-8", b"hi", Encoding::Base64),
+            "data:text/plain;charset=utf-8;base64,aGk="
+        );
+        let uri = encode_data_uri("text/css", b"a{b:c}", Encoding::Percent);
+        assert_eq!(uri, "data:text/css,a%7Bb%3Ac%7D");
+        assert_eq!(uri.capacity(), uri.len() + ";base64".len());
+        for encoding in [Encoding::Base64, Encoding::Base45, Encoding::Percent] {
+            let uri = encode_data_uri("", b"\x00 hello", encoding);
+            let hint = encoded_len_hint(b"\x00 hello", encoding, EncodeSet::NonAlphanumeric);
+            assert_eq!(uri.len() - uri.find(',').unwrap() - 1, hint);
+        }
+        assert_eq!(
+            encode_svg_data_uri("\u{FEFF} <svg>\n  <g/> </svg>\n", EncodeSet::Minimal),
+            "data:image/svg+xml,%3Csvg%3E %3Cg/%3E %3C/svg%3E"
+        );
+        for (alphabet, pad, encoded) in [
+            (Base64Alphabet::Standard, true, "+/8="),
+            (Base64Alphabet::Standard, false, "+/8"),
+            (Base64Alphabet::UrlSafe, true, "-_8"),
+        ] {
+            assert_eq!(encoding::encode_base64(b"\xfb\xff", alphabet, pad), encoded);
+            assert_eq!(alphabet.encoded_len(pad, 2), encoded.len());
+            let engine = alphabet.engine(!pad);
+            assert_eq!(engine.decode(encoded).unwrap(), b"\xfb\xff");
+        }
+    }
+
+    #[test]
+    fn single_pass_svg_encoding() {
+        assert_eq!(
+            EncodeSet::NonAlphanumeric.ascii_mask(),
+            probe_ascii_mask(NON_ALPHANUMERIC)
+        );
+        assert_eq!(
+            EncodeSet::Minimal.ascii_mask(),
+            probe_ascii_mask(SVG_MINIMAL)
+        );
+
+        const READABLE: &AsciiSet = &NON_ALPHANUMERIC.remove(b' ').remove(b'\'');
+        let svg = "<a b=\"c\">\t\u{a0}d\u{2003} \u{e9}\r\n\x0b<e/>\"%#</a>";
+        for encode_set in [
+            EncodeSet::NonAlphanumeric,
+            EncodeSet::Minimal,
+            EncodeSet::Custom(READABLE),
+        ] {
+            for swap_quotes in [false, true] {
+                let mut encoded = String::new();
+                encode_svg_payload::<()>(svg, encode_set, swap_quotes, |part| {
+                    encoded.push_str(part);
+                    Ok(())
+                })
+                .unwrap();
+                let collapsed = collapse_whitespace(svg);
+                let reference = if swap_quotes {
+                    collapsed.replace('"', "'")
+                } else {
+                    collapsed.into_owned()
+                };
+                let reference: String =
+                    utf8_percent_encode(&reference, encode_set.ascii_set()).collect();
+                assert_eq!(encoded, reference);
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{ParseError, ValidationError};
+use std::fmt;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// The image codec failed to encode or decode the payload.
+    #[cfg(feature = "image")]
+    Image(image::ImageError),
+    /// The data URI is malformed or its payload can't be decoded.
+    Parse(ParseError),
+    Validation(ValidationError),
+    /// The media type is valid but can't be handled by this operation.
+    UnsupportedMediaType(mime::Mime),
+    /// The charset named in the data URI isn't known.
+    UnsupportedCharset(String),
+    /// The text can't be represented in, or decoded from, the charset.
+    InvalidText {
+        charset: String,
+    },
+    /// The data URI would be longer than the configured limit.
+    TooLarge {
+        actual: usize,
+        limit: usize,
+    },
+    /// The image header is truncated or doesn't give a size.
+    MissingDimensions,
+    /// The requested raster size is empty or too large.
+    InvalidSize {
+        width: u32,
+        height: u32,
+    },
+    /// The SVG couldn't be parsed for rasterization.
+    #[cfg(feature = "resvg")]
+    Svg(resvg::usvg::Error),
+    /// The chart drawing callback or backend failed.
+    #[cfg(feature = "plotters")]
+    Plot(Box<dyn std::error::Error + Send + Sync>),
+    /// The data URI has no `sig` parameter, see
+    /// [`verify_data_uri`](crate::verify_data_uri).
+    #[cfg(feature = "sha2")]
+    MissingSignature,
+    /// The `sig` parameter doesn't match the content, which was modified or
+    /// signed with another key.
+    #[cfg(feature = "sha2")]
+    InvalidSignature,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => error.fmt(f),
+            #[cfg(feature = "image")]
+            Error::Image(error) => error.fmt(f),
+            Error::Parse(error) => error.fmt(f),
+            Error::Validation(error) => error.fmt(f),
+            Error::UnsupportedMediaType(mime) => write!(f, "unsupported media type `{}`", mime),
+            Error::UnsupportedCharset(charset) => write!(f, "unsupported charset `{}`", charset),
+            Error::InvalidText { charset } => write!(f, "text isn't valid {}", charset),
+            Error::TooLarge { actual, limit } => write!(
+                f,
+                "data URI is {} bytes long, exceeding the {} bytes limit",
+                actual, limit
+            ),
+            Error::MissingDimensions => write!(f, "image dimensions not found"),
+            Error::InvalidSize { width, height } => {
+                write!(f, "invalid raster size {}x{}", width, height)
+            }
+            #[cfg(feature = "resvg")]
+            Error::Svg(error) => error.fmt(f),
+            #[cfg(feature = "plotters")]
+            Error::Plot(error) => error.fmt(f),
+            #[cfg(feature = "sha2")]
+            Error::MissingSignature => write!(f, "data URI isn't signed"),
+            #[cfg(feature = "sha2")]
+            Error::InvalidSignature => write!(f, "data URI signature doesn't match"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            #[cfg(feature = "image")]
+            Error::Image(error) => Some(error),
+            Error::Parse(error) => Some(error),
+            Error::Validation(error) => Some(error),
+            #[cfg(feature = "resvg")]
+            Error::Svg(error) => Some(error),
+            #[cfg(feature = "plotters")]
+            Error::Plot(error) => Some(error.as_ref()),
+            Error::UnsupportedMediaType(_)
+            | Error::UnsupportedCharset(_)
+            | Error::InvalidText { .. }
+            | Error::TooLarge { .. }
+            | Error::MissingDimensions
+            | Error::InvalidSize { .. } => None,
+            #[cfg(feature = "sha2")]
+            Error::MissingSignature | Error::InvalidSignature => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for Error {
+    fn from(error: image::ImageError) -> Self {
+        Error::Image(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::Validation(error)
+    }
+}
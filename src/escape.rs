@@ -0,0 +1,135 @@
+//! The percent-encoder of SVG payloads. It only uses `core`, so that
+//! `data-uri-utils-macros` compiles this very file and `include_data_uri!`
+//! encodes like the runtime does.
+
+/// The escaped ASCII bytes of each built-in set, as a bit mask. Checked against
+/// the sets themselves by the tests of `encoding`.
+pub(crate) const NON_ALPHANUMERIC_MASK: u128 =
+    !(ascii_range_mask(b'0', b'9') | ascii_range_mask(b'A', b'Z') | ascii_range_mask(b'a', b'z'));
+pub(crate) const SVG_MINIMAL_MASK: u128 =
+    ascii_range_mask(0, 0x1f) | ascii_range_mask(0x7f, 0x7f) | ascii_mask(b"\"#%<>\\^`{|}");
+
+/// ASCII whitespace as defined by [`char::is_whitespace`].
+pub(crate) const WHITESPACE_MASK: u128 = ascii_mask(b" \t\n\x0b\x0c\r");
+
+const fn ascii_mask(bytes: &[u8]) -> u128 {
+    let mut mask = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        mask |= 1 << bytes[index];
+        index += 1;
+    }
+    mask
+}
+
+const fn ascii_range_mask(first: u8, last: u8) -> u128 {
+    (u128::MAX >> (127 - last)) & (u128::MAX << first)
+}
+
+/// Flag of the [`encode_svg_segment`] outputs of ASCII whitespace.
+const WHITESPACE: u32 = 1 << 31;
+/// Flag of the [`encode_svg_segment`] outputs of the lead bytes of non-ASCII
+/// whitespace.
+const NON_ASCII_WHITESPACE: u32 = 1 << 30;
+
+/// Bytes encoded between two calls to `emit` by [`encode_svg_segment`].
+const CHUNK_LEN: usize = 1024;
+
+/// Percent-encodes an SVG, escaping the ASCII bytes of the `escaped` mask and
+/// every non-ASCII byte. Quotes are swapped for single ones if `swap_quotes`
+/// is set, and runs of whitespace collapsed to a space if `collapse` is.
+pub(crate) fn encode_svg_segment<E>(
+    svg: &str,
+    escaped: u128,
+    swap_quotes: bool,
+    collapse: bool,
+    mut emit: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let outputs = output_table(escaped, swap_quotes, collapse);
+    // Outputs are copied 4 bytes at a time, one past their end.
+    let mut buffer = [0u8; CHUNK_LEN * 3 + 1];
+    let mut index = 0;
+    while index < svg.len() {
+        let (next, len) = encode_chunk(svg, index, &outputs, &mut buffer);
+        index = next;
+        emit(ascii_str(&buffer[..len]))?;
+    }
+    Ok(())
+}
+
+/// What each byte is written as: its up to 3 bytes in little-endian order,
+/// then their count and the whitespace flags.
+fn output_table(escaped: u128, swap_quotes: bool, collapse: bool) -> [u32; 256] {
+    let is_literal = |byte: u8| byte < 128 && escaped & 1 << byte == 0;
+    let hex = |nibble: u8| b"0123456789ABCDEF"[nibble as usize] as u32;
+    let output = |byte: u8| {
+        if is_literal(byte) {
+            byte as u32 | 1 << 24
+        } else {
+            b'%' as u32 | hex(byte >> 4) << 8 | hex(byte & 15) << 16 | 3 << 24
+        }
+    };
+    let mut outputs = [0u32; 256];
+    for byte in 0..=255u8 {
+        outputs[byte as usize] = output(byte);
+    }
+    if collapse {
+        for byte in (0..128u8).filter(|byte| WHITESPACE_MASK & 1 << byte != 0) {
+            outputs[byte as usize] = output(b' ') | WHITESPACE;
+        }
+        for lead in [0xC2, 0xE1, 0xE2, 0xE3] {
+            outputs[lead] |= NON_ASCII_WHITESPACE;
+        }
+    }
+    if swap_quotes {
+        outputs[b'"' as usize] = output(b'\'');
+    }
+    outputs
+}
+
+/// Encodes up to [`CHUNK_LEN`] bytes of `svg` from `index` into `buffer`,
+/// returning where it stopped and the length written. Not generic, so that
+/// every caller of [`encode_svg_segment`] runs the same code.
+fn encode_chunk(
+    svg: &str,
+    mut index: usize,
+    outputs: &[u32; 256],
+    buffer: &mut [u8; CHUNK_LEN * 3 + 1],
+) -> (usize, usize) {
+    let is_ascii_whitespace = |byte: u8| byte < 128 && WHITESPACE_MASK & 1 << byte != 0;
+    // Non-ASCII whitespace, which only `trim_start` knows, ends at `index`.
+    let trim_start = |index: usize| svg.len() - svg[index..].trim_start().len();
+
+    let bytes = svg.as_bytes();
+    let end = bytes.len().min(index + CHUNK_LEN);
+    let mut len = 0;
+    while index < end {
+        let mut output = outputs[bytes[index] as usize];
+        index += 1;
+        // One test keeps the common case of a byte that is no whitespace off
+        // both flags.
+        if output & (WHITESPACE | NON_ASCII_WHITESPACE) != 0 {
+            if output & NON_ASCII_WHITESPACE != 0 {
+                let run_end = trim_start(index - 1);
+                if run_end >= index {
+                    index = run_end;
+                    output = outputs[b' ' as usize];
+                }
+            } else {
+                while index < bytes.len() && is_ascii_whitespace(bytes[index]) {
+                    index += 1;
+                }
+                if index < bytes.len() && bytes[index] >= 0xC2 {
+                    index = trim_start(index);
+                }
+            }
+        }
+        buffer[len..len + 4].copy_from_slice(&output.to_le_bytes());
+        len += (output >> 24 & 3) as usize;
+    }
+    (index, len)
+}
+
+fn ascii_str(ascii: &[u8]) -> &str {
+    core::str::from_utf8(ascii).expect("escaped output is ASCII")
+}
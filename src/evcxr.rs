@@ -0,0 +1,109 @@
+//! Inline display in evcxr Jupyter notebooks, which call `evcxr_display` on
+//! the value of a cell.
+
+use crate::{Base64Alphabet, DataUri, Encoding};
+use base64::Engine;
+
+impl DataUri {
+    /// Prints the payload in the evcxr display protocol, so a data URI
+    /// evaluated in a notebook cell shows as an image or rich text.
+    pub fn evcxr_display(&self) {
+        println!("{}", evcxr_content(self));
+    }
+}
+
+/// Displays images in evcxr notebooks as PNG. Import it for `image` buffers
+/// to show inline:
+///
+/// ```no_run
+/// use data_uri_utils::EvcxrDisplay;
+///
+/// let gradient = image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([x as u8 * 4, y as u8 * 4, 128]));
+/// gradient.evcxr_display();
+/// ```
+#[cfg(feature = "image")]
+pub trait EvcxrDisplay {
+    fn evcxr_display(&self);
+}
+
+#[cfg(feature = "image")]
+impl<P, C> EvcxrDisplay for image::ImageBuffer<P, C>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: image::EncodableLayout,
+    C: std::ops::Deref<Target = [P::Subpixel]>,
+{
+    fn evcxr_display(&self) {
+        display_png(crate::image_to_png_data_uri(self));
+    }
+}
+
+#[cfg(feature = "image")]
+impl EvcxrDisplay for image::DynamicImage {
+    fn evcxr_display(&self) {
+        display_png(crate::image_to_png_data_uri(self));
+    }
+}
+
+/// Shows the PNG, or the error as the output of the cell.
+#[cfg(feature = "image")]
+fn display_png(uri: crate::Result<DataUri>) {
+    match uri {
+        Ok(uri) => uri.evcxr_display(),
+        Err(error) => println!("{}", protocol_content("text/plain", &error.to_string())),
+    }
+}
+
+/// Raster images are sent base64-encoded, anything else as text.
+fn evcxr_content(uri: &DataUri) -> String {
+    let mime = uri.mime();
+    let payload = uri.decode_payload().unwrap_or_default();
+    let content = if mime.type_() == mime::IMAGE && mime.subtype() != mime::SVG {
+        match uri.encoding() {
+            // Anything but plain standard base64 is encoded again.
+            Encoding::Base64
+                if uri
+                    .payload()
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"+/=".contains(&byte)) =>
+            {
+                uri.payload().to_string()
+            }
+            _ => Base64Alphabet::Standard.engine(true).encode(&payload),
+        }
+    } else {
+        String::from_utf8_lossy(&payload).into_owned()
+    };
+    protocol_content(mime.essence_str(), &content)
+}
+
+fn protocol_content(essence: &str, content: &str) -> String {
+    format!(
+        "EVCXR_BEGIN_CONTENT {}\n{}\nEVCXR_END_CONTENT",
+        essence, content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evcxr_content, protocol_content};
+    use crate::*;
+
+    #[test]
+    fn display_protocol() {
+        let gif = DataUri::parse("data:image/gif;base64,R0lGODlh").unwrap();
+        assert_eq!(
+            evcxr_content(&gif),
+            "EVCXR_BEGIN_CONTENT image/gif\nR0lGODlh\nEVCXR_END_CONTENT"
+        );
+        let svg = svg_str_to_data_uri("<svg/>");
+        assert_eq!(
+            evcxr_content(&svg),
+            "EVCXR_BEGIN_CONTENT image/svg+xml\n<svg/>\nEVCXR_END_CONTENT"
+        );
+        assert_eq!(
+            protocol_content("text/plain", "too large"),
+            "EVCXR_BEGIN_CONTENT text/plain\ntoo large\nEVCXR_END_CONTENT"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::charset::decode_text_file;
+use crate::{
+    bytes_to_data_uri, svg_str_to_data_uri, wrap_data_uri, DataUri, DataUriBuilder, EncodeOptions,
+    Encoding, Error, MediaType, Result,
+};
+use base64::write::EncoderStringWriter;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Reads a file into a data URI, guessing its media type from the extension.
+///
+/// SVG and textual files are percent-encoded as UTF-8 when they are valid
+/// UTF-8, or UTF-16 with a byte order mark. Anything else is base64-encoded.
+pub fn file_to_data_uri(path: impl AsRef<Path>) -> Result<DataUri> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    Ok(file_bytes_to_data_uri(path, &bytes))
+}
+
+/// Streams `reader` into a base64 data URI, encoding it chunk by chunk instead
+/// of reading it whole first.
+///
+/// Encoding stops as soon as the data URI would exceed
+/// [`EncodeOptions::max_len`], the rest of the input is only counted to report
+/// the length in [`Error::TooLarge`].
+pub fn reader_to_data_uri<R: Read>(
+    mut reader: R,
+    media_type: impl Into<MediaType>,
+    options: &EncodeOptions,
+) -> Result<DataUri> {
+    let mime = mime::Mime::from(media_type.into());
+    let header = DataUriBuilder::new().media_type(&mime).header();
+    let header_len = header.len();
+    let engine = options.base64_engine();
+    let mut writer = EncoderStringWriter::from_consumer(header, &engine);
+    match options.max_len {
+        Some(limit) => {
+            let available = limit.saturating_sub(header_len);
+            let max_payload_len = options.max_base64_payload_len(available) as u64;
+            let copied = io::copy(&mut (&mut reader).take(max_payload_len + 1), &mut writer)?;
+            if copied > max_payload_len || header_len > limit {
+                let rest = io::copy(&mut reader, &mut io::sink())?;
+                let actual = header_len + options.base64_len((copied + rest) as usize);
+                return Err(Error::TooLarge { actual, limit });
+            }
+        }
+        None => {
+            io::copy(&mut reader, &mut writer)?;
+        }
+    }
+    let mut uri = writer.into_inner();
+    if let Some(width) = options.line_width {
+        uri = wrap_data_uri(&uri, width);
+        match options.max_len {
+            Some(limit) if uri.len() > limit => {
+                return Err(Error::TooLarge {
+                    actual: uri.len(),
+                    limit,
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(DataUri::from_parts(uri, mime, Encoding::Base64))
+}
+
+/// Encodes the content of the file at `path` the way [`file_to_data_uri`] does.
+pub(crate) fn file_bytes_to_data_uri(path: &Path, bytes: &[u8]) -> DataUri {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    match decode_text_file(bytes) {
+        Some(text) if mime == mime::IMAGE_SVG => svg_str_to_data_uri(text),
+        Some(text) if is_textual(&mime) => DataUriBuilder::new()
+            .media_type(&mime)
+            .charset("utf-8")
+            .encoding(Encoding::Percent)
+            .build_str(&text),
+        _ => bytes_to_data_uri(bytes, &mime),
+    }
+}
+
+fn is_textual(mime: &mime::Mime) -> bool {
+    mime.type_() == mime::TEXT
+        || [mime::JSON, mime::XML, mime::JAVASCRIPT].contains(&mime.subtype())
+        || [Some(mime::JSON), Some(mime::XML)].contains(&mime.suffix())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn file_encoding_by_type() {
+        let dir = std::env::temp_dir().join("data-uri-utils-file-test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let svg = dir.join("icon.svg");
+        std::fs::write(&svg, "<svg>\n</svg>").unwrap();
+        assert_eq!(
+            file_to_data_uri(&svg).unwrap(),
+            svg_str_to_data_uri("<svg>\n</svg>")
+        );
+
+        std::fs::write(&svg, b"\xff\xfe<\0s\0v\0g\0/\0>\0").unwrap();
+        assert_eq!(
+            file_to_data_uri(&svg).unwrap(),
+            svg_str_to_data_uri("<svg/>")
+        );
+
+        let json = dir.join("data.json");
+        std::fs::write(&json, r#"{"a":1}"#).unwrap();
+        assert_eq!(
+            file_to_data_uri(&json).unwrap(),
+            "data:application/json;charset=utf-8,%7B%22a%22%3A1%7D"
+        );
+
+        let font = dir.join("font.woff2");
+        std::fs::write(&font, b"wOF2\xff").unwrap();
+        assert_eq!(
+            file_to_data_uri(&font).unwrap(),
+            "data:font/woff2;base64,d09GMv8="
+        );
+
+        assert!(file_to_data_uri(dir.join("missing.png")).is_err());
+    }
+
+    #[test]
+    fn streamed_reader() {
+        let bytes: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let uri = reader_to_data_uri(&bytes[..], &mime::IMAGE_PNG, &EncodeOptions::default());
+        assert_eq!(uri.unwrap(), bytes_to_data_uri(&bytes, &mime::IMAGE_PNG));
+
+        let options = EncodeOptions {
+            max_len: Some(26),
+            ..Default::default()
+        };
+        let uri = reader_to_data_uri(&b"abc"[..], &mime::IMAGE_PNG, &options).unwrap();
+        assert_eq!(uri, "data:image/png;base64,YWJj");
+        assert!(matches!(
+            reader_to_data_uri(&b"abcd"[..], &mime::IMAGE_PNG, &options),
+            Err(Error::TooLarge {
+                actual: 30,
+                limit: 26
+            })
+        ));
+        let options = EncodeOptions {
+            max_len: Some(10),
+            ..Default::default()
+        };
+        assert!(reader_to_data_uri(&b""[..], &mime::IMAGE_PNG, &options).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{bytes_to_data_uri, css_url, sniff_mime, DataUri};
+use std::sync::LazyLock;
+
+static FONT_TTF: LazyLock<mime::Mime> = LazyLock::new(|| "font/ttf".parse().unwrap());
+static FONT_OTF: LazyLock<mime::Mime> = LazyLock::new(|| "font/otf".parse().unwrap());
+static FONT_EOT: LazyLock<mime::Mime> =
+    LazyLock::new(|| "application/vnd.ms-fontobject".parse().unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontFormat {
+    Woff2,
+    Woff,
+    /// TrueType.
+    Ttf,
+    /// OpenType with CFF outlines.
+    Otf,
+    /// Embedded OpenType, only understood by old Internet Explorer versions.
+    Eot,
+}
+
+impl FontFormat {
+    /// Guesses the format from the font's signature. EOT has none and is
+    /// never detected.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match sniff_mime(bytes)?.essence_str() {
+            "font/woff2" => Some(FontFormat::Woff2),
+            "font/woff" => Some(FontFormat::Woff),
+            "font/ttf" => Some(FontFormat::Ttf),
+            "font/otf" => Some(FontFormat::Otf),
+            _ => None,
+        }
+    }
+
+    pub fn mime(&self) -> mime::Mime {
+        match self {
+            FontFormat::Woff2 => mime::FONT_WOFF2,
+            FontFormat::Woff => mime::FONT_WOFF,
+            FontFormat::Ttf => FONT_TTF.clone(),
+            FontFormat::Otf => FONT_OTF.clone(),
+            FontFormat::Eot => FONT_EOT.clone(),
+        }
+    }
+
+    /// Format hint for the CSS `format()` function.
+    pub fn css_format(&self) -> &'static str {
+        match self {
+            FontFormat::Woff2 => "woff2",
+            FontFormat::Woff => "woff",
+            FontFormat::Ttf => "truetype",
+            FontFormat::Otf => "opentype",
+            FontFormat::Eot => "embedded-opentype",
+        }
+    }
+}
+
+pub fn font_to_data_uri(bytes: &[u8], format: FontFormat) -> DataUri {
+    bytes_to_data_uri(bytes, format.mime())
+}
+
+/// The `src` descriptor of an `@font-face` rule embedding the font, as in
+/// `src: url("data:font/woff2;base64,...") format("woff2");`.
+pub fn font_face_src(bytes: &[u8], format: FontFormat) -> String {
+    format!(
+        "src: {} format(\"{}\");",
+        css_url(&font_to_data_uri(bytes, format)),
+        format.css_format()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn font_face() {
+        assert_eq!(FontFormat::detect(b"wOF2\0\x01"), Some(FontFormat::Woff2));
+        assert_eq!(FontFormat::detect(b"\x89PNG\r\n\x1a\n"), None);
+        assert_eq!(
+            font_to_data_uri(b"\0\x01\0\0", FontFormat::Ttf),
+            "data:font/ttf;base64,AAEAAA=="
+        );
+        assert_eq!(
+            font_face_src(b"wOF2", FontFormat::Woff2),
+            r#"src: url("data:font/woff2;base64,d09GMg==") format("woff2");"#
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    data_uri_dimensions, decode_payload_into, decoded_len, sniff_mime, validate_data_uri, DataUri,
+    DecodePolicy,
+};
+
+/// Runs the parser and decoders on arbitrary bytes, the way a service facing
+/// hostile input would, for fuzzers such as cargo-fuzz:
+///
+/// ```ignore
+/// libfuzzer_sys::fuzz_target!(|data: &[u8]| data_uri_utils::fuzz_data_uri(data));
+/// ```
+///
+/// Decoding goes through a [`DecodePolicy`] limited to 1 MiB and 1024×1024
+/// pixels, so that no allocation is larger than a small multiple of the
+/// input before its size is checked. It panics only on bugs: a panic inside
+/// the crate or decoded lengths disagreeing with the computed ones.
+pub fn fuzz_data_uri(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = validate_data_uri(input);
+    let _ = data_uri_dimensions(input);
+    let _ = decoded_len(input);
+    let Ok(uri) = DataUri::parse(input) else {
+        return;
+    };
+    let _ = uri.normalized_mime();
+    let _ = uri.charset();
+    let _ = format!("{:#}", uri);
+    let policy = DecodePolicy {
+        max_payload_bytes: Some(1024 * 1024),
+        max_pixels: Some(1024 * 1024),
+        ..DecodePolicy::default()
+    };
+    if policy.check(&uri).is_err() {
+        return;
+    }
+    match uri.decode_payload() {
+        Ok(bytes) => {
+            assert_eq!(
+                bytes.len(),
+                uri.payload_len(),
+                "decoded length of {:?}",
+                input
+            );
+            let chunks: Result<Vec<Vec<u8>>, _> = uri.payload_chunks().collect();
+            assert_eq!(
+                chunks.map(|chunks| chunks.concat()).ok(),
+                Some(bytes.clone()),
+                "chunks of {:?}",
+                input
+            );
+            let mut into = vec![0];
+            if decode_payload_into(&uri, &mut into).is_ok() {
+                assert_eq!(into[1..], bytes);
+            }
+            let _ = sniff_mime(&bytes);
+            let _ = uri.decode_text();
+        }
+        Err(_) => assert!(uri.payload_chunks().any(|chunk| chunk.is_err())),
+    }
+    let _ = policy.decode(&uri);
+    #[cfg(feature = "image")]
+    {
+        let options = crate::DecodeOptions {
+            apply_orientation: true,
+            policy: Some(policy),
+        };
+        let _ = crate::data_uri_to_image_with(&uri, &options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// Mutations of well-formed data URIs, with a fixed seed so failures
+    /// reproduce.
+    #[test]
+    fn fuzz_corpus() {
+        let gif = bytes_to_data_uri(b"GIF89a\x02\x00\x02\x00\x80\x00\x00", &mime::IMAGE_GIF);
+        let seeds = [
+            "data:,hello%20world",
+            "data:text/plain;charset=utf-8;base64,aGVsbG8=",
+            "data:image/svg+xml,%3Csvg/%3E",
+            "data:;base45,BB8",
+            "data:text/plain;base64,aGVs\r\nbG8",
+            "data:image/png;base64,iVBORw0KGgo=",
+            &gif,
+        ];
+        let tokens: [&[u8]; 12] = [
+            b"%",
+            b"%2",
+            b",",
+            b";",
+            b"=",
+            b"\n",
+            b"base64",
+            b"base45",
+            b"\xc3\xa9",
+            b"-",
+            b" ",
+            b"data:",
+        ];
+        let mut state = 0x9e37_79b9_u32;
+        let mut random = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize % bound
+        };
+        for _ in 0..20_000 {
+            let mut input = seeds[random(seeds.len())].as_bytes().to_vec();
+            for _ in 0..1 + random(4) {
+                let at = random(input.len() + 1);
+                let (end, inserted): (usize, &[u8]) = match random(3) {
+                    0 => (at, tokens[random(tokens.len())]),
+                    1 => ((at + random(4)).min(input.len()), b""),
+                    _ => (at, &[b'\0', b'\x7f', b'\xff', b'A'][random(4)..][..1]),
+                };
+                input.splice(at..end, inserted.iter().copied());
+            }
+            fuzz_data_uri(&input);
+        }
+    }
+}
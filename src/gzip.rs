@@ -0,0 +1,105 @@
+//! Gzip payloads, enabled with the `gzip` feature.
+
+use crate::{DataUri, MediaType, Result, ValidationError};
+use std::borrow::Cow;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8] = b"\x1f\x8b";
+
+/// Decompressed length [`data_uri_to_bytes`](crate::data_uri_to_bytes)
+/// accepts, the default of [`DecodePolicy`](crate::DecodePolicy).
+pub(crate) const DEFAULT_LIMIT: usize = 16 * 1024 * 1024;
+
+pub(crate) fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder
+        .write_all(bytes)
+        .and_then(|()| encoder.finish())
+        .expect("writing to a Vec can't fail")
+}
+
+/// Decompresses the decoded payload of an SVG data URI when it starts with
+/// the gzip magic number, as `.svgz` files do, failing once the output
+/// exceeds `limit` bytes. Other payloads, and ones that don't decompress,
+/// are returned as they are.
+pub(crate) fn decompress_svgz<'a>(
+    uri: &DataUri,
+    bytes: &'a [u8],
+    limit: usize,
+) -> Result<Cow<'a, [u8]>> {
+    let is_svg = uri
+        .mime()
+        .essence_str()
+        .eq_ignore_ascii_case(MediaType::SvgXml.essence());
+    if !is_svg || !bytes.starts_with(MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut decompressed = Vec::new();
+    if flate2::read::MultiGzDecoder::new(bytes)
+        .take((limit as u64).saturating_add(1))
+        .read_to_end(&mut decompressed)
+        .is_err()
+    {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    if decompressed.len() > limit {
+        return Err(ValidationError::TooLarge {
+            actual: decompressed.len(),
+            limit,
+        }
+        .into());
+    }
+    Ok(Cow::Owned(decompressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn gzip_payloads() {
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+        let svgz = DataUriBuilder::new()
+            .media_type(&mime::IMAGE_SVG)
+            .gzip(true);
+        let uri = svgz.build_str(svg);
+        assert!(uri.decode_payload().unwrap().starts_with(b"\x1f\x8b"));
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), svg.as_bytes());
+        assert_eq!(data_uri_to_bytes("data:,abc").unwrap(), b"abc");
+        assert_eq!(
+            data_uri_to_bytes("data:image/svg+xml;base64,H4sIAAAA").unwrap(),
+            b"\x1f\x8b\x08\x00\x00\x00"
+        );
+
+        // Only SVG payloads are decompressed.
+        let binary = bytes_to_data_uri(
+            &[0x1f, 0x8b, 0, 0, 1, 2, 3],
+            &mime::APPLICATION_OCTET_STREAM,
+        );
+        assert_eq!(
+            data_uri_to_bytes(&binary).unwrap(),
+            [0x1f, 0x8b, 0, 0, 1, 2, 3]
+        );
+        let gzip = DataUriBuilder::new()
+            .media_type("application/gzip".parse::<mime::Mime>().unwrap())
+            .gzip(true)
+            .build_str(svg);
+        assert_eq!(
+            data_uri_to_bytes(&gzip).unwrap(),
+            gzip.decode_payload().unwrap()
+        );
+
+        let bomb = svgz.build(&[b' '; 100_000]);
+        let policy = DecodePolicy {
+            max_payload_bytes: Some(1000),
+            ..DecodePolicy::default()
+        };
+        assert!(matches!(
+            policy.decode(&bomb),
+            Err(Error::Validation(ValidationError::TooLarge {
+                actual: 1001,
+                limit: 1000
+            }))
+        ));
+    }
+}
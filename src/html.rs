@@ -0,0 +1,634 @@
+use crate::css::{resolve_data_uri, url_ranges};
+use crate::markup::{offset_in, unescape, Tag, Token, Tokenizer};
+use crate::svg::svg_dimensions;
+use crate::{svg_str_to_data_uri, DataUri, EncodeOptions};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::Range;
+
+/// An `<img>` tag for the data URI.
+///
+/// Missing dimensions are read from the image when possible, keeping the
+/// aspect ratio if only one of them is given.
+pub fn data_uri_to_img_tag(
+    uri: &str,
+    alt: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> String {
+    let dimensions = resolve_dimensions(width, height, || {
+        DataUri::parse(uri)
+            .ok()
+            .and_then(|uri| detect_dimensions(&uri))
+    });
+    img_tag(uri, alt, dimensions, None)
+}
+
+/// Same as [`data_uri_to_img_tag`] with an `integrity` attribute holding the
+/// [`data_uri_sri_hash`](crate::data_uri_sri_hash) of the payload, for
+/// pipelines that later move the asset to its own file.
+#[cfg(feature = "sha2")]
+pub fn data_uri_to_img_tag_with_integrity(
+    uri: &str,
+    alt: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    algorithm: crate::SriAlgorithm,
+) -> crate::Result<String> {
+    let parsed = DataUri::parse(uri)?;
+    let integrity = crate::data_uri_sri_hash(uri, algorithm)?;
+    let dimensions = resolve_dimensions(width, height, || detect_dimensions(&parsed));
+    Ok(img_tag(uri, alt, dimensions, Some(&integrity)))
+}
+
+/// Same as [`data_uri_to_img_tag`], with dimensions taken from the SVG
+/// `width`, `height` or `viewBox` attributes.
+pub fn svg_str_to_img_tag(
+    svg: impl AsRef<str>,
+    alt: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+) -> String {
+    let svg = svg.as_ref();
+    let dimensions = resolve_dimensions(width, height, || svg_pixels(svg));
+    img_tag(&svg_str_to_data_uri(svg), alt, dimensions, None)
+}
+
+/// An `<object>` tag for the data URI, which unlike `<img>` lets SVG
+/// run its scripts and load external resources.
+pub fn data_uri_to_object_tag(uri: &str, width: Option<u32>, height: Option<u32>) -> String {
+    object_tag(uri, width, height, None)
+}
+
+/// Same as [`data_uri_to_object_tag`] with an `integrity` attribute, see
+/// [`data_uri_to_img_tag_with_integrity`].
+#[cfg(feature = "sha2")]
+pub fn data_uri_to_object_tag_with_integrity(
+    uri: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    algorithm: crate::SriAlgorithm,
+) -> crate::Result<String> {
+    let integrity = crate::data_uri_sri_hash(uri, algorithm)?;
+    Ok(object_tag(uri, width, height, Some(&integrity)))
+}
+
+fn object_tag(
+    uri: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    integrity: Option<&str>,
+) -> String {
+    let parsed = DataUri::parse(uri).ok();
+    let dimensions = resolve_dimensions(width, height, || {
+        parsed.as_ref().and_then(detect_dimensions)
+    });
+    let mut tag = format!("<object data=\"{}\"", escape_attribute(uri));
+    if let Some(uri) = &parsed {
+        let _ = write!(
+            tag,
+            " type=\"{}\"",
+            escape_attribute(uri.mime().essence_str())
+        );
+    }
+    write_dimensions(&mut tag, dimensions);
+    write_integrity(&mut tag, integrity);
+    tag.push_str("></object>");
+    tag
+}
+
+fn img_tag(
+    uri: &str,
+    alt: &str,
+    dimensions: (Option<u32>, Option<u32>),
+    integrity: Option<&str>,
+) -> String {
+    let mut tag = format!(
+        "<img src=\"{}\" alt=\"{}\"",
+        escape_attribute(uri),
+        escape_attribute(alt)
+    );
+    write_dimensions(&mut tag, dimensions);
+    write_integrity(&mut tag, integrity);
+    tag.push('>');
+    tag
+}
+
+fn write_integrity(tag: &mut String, integrity: Option<&str>) {
+    if let Some(integrity) = integrity {
+        let _ = write!(tag, " integrity=\"{}\"", integrity);
+    }
+}
+
+fn write_dimensions(tag: &mut String, (width, height): (Option<u32>, Option<u32>)) {
+    if let Some(width) = width {
+        let _ = write!(tag, " width=\"{}\"", width);
+    }
+    if let Some(height) = height {
+        let _ = write!(tag, " height=\"{}\"", height);
+    }
+}
+
+fn resolve_dimensions(
+    width: Option<u32>,
+    height: Option<u32>,
+    detect: impl FnOnce() -> Option<(u32, u32)>,
+) -> (Option<u32>, Option<u32>) {
+    if width.is_some() && height.is_some() {
+        return (width, height);
+    }
+    let Some((detected_width, detected_height)) = detect().filter(|(w, h)| *w > 0 && *h > 0) else {
+        return (width, height);
+    };
+    let scale = |value: u32, from: u32, to: u32| {
+        (u64::from(value) * u64::from(to) / u64::from(from)) as u32
+    };
+    match (width, height) {
+        (Some(width), None) => (
+            Some(width),
+            Some(scale(width, detected_width, detected_height)),
+        ),
+        (None, Some(height)) => (
+            Some(scale(height, detected_height, detected_width)),
+            Some(height),
+        ),
+        _ => (Some(detected_width), Some(detected_height)),
+    }
+}
+
+fn detect_dimensions(uri: &DataUri) -> Option<(u32, u32)> {
+    let bytes = uri.decode_payload().ok()?;
+    if uri.mime().essence_str() == mime::IMAGE_SVG.essence_str() {
+        return svg_pixels(std::str::from_utf8(&bytes).ok()?);
+    }
+    #[cfg(feature = "image")]
+    {
+        crate::raster::image_dimensions(&bytes, uri.mime())
+    }
+    #[cfg(not(feature = "image"))]
+    None
+}
+
+fn svg_pixels(svg: &str) -> Option<(u32, u32)> {
+    let (width, height) = svg_dimensions(svg)?;
+    Some((width.round() as u32, height.round() as u32))
+}
+
+fn escape_attribute(value: &str) -> Cow<'_, str> {
+    if !value.contains(['&', '"', '<', '>']) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Makes an HTML document self-contained by replacing `<img src>`,
+/// `<link rel="icon" href>` and `url()` references in `style` attributes and
+/// `<style>` elements with data URIs.
+///
+/// `resolver` receives each URL with character references decoded and returns
+/// its content, or `None` to leave the reference untouched. Existing data URIs
+/// and `#fragment` references are skipped.
+pub fn inline_html_images(html: &str, resolver: impl Fn(&str) -> Option<Vec<u8>>) -> String {
+    let mut replacements = Vec::new();
+    let mut inline = |range: Range<usize>| {
+        let range = strip_escaped_quotes(html, range);
+        let url = unescape(&html[range.clone()]);
+        if let Some(uri) = resolve_data_uri(&url, &resolver, &EncodeOptions::default()) {
+            replacements.push((range, uri));
+        }
+    };
+    for_each_reference(html, |reference| match reference {
+        Reference::Attribute {
+            range,
+            image_source: true,
+        } => inline(range),
+        Reference::Attribute { .. } => {}
+        Reference::Css(range) => {
+            for url in url_ranges(&html[range.clone()]) {
+                inline(range.start + url.start..range.start + url.end);
+            }
+        }
+    });
+
+    let mut inlined = String::with_capacity(html.len());
+    let mut position = 0;
+    for (range, uri) in replacements {
+        inlined.push_str(&html[position..range.start]);
+        inlined.push_str(&uri);
+        position = range.end;
+    }
+    inlined.push_str(&html[position..]);
+    inlined
+}
+
+/// Same as [`inline_html_images`], except that SVG images shown more than
+/// once with `<img>` are emitted a single time, as a `<symbol>` of a hidden
+/// `<svg>` at the top of `<body>`, and each of those `<img>` becomes an
+/// `<svg>` that `<use>`s it.
+///
+/// The other attributes of the `<img>` are kept, its `alt` text becoming an
+/// `aria-label`. `resolver` is called once per distinct URL.
+///
+/// ```
+/// let html = r#"<body><img src="icon.svg" alt="Home"><img src="icon.svg" alt="Back"></body>"#;
+/// let inlined = data_uri_utils::inline_html_images_deduped(html, |_| {
+///     Some(br#"<svg viewBox="0 0 8 8"><path d="M0 0h8"/></svg>"#.to_vec())
+/// });
+/// assert_eq!(inlined.matches("<symbol").count(), 1);
+/// assert!(inlined.contains(r##"aria-label="Back"><use href="#data-uri-1"/></svg>"##));
+/// ```
+pub fn inline_html_images_deduped(
+    html: &str,
+    resolver: impl Fn(&str) -> Option<Vec<u8>>,
+) -> String {
+    let cache = RefCell::new(HashMap::new());
+    let resolver = |url: &str| {
+        cache
+            .borrow_mut()
+            .entry(url.to_string())
+            .or_insert_with(|| resolver(url))
+            .clone()
+    };
+
+    let mut images = Vec::new();
+    let mut body_end = 0;
+    for token in Tokenizer::new(html) {
+        let Token::StartTag(tag) = token else {
+            continue;
+        };
+        if tag.name.eq_ignore_ascii_case("body") && body_end == 0 {
+            body_end = offset_in(html, tag.source) + tag.source.len();
+        }
+        let Some(src) = tag.attribute("src") else {
+            continue;
+        };
+        let url = unescape(src).trim().to_string();
+        let is_local = !url.is_empty()
+            && !url.starts_with('#')
+            && !url
+                .get(..5)
+                .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"));
+        if tag.name.eq_ignore_ascii_case("img") && is_local {
+            images.push((tag, url));
+        }
+    }
+    let mut counts = HashMap::new();
+    for (_, url) in &images {
+        *counts.entry(url.as_str()).or_insert(0) += 1;
+    }
+    let mut symbols = Vec::new();
+    let mut symbol_indices = HashMap::new();
+    for (_, url) in &images {
+        if counts[url.as_str()] < 2 || symbol_indices.contains_key(url.as_str()) {
+            continue;
+        }
+        let bytes = resolver(url);
+        let svg = bytes
+            .as_deref()
+            .and_then(|bytes| std::str::from_utf8(bytes).ok());
+        if let Some(symbol) = svg.and_then(SvgSymbol::parse) {
+            symbol_indices.insert(url.as_str(), symbols.len());
+            symbols.push(symbol);
+        }
+    }
+    if symbols.is_empty() {
+        return inline_html_images(html, resolver);
+    }
+
+    let mut sprite = String::from("<svg style=\"display: none\">");
+    for (index, symbol) in symbols.iter().enumerate() {
+        symbol.write_definition(index + 1, &mut sprite);
+    }
+    sprite.push_str("</svg>");
+    let mut replacements = vec![(body_end..body_end, sprite)];
+    for (tag, url) in &images {
+        if let Some(&index) = symbol_indices.get(url.as_str()) {
+            let start = offset_in(html, tag.source);
+            let mut svg = String::new();
+            symbols[index].write_use(tag, index + 1, &mut svg);
+            replacements.push((start..start + tag.source.len(), svg));
+        }
+    }
+    replacements.sort_by_key(|(range, _)| range.start);
+
+    let mut deduped = String::with_capacity(html.len());
+    let mut position = 0;
+    for (range, replacement) in replacements {
+        deduped.push_str(&html[position..range.start]);
+        deduped.push_str(&replacement);
+        position = range.end;
+    }
+    deduped.push_str(&html[position..]);
+    inline_html_images(&deduped, resolver)
+}
+
+/// The parts of an SVG that [`inline_html_images_deduped`] moves to a
+/// `<symbol>`.
+struct SvgSymbol {
+    view_box: Option<String>,
+    dimensions: Option<(f64, f64)>,
+    content: String,
+}
+
+impl SvgSymbol {
+    fn parse(svg: &str) -> Option<Self> {
+        let root = Tokenizer::new(svg).find_map(|token| match token {
+            Token::StartTag(tag) => Some(tag),
+            _ => None,
+        })?;
+        if root.name.rsplit(':').next() != Some("svg") {
+            return None;
+        }
+        let start = offset_in(svg, root.source) + root.source.len();
+        let content = if root.self_closing {
+            ""
+        } else {
+            let rest = &svg[start..];
+            &rest[..rest.rfind("</")?]
+        }
+        .to_string();
+        let dimensions = svg_dimensions(svg);
+        let view_box = match root.attribute("viewBox") {
+            Some(view_box) => Some(view_box.to_string()),
+            None => dimensions.map(|(width, height)| format!("0 0 {} {}", width, height)),
+        };
+        Some(SvgSymbol {
+            view_box,
+            dimensions,
+            content,
+        })
+    }
+
+    fn write_definition(&self, index: usize, out: &mut String) {
+        let _ = write!(out, "<symbol id=\"data-uri-{}\"", index);
+        if let Some(view_box) = &self.view_box {
+            let _ = write!(out, " viewBox=\"{}\"", escape_attribute(view_box));
+        }
+        let _ = write!(out, ">{}</symbol>", self.content);
+    }
+
+    /// Writes the `<svg>` replacing `img`.
+    fn write_use(&self, img: &Tag<'_>, index: usize, out: &mut String) {
+        out.push_str("<svg");
+        if let Some(view_box) = &self.view_box {
+            let _ = write!(out, " viewBox=\"{}\"", escape_attribute(view_box));
+        }
+        for attribute in &img.attributes {
+            if ["src", "alt"]
+                .iter()
+                .any(|name| attribute.name.eq_ignore_ascii_case(name))
+            {
+                continue;
+            }
+            out.push(' ');
+            out.push_str(attribute.name);
+            if let Some(value) = attribute.value {
+                let quote = if value.contains('"') { '\'' } else { '"' };
+                let _ = write!(out, "={0}{1}{0}", quote, value);
+            }
+        }
+        let is_sized = ["width", "height"]
+            .iter()
+            .any(|name| img.attribute(name).is_some());
+        if let Some((width, height)) = self.dimensions.filter(|_| !is_sized) {
+            let _ = write!(out, " width=\"{}\" height=\"{}\"", width, height);
+        }
+        match img.attribute("alt") {
+            Some("") => out.push_str(" aria-hidden=\"true\""),
+            Some(alt) => {
+                let alt = if alt.contains('"') {
+                    Cow::Owned(alt.replace('"', "&quot;"))
+                } else {
+                    Cow::Borrowed(alt)
+                };
+                let _ = write!(out, " role=\"img\" aria-label=\"{}\"", alt);
+            }
+            None => {}
+        }
+        let _ = write!(out, "><use href=\"#data-uri-{}\"/></svg>", index);
+    }
+}
+
+/// Every data URI found in attributes, `style` attributes and `<style>`
+/// elements, along with its byte range in `html`.
+pub fn extract_data_uris(html: &str) -> Vec<(Range<usize>, DataUri)> {
+    reference_values(html)
+        .into_iter()
+        .filter_map(|(range, value)| Some((range, DataUri::parse(&value).ok()?)))
+        .collect()
+}
+
+/// Every attribute value and `url()` reference in stylesheets, with
+/// character references decoded, along with its byte range in `html`.
+pub(crate) fn reference_values(html: &str) -> Vec<(Range<usize>, Cow<'_, str>)> {
+    let mut values = Vec::new();
+    let mut push = |range: Range<usize>| {
+        let range = strip_escaped_quotes(html, range);
+        values.push((range.clone(), unescape(&html[range])));
+    };
+    for_each_reference(html, |reference| match reference {
+        Reference::Attribute { range, .. } => push(range),
+        Reference::Css(range) => {
+            for url in url_ranges(&html[range.clone()]) {
+                push(range.start + url.start..range.start + url.end);
+            }
+        }
+    });
+    values
+}
+
+enum Reference {
+    /// Any attribute other than `style`, `image_source` when it references
+    /// an image to display.
+    Attribute {
+        range: Range<usize>,
+        image_source: bool,
+    },
+    /// A stylesheet fragment whose `url()` references should be followed.
+    Css(Range<usize>),
+}
+
+fn for_each_reference(html: &str, mut visit: impl FnMut(Reference)) {
+    let mut in_style = false;
+    for token in Tokenizer::new(html) {
+        match token {
+            Token::StartTag(tag) => {
+                in_style = tag.name.eq_ignore_ascii_case("style") && !tag.self_closing;
+                for attribute in &tag.attributes {
+                    let Some(value) = attribute.value else {
+                        continue;
+                    };
+                    let offset = offset_in(html, value);
+                    let range = offset..offset + value.len();
+                    if attribute.name.eq_ignore_ascii_case("style") {
+                        visit(Reference::Css(range));
+                    } else {
+                        let image_source = is_image_source(&tag, attribute.name);
+                        visit(Reference::Attribute {
+                            range,
+                            image_source,
+                        });
+                    }
+                }
+            }
+            Token::Text(text) if in_style => {
+                let offset = offset_in(html, text);
+                visit(Reference::Css(offset..offset + text.len()));
+            }
+            Token::EndTag { .. } => in_style = false,
+            _ => {}
+        }
+    }
+}
+
+fn is_image_source(tag: &Tag<'_>, attribute: &str) -> bool {
+    if tag.name.eq_ignore_ascii_case("img") {
+        return attribute.eq_ignore_ascii_case("src");
+    }
+    tag.name.eq_ignore_ascii_case("link")
+        && attribute.eq_ignore_ascii_case("href")
+        && tag.attribute("rel").is_some_and(|rel| {
+            rel.split_ascii_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("icon"))
+        })
+}
+
+/// Narrows `url(&quot;...&quot;)` in an attribute down to the URL itself.
+fn strip_escaped_quotes(html: &str, range: Range<usize>) -> Range<usize> {
+    let value = &html[range.clone()];
+    for quote in ["&quot;", "&apos;", "&#34;", "&#39;"] {
+        if let Some(unquoted) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            let start = range.start + quote.len();
+            return start..start + unquoted.len();
+        }
+    }
+    range
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn svg_img_tag() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 30"/>"#;
+        let tag = svg_str_to_img_tag(svg, "A \"quoted\" <logo> & more", None, None);
+        assert_eq!(
+            tag,
+            format!(
+                "<img src=\"{}\" alt=\"A &quot;quoted&quot; &lt;logo&gt; &amp; more\" width=\"40\" height=\"30\">",
+                svg_str_to_data_uri(svg)
+            )
+        );
+        let tag = svg_str_to_img_tag(svg, "", Some(80), None);
+        assert!(tag.ends_with("alt=\"\" width=\"80\" height=\"60\">"));
+
+        let mini = svg_str_to_data_uri_with(svg, &SvgEncodeOptions::mini());
+        let tag = data_uri_to_object_tag(&mini, None, Some(15));
+        assert!(tag.starts_with("<object data=\"data:image/svg+xml,%3Csvg xmlns='"));
+        assert!(tag.ends_with(" type=\"image/svg+xml\" width=\"20\" height=\"15\"></object>"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn raster_img_tag() {
+        let uri = image_to_png_data_uri(&image::RgbImage::new(3, 2)).unwrap();
+        let tag = data_uri_to_img_tag(&uri, "pixel", None, None);
+        assert_eq!(
+            tag,
+            format!(
+                "<img src=\"{}\" alt=\"pixel\" width=\"3\" height=\"2\">",
+                uri
+            )
+        );
+        let tag = data_uri_to_img_tag("data:,not an image", "x", None, None);
+        assert_eq!(tag, "<img src=\"data:,not an image\" alt=\"x\">");
+    }
+
+    #[test]
+    fn inline_and_extract() {
+        let html = r#"<html><head><link rel="shortcut icon" href="favicon.ico?v=2"><style>
+body { background: url('bg.svg') }
+</style></head><body><img alt=logo src="logo.png"><img src='missing.png'>
+<div style="background: url(&quot;bg.svg&quot;); border-image: url(bg.svg)"></div>
+<img src="data:,kept"></body></html>"#;
+        let inlined = inline_html_images(html, |url| match url {
+            "favicon.ico?v=2" => Some(b"\0\0\x01\0".to_vec()),
+            "logo.png" => Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+            "bg.svg" => Some(b"<svg/>".to_vec()),
+            _ => None,
+        });
+        assert!(inlined
+            .contains(r#"<link rel="shortcut icon" href="data:image/x-icon;base64,AAABAA==">"#));
+        assert!(inlined.contains("background: url('data:image/svg+xml;base64,PHN2Zy8+') }"));
+        assert!(inlined.contains(r#"<img alt=logo src="data:image/png;base64,iVBORw0KGgo=">"#));
+        assert!(inlined.contains("<img src='missing.png'>"));
+        assert!(inlined.contains("border-image: url(data:image/svg+xml;base64,PHN2Zy8+)"));
+        assert!(inlined.contains(r#"<img src="data:,kept">"#));
+
+        let uris = extract_data_uris(&inlined);
+        let mimes: Vec<_> = uris
+            .iter()
+            .map(|(_, uri)| uri.mime().essence_str().to_string())
+            .collect();
+        assert_eq!(
+            mimes,
+            [
+                "image/x-icon",
+                "image/svg+xml",
+                "image/png",
+                "image/svg+xml",
+                "image/svg+xml",
+                "text/plain"
+            ]
+        );
+        for (range, uri) in &uris {
+            assert_eq!(&inlined[range.clone()], uri.as_str());
+        }
+    }
+
+    #[test]
+    fn inline_deduped() {
+        let html = r#"<html><body class=a><img src="icon.svg" alt='"Icon"' class="i"><p><img src=icon.svg width=8><img src="icon.svg" alt=""></p>
+<img src="logo.png"><img src="logo.png"><img src="once.svg"></body></html>"#;
+        let calls = std::cell::Cell::new(0);
+        let inlined = inline_html_images_deduped(html, |url| {
+            calls.set(calls.get() + 1);
+            match url {
+                "icon.svg" => Some(
+                    br#"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"><path d="M0 0h16"/></svg>"#
+                        .to_vec(),
+                ),
+                "logo.png" => Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+                "once.svg" => Some(b"<svg/>".to_vec()),
+                _ => None,
+            }
+        });
+        assert_eq!(
+            inlined,
+            r##"<html><body class=a><svg style="display: none"><symbol id="data-uri-1" viewBox="0 0 16 16"><path d="M0 0h16"/></symbol></svg><svg viewBox="0 0 16 16" class="i" width="16" height="16" role="img" aria-label="&quot;Icon&quot;"><use href="#data-uri-1"/></svg><p><svg viewBox="0 0 16 16" width="8"><use href="#data-uri-1"/></svg><svg viewBox="0 0 16 16" width="16" height="16" aria-hidden="true"><use href="#data-uri-1"/></svg></p>
+<img src="data:image/png;base64,iVBORw0KGgo="><img src="data:image/png;base64,iVBORw0KGgo="><img src="data:image/svg+xml;base64,PHN2Zy8+"></body></html>"##
+        );
+        assert_eq!(calls.get(), 3);
+        assert_eq!(
+            inline_html_images_deduped("<img src=a.svg>", |_| Some(b"<svg/>".to_vec())),
+            "<img src=data:image/svg+xml;base64,PHN2Zy8+>"
+        );
+    }
+}
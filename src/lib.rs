@@ -1,98 +1,543 @@
-use image::ImageEncoder;
-use image::PixelWithColorType;
-use once_cell::sync::Lazy;
-use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
-use regex::Regex;
-use std::borrow::Cow;
-
-static WHITESPACES_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
-
-trait SvgDataUriUtils: AsRef<str> {
-    fn trim_byte_order_mark(&self) -> &str {
-        let string = self.as_ref();
-        match string.chars().next() {
-            Some('\u{FEFF}') => &string[1..],
-            _ => string,
-        }
-    }
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+pub mod bundle;
+#[cfg(feature = "std")]
+mod charset;
+#[cfg(feature = "std")]
+mod css;
+#[cfg(feature = "std")]
+mod data_uri;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "sha2")]
+mod digest;
+#[cfg(feature = "std")]
+mod dimensions;
+#[cfg(feature = "std")]
+mod docs;
+#[cfg(feature = "std")]
+mod document;
+#[cfg(feature = "std")]
+mod email;
+pub mod encoding;
+#[cfg(feature = "std")]
+mod error;
+mod escape;
+#[cfg(feature = "evcxr")]
+mod evcxr;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+mod font;
+#[cfg(feature = "std")]
+mod fuzz;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "std")]
+mod html;
+#[cfg(feature = "std")]
+mod markdown;
+#[cfg(feature = "std")]
+mod markup;
+#[cfg(feature = "std")]
+mod media;
+#[cfg(feature = "std")]
+mod media_type;
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "plotters")]
+mod plot;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(feature = "std")]
+mod qr;
+#[cfg(feature = "image")]
+mod raster;
+#[cfg(feature = "resvg")]
+mod rasterize;
+#[cfg(feature = "test-util")]
+mod roundtrip;
+#[cfg(all(feature = "serde", feature = "image"))]
+pub mod serde_png;
+#[cfg(feature = "sha2")]
+mod signature;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "std")]
+mod sniff;
+#[cfg(feature = "std")]
+mod split;
+#[cfg(feature = "sha2")]
+mod sri;
+#[cfg(feature = "std")]
+mod summary;
+#[cfg(feature = "std")]
+mod svg;
+#[cfg(any(feature = "askama", feature = "maud", feature = "tera"))]
+mod templates;
+#[cfg(feature = "std")]
+mod to_data_uri;
+#[cfg(feature = "url")]
+mod url_interop;
+#[cfg(feature = "std")]
+mod validate;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "web")]
+mod web;
+
+#[cfg(feature = "std")]
+pub use builder::DataUriBuilder;
+#[cfg(feature = "std")]
+pub use charset::{strip_bom, text_to_data_uri, Charset};
+#[cfg(feature = "image")]
+pub use css::image_to_css_background;
+#[cfg(feature = "std")]
+pub use css::{
+    css_background_image, css_url, inline_css_urls, inline_css_urls_deduped, svg_str_to_css_url,
+};
+#[cfg(feature = "std")]
+pub use data_uri::{decode_payload_into, decoded_len, DataUri, ParseError, PayloadChunks};
+#[cfg(feature = "macros")]
+pub use data_uri_utils_macros::include_data_uri;
+#[cfg(feature = "image")]
+pub use diff::PixelDiff;
+#[cfg(feature = "std")]
+pub use diff::{diff_data_uris, DataUriDiff, PayloadDiff};
+#[cfg(feature = "sha2")]
+pub use digest::{data_uri_digest, data_uri_payload_digest};
+#[cfg(feature = "std")]
+pub use dimensions::data_uri_dimensions;
+#[cfg(feature = "mdbook")]
+pub use docs::run_mdbook_preprocessor;
+#[cfg(feature = "std")]
+pub use docs::DocAssets;
+#[cfg(feature = "std")]
+pub use document::{html_to_data_uri, pdf_to_data_uri};
+#[cfg(feature = "std")]
+pub use email::{html_cid_to_data_uris, html_data_uris_to_cid, Attachment};
+pub use encoding::{
+    collapse_whitespace, encode_data_uri, encode_svg_data_uri, encoded_len_hint, wrap_data_uri,
+    Base64Alphabet, EncodeSet, Encoding,
+};
+#[cfg(feature = "std")]
+pub use error::{Error, Result};
+#[cfg(all(feature = "evcxr", feature = "image"))]
+pub use evcxr::EvcxrDisplay;
+#[cfg(feature = "std")]
+pub use file::{file_to_data_uri, reader_to_data_uri};
+#[cfg(feature = "std")]
+pub use font::{font_face_src, font_to_data_uri, FontFormat};
+#[cfg(feature = "std")]
+pub use fuzz::fuzz_data_uri;
+#[cfg(feature = "std")]
+pub use html::{
+    data_uri_to_img_tag, data_uri_to_object_tag, extract_data_uris, inline_html_images,
+    inline_html_images_deduped, svg_str_to_img_tag,
+};
+#[cfg(feature = "sha2")]
+pub use html::{data_uri_to_img_tag_with_integrity, data_uri_to_object_tag_with_integrity};
+#[cfg(feature = "std")]
+pub use markdown::{extract_markdown_data_uris, inline_markdown_images};
+#[cfg(feature = "std")]
+pub use media::{audio_to_data_uri, video_to_data_uri, AudioFormat, VideoFormat};
+#[cfg(feature = "std")]
+pub use media_type::MediaType;
+#[cfg(feature = "async")]
+pub use nonblocking::file_to_data_uri_async;
+#[cfg(all(feature = "async", feature = "image"))]
+pub use nonblocking::{
+    image_to_data_uri_async, image_to_jpeg_data_uri_async, image_to_png_data_uri_async,
+    transcode_data_uri_async,
+};
+#[cfg(feature = "plotters")]
+pub use plot::plot_to_png_data_uri;
+#[cfg(feature = "std")]
+pub use policy::DecodePolicy;
+#[cfg(feature = "std")]
+pub use qr::bytes_to_qr_data_uri;
+#[cfg(all(feature = "image", feature = "avif"))]
+pub use raster::image_to_avif_data_uri;
+#[cfg(feature = "image")]
+pub use raster::{
+    data_uri_to_image, data_uri_to_image_with, data_uri_to_premultiplied_rgba8,
+    data_uri_to_premultiplied_rgba8_with, data_uri_to_rgba8, data_uri_to_rgba8_with, encode_batch,
+    estimated_data_uri_len, float_image_to_png_data_uri, frames_to_apng_data_uri,
+    frames_to_gif_data_uri, image_to_bmp_data_uri, image_to_data_uri, image_to_data_uri_with,
+    image_to_gif_data_uri, image_to_ico_data_uri, image_to_jpeg_data_uri, image_to_png_data_uri,
+    image_to_smallest_data_uri, image_to_thumbnail_data_uri, image_to_tiff_data_uri,
+    image_to_webp_data_uri, luma8_to_png_data_uri, pixels_to_png_data_uri, rgb8_to_png_data_uri,
+    rgba8_to_png_data_uri, strip_metadata_data_uri, strip_png_metadata, tone_map_image,
+    transcode_data_uri, transcode_data_uri_with, write_image_data_uri, ChromaSubsampling,
+    DecodeOptions, EncodableImage, Format, JpegOptions, PngOptions, ThumbnailOptions, ToneMapping,
+    WebPQuality,
+};
+#[cfg(feature = "resvg")]
+pub use rasterize::{svg_data_uri_to_png_data_uri, svg_str_to_png_data_uri};
+#[cfg(feature = "test-util")]
+pub use roundtrip::roundtrip;
+#[cfg(feature = "sha2")]
+pub use signature::{sign_data_uri, verify_data_uri};
+#[cfg(feature = "std")]
+pub use sniff::sniff_mime;
+#[cfg(feature = "std")]
+pub use split::{join_data_uri, split_data_uri};
+#[cfg(feature = "sha2")]
+pub use sri::{data_uri_sri_hash, sri_hash, SriAlgorithm};
+#[cfg(feature = "std")]
+pub use summary::{redact_data_uris, summarize};
+#[cfg(feature = "roxmltree")]
+pub use svg::{check_svg, try_svg_str_to_data_uri_with};
+#[cfg(feature = "std")]
+pub use svg::{
+    fmt_svg_data_uri, fmt_svg_data_uri_with, inline_svg_images, recolor_svg, sanitize_svg,
+    set_svg_size, svg_bytes_to_data_uri, svg_bytes_to_data_uri_with, svg_size, svg_str_to_data_uri,
+    svg_str_to_data_uri_base64, svg_str_to_data_uri_with, SvgDataUri, SvgEncodeOptions, SvgSize,
+    SvgWhitespace,
+};
+#[cfg(feature = "askama")]
+pub use templates::askama_filters;
+#[cfg(feature = "tera")]
+pub use templates::tera_data_uri;
+#[cfg(any(feature = "askama", feature = "maud", feature = "tera"))]
+pub use templates::TemplateAsset;
+#[cfg(feature = "std")]
+pub use to_data_uri::ToDataUri;
+#[cfg(feature = "url")]
+pub use url_interop::svg_str_to_data_url;
+#[cfg(feature = "std")]
+pub use validate::{validate_data_uri, DataUriInfo, ValidationError};
+#[cfg(all(feature = "wasm", feature = "image"))]
+pub use wasm::{image_to_js_string, set_image_src};
+#[cfg(feature = "wasm")]
+pub use wasm::{set_image_src_svg, svg_str_to_js_string};
+#[cfg(feature = "web")]
+pub use web::{Upload, UploadPolicy};
 
-    fn collapse_whitespace(&self) -> Cow<str> {
-        WHITESPACES_REGEX.replace_all(self.as_ref(), " ")
+/// Base64-encodes `bytes` as a data URI of `media_type`.
+#[cfg(feature = "std")]
+pub fn bytes_to_data_uri(bytes: &[u8], media_type: impl Into<MediaType>) -> DataUri {
+    DataUriBuilder::new().media_type(media_type).build(bytes)
+}
+
+/// Same as [`bytes_to_data_uri`], honoring the limit, strategy and base64
+/// settings of the options.
+///
+/// ```
+/// use data_uri_utils::{bytes_to_data_uri_with, EncodeOptions, EncodeSet, EncodeStrategy};
+///
+/// let options = EncodeOptions {
+///     strategy: EncodeStrategy::Shortest,
+///     encode_set: EncodeSet::Minimal,
+///     ..Default::default()
+/// };
+/// let uri = bytes_to_data_uri_with(br#"{"ok":true}"#, &mime::APPLICATION_JSON, &options)?;
+/// assert_eq!(uri, "data:application/json,%7B%22ok%22:true%7D");
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn bytes_to_data_uri_with(
+    bytes: &[u8],
+    media_type: impl Into<MediaType>,
+    options: &EncodeOptions,
+) -> Result<DataUri> {
+    let media_type = media_type.into();
+    let encoding = options.encoding(bytes, &media_type);
+    let mut builder = DataUriBuilder::new()
+        .media_type(media_type)
+        .encoding(encoding)
+        .encode_set(options.encode_set)
+        .alphabet(options.alphabet)
+        .omit_padding(options.omit_padding);
+    if let Some(width) = options.line_width {
+        builder = builder.line_width(width);
+    }
+    let Some(limit) = options.max_len else {
+        return Ok(builder.build(bytes));
+    };
+    let header_len = builder.header().len();
+    let payload_len = options.payload_len(bytes, encoding);
+    let line_breaks = options.line_width.map_or(0, |width| {
+        encoding::line_breaks(header_len, payload_len, width)
+    });
+    let actual = header_len + payload_len + line_breaks;
+    if actual > limit {
+        return Err(Error::TooLarge { actual, limit });
     }
+    let uri = builder.build(bytes);
+    // Escapes are never split, which can take a few more line breaks.
+    if uri.len() > limit {
+        return Err(Error::TooLarge {
+            actual: uri.len(),
+            limit,
+        });
+    }
+    Ok(uri)
+}
 
-    fn encode_uri_components(&self) -> Cow<str> {
-        let string = self.as_ref();
-        utf8_percent_encode(string, NON_ALPHANUMERIC).collect()
+/// Same as [`bytes_to_data_uri`] with the media type guessed by [`sniff_mime`],
+/// falling back to `application/octet-stream`.
+#[cfg(feature = "std")]
+pub fn bytes_to_data_uri_sniffed(bytes: &[u8]) -> DataUri {
+    let mime = sniff_mime(bytes).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+    bytes_to_data_uri(bytes, &mime)
+}
+
+/// Parses the data URI and decodes its payload.
+///
+/// With the `gzip` feature, gzip-compressed SVG payloads such as `.svgz`
+/// content are decompressed transparently, up to 16 MiB.
+#[cfg(feature = "std")]
+pub fn data_uri_to_bytes(uri: &str) -> Result<Vec<u8>> {
+    let uri = DataUri::parse(uri)?;
+    let bytes = uri.decode_payload()?;
+    #[cfg(feature = "gzip")]
+    if let std::borrow::Cow::Owned(decompressed) =
+        gzip::decompress_svgz(&uri, &bytes, gzip::DEFAULT_LIMIT)?
+    {
+        return Ok(decompressed);
     }
+    Ok(bytes)
 }
 
-impl<T: AsRef<str>> SvgDataUriUtils for T {}
+/// [`data_uri_to_bytes`] within `policy`, decompressing gzip SVG payloads
+/// up to its `max_payload_bytes`.
+#[cfg(feature = "std")]
+pub fn data_uri_to_bytes_with(uri: &str, policy: &DecodePolicy) -> Result<Vec<u8>> {
+    policy.decode(uri)
+}
 
-pub fn svg_str_to_data_uri(svg: impl AsRef<str>) -> String {
-    format!(
-        "data:image/svg+xml,{}",
-        svg.trim_byte_order_mark()
-            .trim()
-            .collapse_whitespace()
-            .encode_uri_components()
-    )
+/// Exact length of the base64 data URI [`bytes_to_data_uri`] would produce
+/// for a payload of `payload_len` bytes.
+#[cfg(feature = "std")]
+pub fn base64_data_uri_len(media_type: impl Into<MediaType>, payload_len: usize) -> usize {
+    DataUriBuilder::new().media_type(media_type).header().len() + payload_len.div_ceil(3) * 4
 }
 
-pub fn image_to_png_data_uri<T>(image: &T) -> image::ImageResult<String>
-where
-    T: image::GenericImageView + image::EncodableLayout,
-    <T as image::GenericImageView>::Pixel: image::PixelWithColorType,
-{
-    let mut buffer = Vec::new();
-    let encoder = image::codecs::png::PngEncoder::new(&mut buffer);
-    encoder.write_image(
-        image.as_bytes(),
-        image.width(),
-        image.height(),
-        <T as image::GenericImageView>::Pixel::COLOR_TYPE,
-    )?;
-    Ok([
-        "data:",
-        mime::IMAGE_PNG.as_ref(),
-        ";base64,",
-        base64::encode(&buffer).as_ref(),
-    ]
-    .iter()
-    .cloned()
-    .collect())
+/// Settings of [`bytes_to_data_uri_with`] and the image encoders.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Fail with [`Error::TooLarge`] instead of producing a longer data URI.
+    ///
+    /// Some browsers and CSS engines reject data URIs above 32KB.
+    pub max_len: Option<usize>,
+    /// Alphabet of base64 payloads.
+    pub alphabet: Base64Alphabet,
+    /// Drop the trailing `=` of base64 payloads, which browsers don't need.
+    pub omit_padding: bool,
+    /// Break the URI into lines of at most this many characters, e.g. 76 as
+    /// in MIME, see [`wrap_data_uri`]. Line breaks count towards `max_len`.
+    pub line_width: Option<usize>,
+    /// How the payload is encoded.
+    pub strategy: EncodeStrategy,
+    /// Characters escaped in percent-encoded payloads.
+    /// [`EncodeSet::Minimal`] keeps JSON and CSS readable.
+    pub encode_set: EncodeSet,
 }
 
-pub fn image_to_jpeg_data_uri<T>(image: &T, quality: u8) -> image::ImageResult<String>
-where
-    T: image::GenericImageView,
-    <T as image::GenericImageView>::Pixel: image::PixelWithColorType,
-{
-    let mut buffer = Vec::new();
-    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
-    encoder.encode_image(image)?;
-    Ok([
-        "data:",
-        mime::IMAGE_JPEG.as_ref(),
-        ";base64,",
-        base64::encode(&buffer).as_ref(),
-    ]
-    .iter()
-    .cloned()
-    .collect())
+/// How [`bytes_to_data_uri_with`] encodes payloads.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncodeStrategy {
+    #[default]
+    Base64,
+    /// Percent-encoding with [`EncodeOptions::encode_set`], readable and
+    /// usually shorter for text such as JSON, CSS or HTML.
+    Percent,
+    /// Whichever gives the shorter data URI, `;base64` marker included, and
+    /// base64 on ties.
+    Shortest,
+    /// [`EncodeStrategy::Shortest`] for textual media types, see
+    /// [`MediaType::default_encoding`], base64 for the others, which
+    /// percent-encoding would only make longer.
+    ///
+    /// [`DataUri::encoding`] tells which was picked.
+    Auto,
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl EncodeOptions {
+    /// The engine encoding base64 payloads with [`EncodeOptions::alphabet`]
+    /// and [`EncodeOptions::omit_padding`], see [`Base64Alphabet::engine`].
+    pub fn base64_engine(&self) -> base64::engine::GeneralPurpose {
+        self.alphabet.engine(!self.omit_padding)
+    }
+
+    /// Length of `payload_len` bytes once base64-encoded.
+    pub(crate) fn base64_len(&self, payload_len: usize) -> usize {
+        self.alphabet.encoded_len(!self.omit_padding, payload_len)
+    }
+
+    /// The encoding [`EncodeOptions::strategy`] picks for `bytes`.
+    pub(crate) fn encoding(&self, bytes: &[u8], media_type: &MediaType) -> Encoding {
+        match self.strategy {
+            EncodeStrategy::Base64 => Encoding::Base64,
+            EncodeStrategy::Percent => Encoding::Percent,
+            EncodeStrategy::Auto if media_type.default_encoding() == Encoding::Base64 => {
+                Encoding::Base64
+            }
+            EncodeStrategy::Shortest | EncodeStrategy::Auto => {
+                let percent_len = encoding::percent_encoded_len(bytes, self.encode_set);
+                if percent_len < ";base64".len() + self.base64_len(bytes.len()) {
+                    Encoding::Percent
+                } else {
+                    Encoding::Base64
+                }
+            }
+        }
+    }
+
+    /// Length of `bytes` once encoded with `encoding`.
+    pub(crate) fn payload_len(&self, bytes: &[u8], encoding: Encoding) -> usize {
+        match encoding {
+            Encoding::Percent => encoding::percent_encoded_len(bytes, self.encode_set),
+            _ => self.base64_len(bytes.len()),
+        }
+    }
+
+    /// Largest payload whose base64 encoding fits in `encoded_len` characters.
+    pub(crate) fn max_base64_payload_len(&self, encoded_len: usize) -> usize {
+        self.alphabet
+            .max_payload_len(!self.omit_padding, encoded_len)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use crate::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn bytes_round_trip() {
+        let font = mime::Mime::from_str("font/woff2").unwrap();
+        let uri = bytes_to_data_uri(b"wOF2\x00\x01", &font);
+        assert_eq!(uri, "data:font/woff2;base64,d09GMgAB");
+        let parsed = DataUri::parse(&uri).unwrap();
+        assert_eq!(parsed.mime(), &font);
+        assert_eq!(parsed.decode_payload().unwrap(), b"wOF2\x00\x01");
+    }
 
     #[test]
-    fn full_test() {
-        let svg = r##"
-            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 50 50">
-                <path d="M22 38V51L32 32l19-19v12C44 26 43 10 38 0 52 15 49 39 22 38z"/>
-            </svg>"##;
-        let expected = r#"data:image/svg+xml,%3Csvg%20xmlns%3D%22http%3A%2F%2Fwww%2Ew3%2Eorg%2F2000%2Fsvg%22%20viewBox%3D%220%200%2050%2050%22%3E%20%3Cpath%20d%3D%22M22%2038V51L32%2032l19%2D19v12C44%2026%2043%2010%2038%200%2052%2015%2049%2039%2022%2038z%22%2F%3E%20%3C%2Fsvg%3E"#;
-        let result = svg_str_to_data_uri(svg);
-        assert_eq!(result, expected);
+    fn base64_alphabet_and_padding() {
+        let options = EncodeOptions {
+            alphabet: Base64Alphabet::UrlSafe,
+            ..Default::default()
+        };
+        let png = &mime::IMAGE_PNG;
+        let uri = bytes_to_data_uri_with(b"\xfb\xff", png, &options).unwrap();
+        assert_eq!(uri, "data:image/png;base64,-_8");
+        assert_eq!(uri.payload_len(), 2);
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"\xfb\xff");
+        assert_eq!(
+            data_uri_to_bytes("data:image/png;base64,+/8=").unwrap(),
+            b"\xfb\xff"
+        );
+        let limited = EncodeOptions {
+            max_len: Some(24),
+            ..options
+        };
+        assert!(matches!(
+            bytes_to_data_uri_with(b"\xfb\xff", png, &limited),
+            Err(Error::TooLarge {
+                actual: 25,
+                limit: 24
+            })
+        ));
+        let uri = reader_to_data_uri(&b"\xfb\xff"[..], png, &options).unwrap();
+        assert_eq!(uri, "data:image/png;base64,-_8");
+
+        let unpadded = EncodeOptions {
+            omit_padding: true,
+            ..Default::default()
+        };
+        let uri = bytes_to_data_uri_with(b"\xfb\xff", png, &unpadded).unwrap();
+        assert_eq!(uri, "data:image/png;base64,+/8");
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"\xfb\xff");
+        let uri = reader_to_data_uri(&b"\xfb\xff"[..], png, &unpadded).unwrap();
+        assert_eq!(uri, "data:image/png;base64,+/8");
+    }
+    #[test]
+    fn encode_strategies() {
+        let json = &mime::APPLICATION_JSON;
+        let percent = EncodeOptions {
+            strategy: EncodeStrategy::Percent,
+            ..Default::default()
+        };
+        let uri = bytes_to_data_uri_with(b"[1, 2]", json, &percent).unwrap();
+        assert_eq!(uri, "data:application/json,%5B1%2C%202%5D");
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"[1, 2]");
+
+        let shortest = EncodeOptions {
+            strategy: EncodeStrategy::Shortest,
+            encode_set: EncodeSet::Minimal,
+            max_len: Some(35),
+            ..Default::default()
+        };
+        let uri = bytes_to_data_uri_with(b"[1, 2]", json, &shortest).unwrap();
+        assert_eq!(uri, "data:application/json,[1, 2]");
+        let minimal = EncodeOptions {
+            encode_set: EncodeSet::Minimal,
+            ..percent.clone()
+        };
+        let uri = bytes_to_data_uri_with(b"a  ", MediaType::PlainText, &minimal).unwrap();
+        assert_eq!(uri, "data:text/plain,a%20%20");
+        assert_eq!(uri.payload_len(), 3);
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"a  ");
+        assert_eq!(DataUri::parse(&uri).unwrap(), uri);
+        let binary = b"\0\x01\x02\x03\x04\x05";
+        let uri = bytes_to_data_uri_with(binary, &mime::TEXT_PLAIN, &shortest).unwrap();
+        assert_eq!(uri, "data:text/plain;base64,AAECAwQF");
+        let auto = EncodeOptions {
+            strategy: EncodeStrategy::Auto,
+            ..shortest.clone()
+        };
+        let uri = bytes_to_data_uri_with(b"[1, 2]", MediaType::Json, &auto).unwrap();
+        assert_eq!(uri.encoding(), Encoding::Percent);
+        let uri = bytes_to_data_uri_with(b"abc", MediaType::Png, &auto).unwrap();
+        assert_eq!(uri, "data:image/png;base64,YWJj");
+        assert!(matches!(
+            bytes_to_data_uri_with(b"[1, 2, 3, 4, 5, 6, 7]", json, &shortest),
+            Err(Error::TooLarge {
+                actual: 43,
+                limit: 35
+            })
+        ));
+    }
+
+    #[test]
+    fn line_wrapping() {
+        let png = &mime::IMAGE_PNG;
+        let options = EncodeOptions {
+            line_width: Some(30),
+            ..Default::default()
+        };
+        let uri = bytes_to_data_uri_with(b"hello world!", png, &options).unwrap();
+        assert_eq!(uri, "data:image/png;base64,aGVsbG8g\nd29ybGQh");
+        assert_eq!(uri.payload_len(), 12);
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"hello world!");
+        let uri = reader_to_data_uri(&b"hello world!"[..], png, &options).unwrap();
+        assert_eq!(uri, "data:image/png;base64,aGVsbG8g\nd29ybGQh");
+        let limited = EncodeOptions {
+            max_len: Some(38),
+            ..options
+        };
+        assert!(matches!(
+            bytes_to_data_uri_with(b"hello world!", png, &limited),
+            Err(Error::TooLarge {
+                actual: 39,
+                limit: 38
+            })
+        ));
+
+        let uri = wrap_data_uri("data:,%3Csvg%3E", 8);
+        assert_eq!(uri, "data:,\n%3Csvg\n%3E");
+        assert_eq!(wrap_data_uri(&uri, 8), uri);
+        assert_eq!(data_uri_to_bytes(&uri).unwrap(), b"<svg>");
     }
 }
@@ -0,0 +1,290 @@
+use crate::css::resolve_data_uri;
+use crate::{DataUri, EncodeOptions};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Makes a Markdown document self-contained by replacing the destination of
+/// images, given inline as in `![alt](path)` or by a `[label]: path`
+/// definition, with data URIs.
+///
+/// `resolver` receives each destination and returns its content, or `None`
+/// to leave it untouched. Existing data URIs and `#fragment` references are
+/// skipped, as are images in code spans and fenced code blocks.
+///
+/// ```
+/// let md = "# Logo\n\n![logo](logo.svg \"Our logo\")\n";
+/// let inlined = data_uri_utils::inline_markdown_images(md, |path| {
+///     (path == "logo.svg").then(|| b"<svg/>".to_vec())
+/// });
+/// assert_eq!(
+///     inlined,
+///     "# Logo\n\n![logo](data:image/svg+xml;base64,PHN2Zy8+ \"Our logo\")\n"
+/// );
+/// ```
+pub fn inline_markdown_images(md: &str, resolver: impl Fn(&str) -> Option<Vec<u8>>) -> String {
+    let mut inlined = String::with_capacity(md.len());
+    let mut position = 0;
+    for range in image_destinations(md) {
+        let Some(uri) = resolve_data_uri(&md[range.clone()], &resolver, &EncodeOptions::default())
+        else {
+            continue;
+        };
+        inlined.push_str(&md[position..range.start]);
+        inlined.push_str(&uri);
+        position = range.end;
+    }
+    inlined.push_str(&md[position..]);
+    inlined
+}
+
+/// Every data URI used as an image destination, along with its byte range in
+/// `md`, to move embedded images out of a document.
+pub fn extract_markdown_data_uris(md: &str) -> Vec<(Range<usize>, DataUri)> {
+    image_destinations(md)
+        .into_iter()
+        .filter_map(|range| Some((range.clone(), DataUri::parse(&md[range]).ok()?)))
+        .collect()
+}
+
+/// Byte ranges of the destinations of images, in order. Reference
+/// definitions are included when an image uses them.
+fn image_destinations(md: &str) -> Vec<Range<usize>> {
+    let mut destinations = Vec::new();
+    let mut labels = HashSet::new();
+    let mut definitions = Vec::new();
+    // The character and length of the opening fence of a code block.
+    let mut fence: Option<(u8, usize)> = None;
+    let mut offset = 0;
+    for line in md.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let content = line[indent..].trim_end();
+        let marker = fence_marker(content);
+        if let Some((character, len)) = fence {
+            let closes = marker.is_some_and(|(marker, marker_len)| {
+                marker == character && marker_len >= len && marker_len == content.len()
+            });
+            if indent < 4 && closes {
+                fence = None;
+            }
+            continue;
+        }
+        if indent < 4 {
+            if marker.is_some() {
+                fence = marker;
+                continue;
+            }
+            if let Some((label, range)) = definition(content) {
+                let start = start + indent;
+                definitions.push((label, start + range.start..start + range.end));
+                continue;
+            }
+        }
+        scan_images(line, start, &mut destinations, &mut labels);
+    }
+    destinations.extend(
+        definitions
+            .into_iter()
+            .filter(|(label, _)| labels.contains(label))
+            .map(|(_, range)| range),
+    );
+    destinations.sort_by_key(|range| range.start);
+    destinations
+}
+
+/// The character and length of a code fence opening `line`.
+fn fence_marker(line: &str) -> Option<(u8, usize)> {
+    let character = *line
+        .as_bytes()
+        .first()
+        .filter(|c| matches!(c, b'`' | b'~'))?;
+    let len = line.len() - line.trim_start_matches(character as char).len();
+    (len >= 3).then_some((character, len))
+}
+
+/// The normalized label and destination range of a `[label]: destination`
+/// definition.
+fn definition(line: &str) -> Option<(String, Range<usize>)> {
+    let label_end = line.strip_prefix('[')?.find(']')? + 1;
+    let label = &line[1..label_end];
+    let rest = line[label_end + 1..].strip_prefix(':')?;
+    let destination = rest.trim_start();
+    let start = line.len() - destination.len();
+    let range = match destination.strip_prefix('<') {
+        Some(angled) => start + 1..start + 1 + angled.find('>')?,
+        None => {
+            let len = destination.find(char::is_whitespace);
+            start..start + len.unwrap_or(destination.len())
+        }
+    };
+    (!label.contains('[') && !range.is_empty()).then(|| (normalize_label(label), range))
+}
+
+/// Pushes the destinations of inline images in `line` and the labels of
+/// reference images.
+fn scan_images(
+    line: &str,
+    offset: usize,
+    destinations: &mut Vec<Range<usize>>,
+    labels: &mut HashSet<String>,
+) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let run = line[i..].len() - line[i..].trim_start_matches('`').len();
+                i += run + closing_backticks(&line[i + run..], run).unwrap_or(0);
+            }
+            b'!' if bytes.get(i + 1) == Some(&b'[') => {
+                let Some(alt_end) = closing_bracket(line, i + 2) else {
+                    i += 2;
+                    continue;
+                };
+                let alt = &line[i + 2..alt_end];
+                i = alt_end + 1;
+                match bytes.get(i) {
+                    Some(b'(') => {
+                        if let Some((range, end)) = inline_destination(line, i + 1) {
+                            destinations.push(offset + range.start..offset + range.end);
+                            i = end;
+                        }
+                    }
+                    Some(b'[') => {
+                        let Some(label_end) = closing_bracket(line, i + 1) else {
+                            continue;
+                        };
+                        let label = &line[i + 1..label_end];
+                        labels.insert(normalize_label(if label.is_empty() { alt } else { label }));
+                        i = label_end + 1;
+                    }
+                    _ => {
+                        labels.insert(normalize_label(alt));
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// Length of `text` up to the end of the code span closed by `run`
+/// backticks.
+fn closing_backticks(text: &str, run: usize) -> Option<usize> {
+    let mut position = 0;
+    while let Some(found) = text[position..].find('`') {
+        let start = position + found;
+        let len = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if len == run {
+            return Some(start + len);
+        }
+        position = start + len;
+    }
+    None
+}
+
+/// Index of the `]` closing the bracket opened just before `start`.
+fn closing_bracket(line: &str, start: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => depth += 1,
+            b']' if depth == 0 => return Some(i),
+            b']' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The destination range of an inline image whose `(` is just before
+/// `start`, and the index following its `)`.
+fn inline_destination(line: &str, start: usize) -> Option<(Range<usize>, usize)> {
+    let bytes = line.as_bytes();
+    let skip_spaces = |mut i: usize| {
+        while bytes.get(i).is_some_and(|b| matches!(b, b' ' | b'\t')) {
+            i += 1;
+        }
+        i
+    };
+    let mut i = skip_spaces(start);
+    let range = if bytes.get(i) == Some(&b'<') {
+        let end = i + 1 + line[i + 1..].find(['>', '\n'])?;
+        if bytes[end] != b'>' {
+            return None;
+        }
+        let range = i + 1..end;
+        i = end + 1;
+        range
+    } else {
+        let destination = i;
+        let mut depth = 0;
+        while let Some(&byte) = bytes.get(i) {
+            match byte {
+                b'\\' => i += 1,
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                byte if byte.is_ascii_whitespace() => break,
+                _ => {}
+            }
+            i += 1;
+        }
+        i = i.min(bytes.len());
+        destination..i
+    };
+    i = skip_spaces(i);
+    if let Some(&quote @ (b'"' | b'\'' | b'(')) = bytes.get(i) {
+        let closing = if quote == b'(' { ')' } else { quote as char };
+        i = skip_spaces(i + 1 + line[i + 1..].find(closing)? + 1);
+    }
+    (bytes.get(i) == Some(&b')')).then_some((range, i + 1))
+}
+
+/// Labels match case-insensitively, with runs of whitespace collapsed.
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn inline_and_extract() {
+        let md = "![a](a.png) [![b](<b c.png> 'B')](https://example.com)\n\
+                  `![code](a.png)` \\![escaped](a.png)\n\
+                  ```md\n![fenced](a.png)\n```\n\
+                  ![Logo][] ![unused](missing.png)\n\
+                  \n[logo]: ./a.png \"Logo\"\n[other]: a.png\n";
+        let inlined = inline_markdown_images(md, |path| {
+            path.ends_with(".png")
+                .then(|| b"\x89PNG\r\n\x1a\n".to_vec())
+        })
+        .replace("data:image/png;base64,iVBORw0KGgo=", "URI");
+        assert_eq!(
+            inlined,
+            "![a](URI) [![b](<URI> 'B')](https://example.com)\n\
+             `![code](a.png)` \\![escaped](a.png)\n\
+             ```md\n![fenced](a.png)\n```\n\
+             ![Logo][] ![unused](URI)\n\
+             \n[logo]: URI \"Logo\"\n[other]: a.png\n"
+        );
+
+        let md = "![x](data:image/gif;base64,R0lGODlh) ![y][y]\n\n[y]: <data:,hi>\n";
+        let uris = extract_markdown_data_uris(md);
+        assert_eq!(uris.len(), 2);
+        assert_eq!(&md[uris[0].0.clone()], "data:image/gif;base64,R0lGODlh");
+        assert_eq!(uris[1].1.decode_payload().unwrap(), b"hi");
+    }
+}
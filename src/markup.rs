@@ -0,0 +1,350 @@
+//! Lenient tokenizer for XML-ish markup.
+//!
+//! It never fails: anything it can't make sense of is handed back as text,
+//! which keeps rewriting passes lossless on malformed documents.
+
+use std::borrow::Cow;
+
+/// Byte offset of `slice`, which must borrow from `source`.
+pub(crate) fn offset_in(source: &str, slice: &str) -> usize {
+    let offset = slice.as_ptr() as usize - source.as_ptr() as usize;
+    debug_assert!(offset + slice.len() <= source.len());
+    offset
+}
+
+/// Decodes the character references that commonly appear in attribute values.
+pub(crate) fn unescape(value: &str) -> Cow<'_, str> {
+    if !value.contains('&') {
+        return Cow::Borrowed(value);
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..1 + end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16),
+                    None => code.parse(),
+                };
+                char::from_u32(code.ok()?)
+            }
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                unescaped.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    Cow::Owned(unescaped)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Comment(&'a str),
+    Cdata(&'a str),
+    /// `<!DOCTYPE ...>` and other `<!` declarations.
+    Declaration(&'a str),
+    ProcessingInstruction(&'a str),
+    StartTag(Tag<'a>),
+    EndTag {
+        name: &'a str,
+        source: &'a str,
+    },
+}
+
+impl<'a> Token<'a> {
+    pub(crate) fn source(&self) -> &'a str {
+        match self {
+            Token::Text(source)
+            | Token::Comment(source)
+            | Token::Cdata(source)
+            | Token::Declaration(source)
+            | Token::ProcessingInstruction(source)
+            | Token::EndTag { source, .. } => source,
+            Token::StartTag(tag) => tag.source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tag<'a> {
+    pub(crate) source: &'a str,
+    pub(crate) name: &'a str,
+    pub(crate) attributes: Vec<Attribute<'a>>,
+    pub(crate) self_closing: bool,
+}
+
+impl<'a> Tag<'a> {
+    /// Raw value of the first attribute with the given name, entities are not decoded.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.eq_ignore_ascii_case(name))
+            .and_then(|attribute| attribute.value)
+    }
+
+    /// Serializes the tag with the given attributes in place of its own.
+    pub(crate) fn write_with<'b>(
+        &self,
+        attributes: impl IntoIterator<Item = (&'b str, &'b str)>,
+        out: &mut String,
+    ) {
+        out.push('<');
+        out.push_str(self.name);
+        for (name, value) in attributes {
+            out.push(' ');
+            out.push_str(name);
+            let quote = if value.contains('"') { '\'' } else { '"' };
+            out.push('=');
+            out.push(quote);
+            out.push_str(value);
+            out.push(quote);
+        }
+        out.push_str(if self.self_closing { "/>" } else { ">" });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Attribute<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) value: Option<&'a str>,
+}
+
+pub(crate) struct Tokenizer<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        Tokenizer {
+            source,
+            position: 0,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> &'a str {
+        let token = &self.source[self.position..self.position + len];
+        self.position += len;
+        token
+    }
+
+    /// Length of a token running until `terminator`, or to the end of input.
+    fn len_until(rest: &str, start: usize, terminator: &str) -> usize {
+        rest[start..]
+            .find(terminator)
+            .map_or(rest.len(), |end| start + end + terminator.len())
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let rest = &self.source[self.position..];
+        if rest.is_empty() {
+            return None;
+        }
+        if !rest.starts_with('<') {
+            let len = rest.find('<').unwrap_or(rest.len());
+            return Some(Token::Text(self.take(len)));
+        }
+        if rest.starts_with("<!--") {
+            let len = Self::len_until(rest, 4, "-->");
+            return Some(Token::Comment(self.take(len)));
+        }
+        if rest.starts_with("<![CDATA[") {
+            let len = Self::len_until(rest, 9, "]]>");
+            return Some(Token::Cdata(self.take(len)));
+        }
+        if rest.starts_with("<!") {
+            let len = declaration_len(rest);
+            return Some(Token::Declaration(self.take(len)));
+        }
+        if rest.starts_with("<?") {
+            let len = Self::len_until(rest, 2, "?>");
+            return Some(Token::ProcessingInstruction(self.take(len)));
+        }
+        if let Some(after) = rest.strip_prefix("</") {
+            let len = Self::len_until(rest, 2, ">");
+            let name = after[..len - 2].trim_end_matches('>').trim();
+            return Some(Token::EndTag {
+                name,
+                source: self.take(len),
+            });
+        }
+        match parse_start_tag(rest) {
+            Some((len, name, attributes, self_closing)) => Some(Token::StartTag(Tag {
+                source: self.take(len),
+                name,
+                attributes,
+                self_closing,
+            })),
+            // A tag left open runs to the end of input, as in browsers,
+            // rather than every following `<` being parsed to the end again.
+            None if starts_tag_name(rest) => Some(Token::Text(self.take(rest.len()))),
+            None => {
+                let len = rest[1..].find('<').map_or(rest.len(), |end| end + 1);
+                Some(Token::Text(self.take(len)))
+            }
+        }
+    }
+}
+
+fn declaration_len(rest: &str) -> usize {
+    let mut depth = 0usize;
+    for (index, c) in rest.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            '>' if depth == 0 => return index + 1,
+            _ => {}
+        }
+    }
+    rest.len()
+}
+
+type StartTag<'a> = (usize, &'a str, Vec<Attribute<'a>>, bool);
+
+fn starts_tag_name(rest: &str) -> bool {
+    rest[1..].starts_with(|c: char| c.is_alphabetic() || c == '_')
+}
+
+/// `None` when `rest` doesn't start with a tag name, or when the tag isn't
+/// terminated.
+fn parse_start_tag(rest: &str) -> Option<StartTag<'_>> {
+    if !starts_tag_name(rest) {
+        return None;
+    }
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=';
+    let name_len = rest[1..].find(is_name_end).unwrap_or(rest.len() - 1);
+    let name = &rest[1..1 + name_len];
+
+    let mut attributes = Vec::new();
+    let mut position = 1 + name_len;
+    loop {
+        let after = &rest[position..];
+        let trimmed = after.trim_start();
+        position += after.len() - trimmed.len();
+        if trimmed.starts_with('>') {
+            return Some((position + 1, name, attributes, false));
+        }
+        if trimmed.starts_with("/>") {
+            return Some((position + 2, name, attributes, true));
+        }
+        if let Some(after_slash) = trimmed.strip_prefix('/') {
+            // Stray slash, as in `<a / b>`.
+            position += trimmed.len() - after_slash.len();
+            continue;
+        }
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        let name_len = trimmed.find(is_name_end).unwrap_or(trimmed.len());
+        let attribute_name = &trimmed[..name_len.max(1)];
+        position += attribute_name.len();
+        let after = &rest[position..];
+        let trimmed = after.trim_start();
+        let Some(value_part) = trimmed.strip_prefix('=') else {
+            attributes.push(Attribute {
+                name: attribute_name,
+                value: None,
+            });
+            continue;
+        };
+        position += after.len() - value_part.len();
+        let after = &rest[position..];
+        let value_part = after.trim_start();
+        position += after.len() - value_part.len();
+        let value = match value_part.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let end = value_part[1..].find(quote)?;
+                position += end + 2;
+                &value_part[1..1 + end]
+            }
+            _ => {
+                let end = value_part
+                    .find(|c: char| c.is_whitespace() || c == '>')
+                    .unwrap_or(value_part.len());
+                position += end;
+                &value_part[..end]
+            }
+        };
+        attributes.push(Attribute {
+            name: attribute_name,
+            value: Some(value),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_document() {
+        let source = r#"<?xml version="1.0"?><!DOCTYPE svg [<!ENTITY a "b">]><!-- c --><svg a='1' b = "x y" c=d e><g/>text &amp; <![CDATA[<x>]]></svg >"#;
+        let tokens: Vec<_> = Tokenizer::new(source).collect();
+        assert_eq!(tokens.iter().map(Token::source).collect::<String>(), source);
+        assert!(matches!(tokens[0], Token::ProcessingInstruction(_)));
+        assert_eq!(
+            tokens[1],
+            Token::Declaration(r#"<!DOCTYPE svg [<!ENTITY a "b">]>"#)
+        );
+        assert_eq!(tokens[2], Token::Comment("<!-- c -->"));
+        let Token::StartTag(svg) = &tokens[3] else {
+            panic!("expected a start tag, got {:?}", tokens[3]);
+        };
+        assert_eq!(svg.name, "svg");
+        let values: Vec<_> = svg
+            .attributes
+            .iter()
+            .map(|attribute| (attribute.name, attribute.value))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("a", Some("1")),
+                ("b", Some("x y")),
+                ("c", Some("d")),
+                ("e", None)
+            ]
+        );
+        assert!(matches!(&tokens[4], Token::StartTag(g) if g.self_closing));
+        assert_eq!(tokens[5], Token::Text("text &amp; "));
+        assert_eq!(tokens[6], Token::Cdata("<![CDATA[<x>]]>"));
+        assert!(matches!(tokens[7], Token::EndTag { name: "svg", .. }));
+    }
+
+    #[test]
+    fn malformed_markup_is_text() {
+        let source = "1 < 2 <a b=\"unterminated>";
+        let tokens: Vec<_> = Tokenizer::new(source).collect();
+        assert_eq!(tokens.iter().map(Token::source).collect::<String>(), source);
+        assert!(tokens.iter().all(|token| matches!(token, Token::Text(_))));
+    }
+
+    #[test]
+    fn unescape_references() {
+        assert!(matches!(unescape("a.png"), Cow::Borrowed("a.png")));
+        assert_eq!(unescape("a?b=1&amp;c=&#50;&#x33;"), "a?b=1&c=23");
+        assert_eq!(unescape("&unknown; & &#xzz;"), "&unknown; & &#xzz;");
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{bytes_to_data_uri_with, DataUri, EncodeOptions, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    Mp3,
+    Wav,
+    Ogg,
+    Flac,
+    Aac,
+    /// AAC or ALAC in an MPEG-4 container.
+    M4a,
+    WebM,
+}
+
+impl AudioFormat {
+    pub fn mime(&self) -> mime::Mime {
+        let essence = match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Wav => "audio/wav",
+            AudioFormat::Ogg => "audio/ogg",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::M4a => "audio/mp4",
+            AudioFormat::WebM => "audio/webm",
+        };
+        essence.parse().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    Mp4,
+    WebM,
+    Ogg,
+    /// QuickTime, which most browsers other than Safari won't play.
+    Mov,
+}
+
+impl VideoFormat {
+    pub fn mime(&self) -> mime::Mime {
+        let essence = match self {
+            VideoFormat::Mp4 => "video/mp4",
+            VideoFormat::WebM => "video/webm",
+            VideoFormat::Ogg => "video/ogg",
+            VideoFormat::Mov => "video/quicktime",
+        };
+        essence.parse().unwrap()
+    }
+}
+
+/// Fails with [`Error::TooLarge`](crate::Error::TooLarge) before encoding anything when the data URI
+/// would exceed [`EncodeOptions::max_len`].
+pub fn audio_to_data_uri(
+    bytes: &[u8],
+    format: AudioFormat,
+    options: &EncodeOptions,
+) -> Result<DataUri> {
+    bytes_to_data_uri_with(bytes, format.mime(), options)
+}
+
+/// Fails with [`Error::TooLarge`](crate::Error::TooLarge) before encoding anything when the data URI
+/// would exceed [`EncodeOptions::max_len`].
+pub fn video_to_data_uri(
+    bytes: &[u8],
+    format: VideoFormat,
+    options: &EncodeOptions,
+) -> Result<DataUri> {
+    bytes_to_data_uri_with(bytes, format.mime(), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn media_data_uris() {
+        let options = EncodeOptions::default();
+        assert_eq!(
+            audio_to_data_uri(b"ID3", AudioFormat::Mp3, &options).unwrap(),
+            "data:audio/mpeg;base64,SUQz"
+        );
+        assert_eq!(
+            video_to_data_uri(b"\x1a\x45\xdf\xa3", VideoFormat::WebM, &options).unwrap(),
+            "data:video/webm;base64,GkXfow=="
+        );
+        let options = EncodeOptions {
+            max_len: Some(27),
+            ..Default::default()
+        };
+        assert!(audio_to_data_uri(b"ID3", AudioFormat::Mp3, &options).is_ok());
+        assert!(matches!(
+            audio_to_data_uri(b"ID3\x04", AudioFormat::Mp3, &options),
+            Err(Error::TooLarge {
+                actual: 31,
+                limit: 27
+            })
+        ));
+    }
+}
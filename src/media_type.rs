@@ -0,0 +1,232 @@
+use crate::Encoding;
+use std::fmt;
+
+/// Media types commonly embedded in data URIs, so they don't have to be
+/// spelled out and can't be misspelled.
+///
+/// Functions taking a media type accept it as well as a `&mime::Mime`, which
+/// converts to the matching variant or to [`MediaType::Custom`].
+///
+/// ```
+/// use data_uri_utils::{bytes_to_data_uri, MediaType};
+///
+/// assert_eq!(MediaType::from_extension("SVG"), Some(MediaType::SvgXml));
+/// assert_eq!(MediaType::SvgXml.as_mime(), mime::IMAGE_SVG);
+/// let uri = bytes_to_data_uri(b"wOF2", MediaType::Woff2);
+/// assert_eq!(uri, "data:font/woff2;base64,d09GMg==");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MediaType {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+    Avif,
+    Bmp,
+    Tiff,
+    Ico,
+    SvgXml,
+    Woff,
+    Woff2,
+    Ttf,
+    Otf,
+    Pdf,
+    Css,
+    Html,
+    PlainText,
+    Json,
+    JavaScript,
+    OctetStream,
+    /// Any other media type, parameters included.
+    Custom(mime::Mime),
+}
+
+/// The essence of each variant and its file extensions, the first being the
+/// usual one.
+const REGISTRY: &[(MediaType, &str, &[&str])] = &[
+    (MediaType::Png, "image/png", &["png"]),
+    (
+        MediaType::Jpeg,
+        "image/jpeg",
+        &["jpg", "jpeg", "jpe", "jfif"],
+    ),
+    (MediaType::Gif, "image/gif", &["gif"]),
+    (MediaType::WebP, "image/webp", &["webp"]),
+    (MediaType::Avif, "image/avif", &["avif"]),
+    (MediaType::Bmp, "image/bmp", &["bmp"]),
+    (MediaType::Tiff, "image/tiff", &["tif", "tiff"]),
+    (MediaType::Ico, "image/x-icon", &["ico", "cur"]),
+    (MediaType::SvgXml, "image/svg+xml", &["svg"]),
+    (MediaType::Woff, "font/woff", &["woff"]),
+    (MediaType::Woff2, "font/woff2", &["woff2"]),
+    (MediaType::Ttf, "font/ttf", &["ttf"]),
+    (MediaType::Otf, "font/otf", &["otf"]),
+    (MediaType::Pdf, "application/pdf", &["pdf"]),
+    (MediaType::Css, "text/css", &["css"]),
+    (MediaType::Html, "text/html", &["html", "htm"]),
+    (MediaType::PlainText, "text/plain", &["txt", "text"]),
+    (MediaType::Json, "application/json", &["json"]),
+    (MediaType::JavaScript, "text/javascript", &["js", "mjs"]),
+    (MediaType::OctetStream, "application/octet-stream", &["bin"]),
+];
+
+/// Other names found in the wild for the registered types.
+const ALIASES: &[(&str, MediaType)] = &[
+    ("image/jpg", MediaType::Jpeg),
+    ("image/pjpeg", MediaType::Jpeg),
+    ("image/vnd.microsoft.icon", MediaType::Ico),
+    ("application/font-woff", MediaType::Woff),
+    ("application/x-font-ttf", MediaType::Ttf),
+    ("application/javascript", MediaType::JavaScript),
+    ("application/x-javascript", MediaType::JavaScript),
+];
+
+impl MediaType {
+    /// The media type of a file extension, with or without its leading dot,
+    /// case-insensitively. `None` for unknown extensions.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        REGISTRY
+            .iter()
+            .find(|(_, _, extensions)| {
+                extensions
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(extension))
+            })
+            .map(|(media_type, _, _)| media_type.clone())
+            .or_else(|| mime_guess::from_ext(extension).first().map(MediaType::from))
+    }
+
+    /// `type/subtype` without parameters.
+    pub fn essence(&self) -> &str {
+        match self {
+            MediaType::Custom(mime) => mime.essence_str(),
+            known => REGISTRY
+                .iter()
+                .find(|(media_type, _, _)| media_type == known)
+                .map(|(_, essence, _)| *essence)
+                .expect("every variant but Custom is registered"),
+        }
+    }
+
+    pub fn as_mime(&self) -> mime::Mime {
+        match self {
+            MediaType::Custom(mime) => mime.clone(),
+            known => known.essence().parse().unwrap(),
+        }
+    }
+
+    /// The usual file extension, `None` for [`MediaType::Custom`].
+    pub fn extension(&self) -> Option<&'static str> {
+        REGISTRY
+            .iter()
+            .find(|(media_type, _, _)| media_type == self)
+            .map(|(_, _, extensions)| extensions[0])
+    }
+
+    /// [`Encoding::Percent`] for textual types, whose percent-encoded payload
+    /// is shorter and readable, [`Encoding::Base64`] for binary ones.
+    pub fn default_encoding(&self) -> Encoding {
+        match self {
+            MediaType::SvgXml
+            | MediaType::Css
+            | MediaType::Html
+            | MediaType::PlainText
+            | MediaType::Json
+            | MediaType::JavaScript => Encoding::Percent,
+            MediaType::Custom(mime)
+                if mime.type_() == mime::TEXT
+                    || matches!(mime.suffix(), Some(mime::XML | mime::JSON)) =>
+            {
+                Encoding::Percent
+            }
+            _ => Encoding::Base64,
+        }
+    }
+}
+
+impl From<&mime::Mime> for MediaType {
+    /// Media types with parameters, such as a `charset`, stay
+    /// [`MediaType::Custom`] to keep them.
+    fn from(mime: &mime::Mime) -> Self {
+        if mime.params().next().is_some() {
+            return MediaType::Custom(mime.clone());
+        }
+        let essence = mime.essence_str();
+        REGISTRY
+            .iter()
+            .find(|(_, known, _)| known.eq_ignore_ascii_case(essence))
+            .map(|(media_type, _, _)| media_type)
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias.eq_ignore_ascii_case(essence))
+                    .map(|(_, media_type)| media_type)
+            })
+            .cloned()
+            .unwrap_or_else(|| MediaType::Custom(mime.clone()))
+    }
+}
+
+impl From<mime::Mime> for MediaType {
+    fn from(mime: mime::Mime) -> Self {
+        MediaType::from(&mime)
+    }
+}
+
+impl From<MediaType> for mime::Mime {
+    fn from(media_type: MediaType) -> Self {
+        match media_type {
+            MediaType::Custom(mime) => mime,
+            known => known.as_mime(),
+        }
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaType::Custom(mime) => mime.fmt(f),
+            known => f.write_str(known.essence()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn registry() {
+        assert_eq!(MediaType::from(&mime::IMAGE_PNG), MediaType::Png);
+        let uri = DataUri::parse("data:image/svg+xml,%3Csvg/%3E").unwrap();
+        assert_eq!(uri.media_type(), MediaType::SvgXml);
+        assert_eq!(
+            MediaType::from("application/x-javascript".parse::<mime::Mime>().unwrap()),
+            MediaType::JavaScript
+        );
+        assert_eq!(
+            MediaType::from(&mime::TEXT_PLAIN_UTF_8),
+            MediaType::Custom(mime::TEXT_PLAIN_UTF_8)
+        );
+        assert_eq!(MediaType::from_extension(".JPEG"), Some(MediaType::Jpeg));
+        assert_eq!(
+            MediaType::from_extension("ico").unwrap().extension(),
+            Some("ico")
+        );
+        assert_eq!(
+            MediaType::from_extension("wasm").unwrap().to_string(),
+            "application/wasm"
+        );
+        assert_eq!(MediaType::from_extension("no-such-extension"), None);
+
+        assert_eq!(MediaType::SvgXml.default_encoding(), Encoding::Percent);
+        assert_eq!(MediaType::Pdf.default_encoding(), Encoding::Base64);
+        let atom = MediaType::from("application/atom+xml".parse::<mime::Mime>().unwrap());
+        assert_eq!(atom.default_encoding(), Encoding::Percent);
+        assert_eq!(
+            DataUriBuilder::for_media_type(MediaType::SvgXml).build_str("<svg/>"),
+            "data:image/svg+xml,%3Csvg%2F%3E"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Async variants that keep encoding off the async runtime threads.
+//!
+//! Encoding and base64 run on tokio's blocking thread pool through
+//! [`tokio::task::spawn_blocking`], so the images are taken by value.
+
+use crate::{DataUri, Result};
+use std::path::PathBuf;
+
+/// Async [`file_to_data_uri`](crate::file_to_data_uri), reading the file with
+/// [`tokio::fs`].
+pub async fn file_to_data_uri_async(path: impl Into<PathBuf>) -> Result<DataUri> {
+    let path = path.into();
+    let bytes = tokio::fs::read(&path).await?;
+    let uri = spawn_blocking(move || crate::file::file_bytes_to_data_uri(&path, &bytes)).await?;
+    Ok(uri)
+}
+
+#[cfg(feature = "image")]
+pub async fn image_to_data_uri_async<T>(
+    image: T,
+    format: crate::Format,
+    options: crate::EncodeOptions,
+) -> Result<DataUri>
+where
+    T: crate::EncodableImage + Send + 'static,
+{
+    spawn_blocking(move || crate::image_to_data_uri_with(&image, format, &options)).await?
+}
+
+#[cfg(feature = "image")]
+pub async fn image_to_png_data_uri_async<T>(image: T) -> Result<DataUri>
+where
+    T: crate::EncodableImage + Send + 'static,
+{
+    spawn_blocking(move || crate::image_to_png_data_uri(&image)).await?
+}
+
+#[cfg(feature = "image")]
+pub async fn image_to_jpeg_data_uri_async<T>(image: T, quality: u8) -> Result<DataUri>
+where
+    T: crate::EncodableImage + Send + 'static,
+{
+    spawn_blocking(move || crate::image_to_jpeg_data_uri(&image, quality)).await?
+}
+
+/// Async [`transcode_data_uri`](crate::transcode_data_uri).
+#[cfg(feature = "image")]
+pub async fn transcode_data_uri_async(
+    uri: String,
+    format: crate::Format,
+    options: crate::EncodeOptions,
+) -> Result<DataUri> {
+    spawn_blocking(move || crate::transcode_data_uri(&uri, format, &options)).await?
+}
+
+/// Runs `f` on the blocking pool, resuming its panic if it panicked.
+async fn spawn_blocking<R: Send + 'static>(f: impl FnOnce() -> R + Send + 'static) -> Result<R> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|error| match error.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(error) => std::io::Error::from(error).into(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[tokio::test]
+    async fn async_file() {
+        let path = std::env::temp_dir().join("data-uri-utils-async-test.json");
+        std::fs::write(&path, "[]").unwrap();
+        assert_eq!(
+            file_to_data_uri_async(&path).await.unwrap(),
+            file_to_data_uri(&path).unwrap()
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[tokio::test]
+    async fn async_image() {
+        let image = image::RgbImage::new(2, 2);
+        let expected = image_to_png_data_uri(&image).unwrap();
+        assert_eq!(
+            image_to_png_data_uri_async(image.clone()).await.unwrap(),
+            expected
+        );
+        assert_eq!(
+            transcode_data_uri_async(
+                expected.to_string(),
+                Format::Png(PngOptions::default()),
+                EncodeOptions::default()
+            )
+            .await
+            .unwrap(),
+            expected
+        );
+    }
+}
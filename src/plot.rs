@@ -0,0 +1,58 @@
+use crate::{rgb8_to_png_data_uri, DataUri, Error, Result};
+use plotters::coord::Shift;
+use plotters::prelude::{BitMapBackend, DrawingArea, IntoDrawingArea};
+
+/// Renders a chart drawn by `draw` on a `width` × `height` bitmap to a PNG
+/// data URI.
+///
+/// ```
+/// use plotters::prelude::*;
+///
+/// let uri = data_uri_utils::plot_to_png_data_uri(64, 32, |root| {
+///     root.fill(&WHITE)?;
+///     root.draw(&Rectangle::new([(0, 0), (32, 32)], RED.filled()))?;
+///     Ok::<_, DrawingAreaErrorKind<_>>(())
+/// })?;
+/// assert!(uri.starts_with("data:image/png;base64,"));
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn plot_to_png_data_uri<E>(
+    width: u32,
+    height: u32,
+    draw: impl FnOnce(&DrawingArea<BitMapBackend<'_>, Shift>) -> Result<(), E>,
+) -> Result<DataUri>
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidSize { width, height });
+    }
+    let mut pixels = vec![0; width as usize * height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut pixels, (width, height)).into_drawing_area();
+        draw(&root).map_err(|error| Error::Plot(error.into()))?;
+        root.present().map_err(|error| Error::Plot(error.into()))?;
+    }
+    rgb8_to_png_data_uri(&pixels, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn plot_png() {
+        let uri = plot_to_png_data_uri(4, 2, |root| {
+            root.fill(&BLUE)?;
+            Ok::<_, DrawingAreaErrorKind<_>>(())
+        })
+        .unwrap();
+        let image = data_uri_to_image(&uri).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.get_pixel(3, 1).0, [0, 0, 255]);
+
+        let failed = plot_to_png_data_uri(4, 2, |_| Err("no data"));
+        assert!(matches!(failed, Err(Error::Plot(error)) if error.to_string() == "no data"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::svg::has_active_content;
+use crate::{data_uri_dimensions, DataUri, MediaType, Result, ValidationError};
+
+/// Limits for decoding data URIs from untrusted input, guarding against
+/// decompression and pixel bombs.
+///
+/// The default accepts any media type, payloads up to 16 MiB and images up
+/// to 8192×8192 pixels, and rejects SVGs with scripts.
+///
+/// It is applied by [`DecodePolicy::decode`], the `_with` variants of
+/// [`data_uri_to_bytes`](crate::data_uri_to_bytes), `data_uri_to_rgba8`,
+/// `data_uri_to_premultiplied_rgba8` and `transcode_data_uri`, and by
+/// `data_uri_to_image_with` through `DecodeOptions::policy`.
+///
+/// ```
+/// use data_uri_utils::{DecodePolicy, Error, MediaType, ValidationError};
+///
+/// let policy = DecodePolicy {
+///     allowed_mimes: vec![MediaType::Png, MediaType::SvgXml],
+///     ..DecodePolicy::default()
+/// };
+/// assert_eq!(policy.decode("data:image/svg+xml,%3Csvg/%3E")?, b"<svg/>");
+/// assert!(matches!(
+///     policy.decode("data:image/svg+xml,%3Csvg onload='x()'/%3E"),
+///     Err(Error::Validation(ValidationError::ActiveSvgContent))
+/// ));
+/// # Ok::<_, Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodePolicy {
+    /// Largest decoded payload in bytes, checked before decoding and again
+    /// after decompressing a gzip SVG payload.
+    pub max_payload_bytes: Option<usize>,
+    /// Accepted media types, compared without parameters. Empty accepts any.
+    pub allowed_mimes: Vec<MediaType>,
+    /// Accept SVGs with scripts, event handlers, script URLs, stylesheet
+    /// processing instructions or doctypes declaring active entities, which
+    /// [`sanitize_svg`](crate::sanitize_svg) would remove.
+    pub allow_svg_scripts: bool,
+    /// Largest image area, read from the image header before decoding.
+    /// Images whose header isn't recognized aren't checked.
+    pub max_pixels: Option<u64>,
+}
+
+impl Default for DecodePolicy {
+    fn default() -> Self {
+        DecodePolicy {
+            max_payload_bytes: Some(16 * 1024 * 1024),
+            allowed_mimes: Vec::new(),
+            allow_svg_scripts: false,
+            max_pixels: Some(8192 * 8192),
+        }
+    }
+}
+
+impl DecodePolicy {
+    /// A policy accepting everything, as the functions without one do.
+    pub fn unrestricted() -> Self {
+        DecodePolicy {
+            max_payload_bytes: None,
+            allowed_mimes: Vec::new(),
+            allow_svg_scripts: true,
+            max_pixels: None,
+        }
+    }
+
+    /// Checks what can be known without decoding the payload: the media
+    /// type, the payload length and the image dimensions.
+    pub fn check(&self, uri: &DataUri) -> Result<(), ValidationError> {
+        let essence = uri.mime().essence_str();
+        if !self.allowed_mimes.is_empty()
+            && !self
+                .allowed_mimes
+                .iter()
+                .any(|allowed| allowed.essence().eq_ignore_ascii_case(essence))
+        {
+            return Err(ValidationError::MediaTypeNotAllowed(essence.to_string()));
+        }
+        if let Some(limit) = self.max_payload_bytes {
+            let actual = uri.payload_len();
+            if actual > limit {
+                return Err(ValidationError::TooLarge { actual, limit });
+            }
+        }
+        if let Some(limit) = self.max_pixels {
+            if let Ok((width, height)) = data_uri_dimensions(uri) {
+                if u64::from(width) * u64::from(height) > limit {
+                    return Err(ValidationError::TooManyPixels {
+                        width,
+                        height,
+                        limit,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects SVG payloads that can run scripts, unless they are allowed.
+    pub(crate) fn check_svg_scripts(
+        &self,
+        uri: &DataUri,
+        bytes: &[u8],
+    ) -> Result<(), ValidationError> {
+        if !self.allow_svg_scripts
+            && uri
+                .mime()
+                .essence_str()
+                .eq_ignore_ascii_case(MediaType::SvgXml.essence())
+            && has_active_content(&String::from_utf8_lossy(bytes))
+        {
+            return Err(ValidationError::ActiveSvgContent);
+        }
+        Ok(())
+    }
+
+    /// [`data_uri_to_bytes`](crate::data_uri_to_bytes) within the policy.
+    pub fn decode(&self, uri: &str) -> Result<Vec<u8>> {
+        let uri = DataUri::parse(uri)?;
+        self.check(&uri)?;
+        let bytes = uri.decode_payload()?;
+        #[cfg(feature = "gzip")]
+        let bytes = crate::gzip::decompress_svgz(
+            &uri,
+            &bytes,
+            self.max_payload_bytes.unwrap_or(usize::MAX),
+        )?
+        .into_owned();
+        self.check_svg_scripts(&uri, &bytes)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn enforce_limits() {
+        let policy = DecodePolicy {
+            max_payload_bytes: Some(8),
+            ..DecodePolicy::default()
+        };
+        assert!(matches!(
+            policy.decode("data:,123456789"),
+            Err(Error::Validation(ValidationError::TooLarge {
+                actual: 9,
+                limit: 8
+            }))
+        ));
+        // A GIF header claiming 65535×65535 pixels.
+        let bomb = bytes_to_data_uri(b"GIF89a\xff\xff\xff\xff", &mime::IMAGE_GIF);
+        let pixels = DecodePolicy {
+            max_pixels: Some(4),
+            ..DecodePolicy::default()
+        };
+        assert_eq!(
+            pixels.check(&bomb),
+            Err(ValidationError::TooManyPixels {
+                width: 65535,
+                height: 65535,
+                limit: 4
+            })
+        );
+        let scripted = "data:image/svg+xml,%3Csvg%3E%3Cscript/%3E%3C/svg%3E";
+        assert!(policy.decode(scripted).is_err());
+        assert!(DecodePolicy::unrestricted().decode(scripted).is_ok());
+        assert!(matches!(
+            DecodePolicy::default()
+                .decode("data:image/svg+xml;charset=utf-8,%3Csvg onload='x()'/%3E"),
+            Err(Error::Validation(ValidationError::ActiveSvgContent))
+        ));
+    }
+
+    #[test]
+    fn svg_scripts() {
+        let policy = DecodePolicy::default();
+        let decode = |svg: &str| policy.decode(svg_str_to_data_uri(svg).as_str());
+        for harmless in [
+            r#"<?xml version="1.0" standalone="no"?><!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd"><svg/>"#,
+            r#"<!DOCTYPE svg [<!ENTITY ns_svg "http://www.w3.org/2000/svg">]><svg xmlns="&ns_svg;"/>"#,
+            "<?xpacket begin=''?><!-- onload='x()' --><svg><a href='#javascript'/></svg>",
+        ] {
+            assert!(decode(harmless).is_ok(), "{}", harmless);
+        }
+        for active in [
+            "<svg><svg:script>x()</svg:script></svg>",
+            "<svg><foreignObject><p/></foreignObject></svg>",
+            "<svg><a xlink:href=' javascript:x()'/></svg>",
+            r#"<?xml-stylesheet type="text/xsl" href="a.xsl"?><svg/>"#,
+            r#"<!DOCTYPE svg [<!ENTITY x "<script>x()</script>">]><svg>&x;</svg>"#,
+            r#"<!DOCTYPE svg [<!ENTITY x SYSTEM "file:///etc/passwd">]><svg>&x;</svg>"#,
+        ] {
+            assert!(
+                matches!(
+                    decode(active),
+                    Err(Error::Validation(ValidationError::ActiveSvgContent))
+                ),
+                "{}",
+                active
+            );
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn decode_apis_apply_policy() {
+        let scripted = "data:image/svg+xml,%3Csvg onload='x()'/%3E";
+        assert!(data_uri_to_bytes(scripted).is_ok());
+        assert!(data_uri_to_bytes_with(scripted, &DecodePolicy::default()).is_err());
+
+        let bomb = bytes_to_data_uri(b"GIF89a\xff\xff\xff\xff", &mime::IMAGE_GIF);
+        let policy = DecodePolicy {
+            max_pixels: Some(4),
+            ..DecodePolicy::default()
+        };
+        fn too_many_pixels<T>(result: Result<T>) -> bool {
+            matches!(
+                result,
+                Err(Error::Validation(ValidationError::TooManyPixels { .. }))
+            )
+        }
+        assert!(too_many_pixels(data_uri_to_rgba8_with(&bomb, &policy)));
+        assert!(too_many_pixels(data_uri_to_premultiplied_rgba8_with(
+            &bomb, &policy
+        )));
+        let png = Format::Png(PngOptions::default());
+        assert!(too_many_pixels(transcode_data_uri_with(
+            &bomb,
+            png,
+            &EncodeOptions::default(),
+            &policy
+        )));
+
+        let pixel = image_to_png_data_uri(&image::RgbaImage::new(2, 2)).unwrap();
+        assert_eq!(data_uri_to_rgba8_with(&pixel, &policy).unwrap().1, 2);
+        assert!(too_many_pixels(data_uri_to_rgba8_with(
+            &image_to_png_data_uri(&image::RgbaImage::new(3, 3)).unwrap(),
+            &policy
+        )));
+        assert!(transcode_data_uri_with(&pixel, png, &EncodeOptions::default(), &policy).is_ok());
+    }
+}
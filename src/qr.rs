@@ -0,0 +1,57 @@
+use crate::encoding::push_payload;
+use crate::{Base64Alphabet, DataUri, DataUriBuilder, EncodeSet, Encoding};
+
+/// Encodes the payload as [`Encoding::Base45`] with an upper case header, so
+/// the whole URI stays within the QR code alphanumeric mode.
+///
+/// Media type parameter values are upper cased too. [`DataUri::parse`] and
+/// [`data_uri_to_bytes`](crate::data_uri_to_bytes) read the result back.
+///
+/// ```
+/// let uri = data_uri_utils::bytes_to_qr_data_uri(b"<svg/>", &mime::IMAGE_SVG);
+/// assert_eq!(uri, "DATA:IMAGE/SVG+XML;BASE45,+S7Q/EY/5");
+/// ```
+pub fn bytes_to_qr_data_uri(bytes: &[u8], mime: &mime::Mime) -> DataUri {
+    let mut uri = DataUriBuilder::new()
+        .media_type(mime)
+        .encoding(Encoding::Base45)
+        .header();
+    uri.make_ascii_uppercase();
+    push_payload(
+        &mut uri,
+        bytes,
+        Encoding::Base45,
+        EncodeSet::default(),
+        Base64Alphabet::default(),
+        true,
+    );
+    DataUri::from_parts(uri, mime.clone(), Encoding::Base45)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn base45_round_trip() {
+        // RFC 9285 examples.
+        let uri = bytes_to_qr_data_uri(b"Hello!!", &mime::TEXT_PLAIN_UTF_8);
+        assert_eq!(uri, "DATA:TEXT/PLAIN;CHARSET=UTF-8;BASE45,%69 VD92EX0");
+        assert_eq!(uri.payload_len(), 7);
+        let parsed = DataUri::parse(&uri).unwrap();
+        assert_eq!(parsed.encoding(), Encoding::Base45);
+        assert_eq!(parsed.mime(), &mime::TEXT_PLAIN_UTF_8);
+        assert_eq!(parsed.decode_text().unwrap(), "Hello!!");
+        assert_eq!(
+            data_uri_to_bytes("data:;base45,UJCLQE7W581").unwrap(),
+            b"base-45"
+        );
+        assert_eq!(data_uri_to_bytes("data:;base45,").unwrap(), b"");
+        for invalid in ["GGW", "a1", "1"] {
+            assert!(matches!(
+                data_uri_to_bytes(&format!("data:;base45,{}", invalid)),
+                Err(Error::Parse(ParseError::InvalidBase45))
+            ));
+        }
+    }
+}
@@ -0,0 +1,1389 @@
+use crate::{
+    base64_data_uri_len, bytes_to_data_uri, bytes_to_data_uri_with, Base64Alphabet, DataUri,
+    DataUriBuilder, DecodePolicy, EncodeOptions, Error, Result,
+};
+use image::EncodableLayout;
+use image::ImageEncoder;
+use image::PixelWithColorType;
+use std::sync::LazyLock;
+
+mod apng;
+mod chunks;
+mod float;
+mod interlace;
+mod jpeg;
+mod metadata;
+mod palette;
+
+pub use apng::frames_to_apng_data_uri;
+pub use chunks::strip_png_metadata;
+pub use float::{float_image_to_png_data_uri, tone_map_image, ToneMapping};
+pub use metadata::strip_metadata_data_uri;
+
+static IMAGE_WEBP: LazyLock<mime::Mime> = LazyLock::new(|| "image/webp".parse().unwrap());
+static IMAGE_TIFF: LazyLock<mime::Mime> = LazyLock::new(|| "image/tiff".parse().unwrap());
+static IMAGE_X_ICON: LazyLock<mime::Mime> = LazyLock::new(|| "image/x-icon".parse().unwrap());
+#[cfg(feature = "avif")]
+static IMAGE_AVIF: LazyLock<mime::Mime> = LazyLock::new(|| "image/avif".parse().unwrap());
+
+/// WebP compression mode.
+///
+/// Lossy encoding relies on libwebp and requires the `webp-lossy` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebPQuality {
+    Lossless,
+    /// Quality from 0 (smallest) to 100 (best).
+    #[cfg(feature = "webp-lossy")]
+    Lossy(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngOptions {
+    pub compression: image::codecs::png::CompressionType,
+    pub filter: image::codecs::png::FilterType,
+    /// Write an indexed PNG. Images with more than `max_colors` colors are
+    /// quantized, which is lossy but typically shrinks screenshots a lot.
+    pub quantize_to_palette: bool,
+    /// Palette size when quantizing, from 2 to 256.
+    pub max_colors: u16,
+    /// Drop metadata chunks such as `tIME` and `tEXt`, see [`strip_png_metadata`].
+    pub strip_metadata: bool,
+    /// Embed the color profile of the source image when transcoding.
+    pub preserve_icc: bool,
+    /// Store the pixels as Adam7 passes, which browsers render progressively.
+    /// Interlaced PNGs are usually a little larger.
+    pub interlace: bool,
+    /// Store 8-bit images losslessly with the smallest color type that fits,
+    /// grayscale, without alpha or indexed, found by scanning every pixel.
+    /// Ignored when quantizing.
+    pub reduce_color_type: bool,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            compression: Default::default(),
+            filter: Default::default(),
+            quantize_to_palette: false,
+            max_colors: 256,
+            strip_metadata: false,
+            preserve_icc: false,
+            interlace: false,
+            reduce_color_type: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChromaSubsampling {
+    /// Full color resolution, best for sharp edges and text.
+    Yuv444,
+    /// Half horizontal color resolution.
+    Yuv422,
+    /// Half horizontal and vertical color resolution, the smallest output.
+    #[default]
+    Yuv420,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JpegOptions {
+    /// Quality from 1 to 100.
+    pub quality: u8,
+    /// Encode in several passes, so a coarse preview shows up while loading.
+    pub progressive: bool,
+    pub subsampling: ChromaSubsampling,
+    /// Drop the color channels, which also drops the chroma data entirely.
+    pub grayscale: bool,
+    /// Embed the color profile of the source image when transcoding.
+    pub preserve_icc: bool,
+    /// Color that translucent pixels are composited onto, as JPEG has no
+    /// alpha. Encoding an image that isn't opaque fails without one.
+    pub background: Option<image::Rgb<u8>>,
+}
+
+impl JpegOptions {
+    pub fn new(quality: u8) -> Self {
+        JpegOptions {
+            quality,
+            ..JpegOptions::default()
+        }
+    }
+}
+
+impl Default for JpegOptions {
+    fn default() -> Self {
+        JpegOptions {
+            quality: 80,
+            progressive: false,
+            subsampling: ChromaSubsampling::default(),
+            grayscale: false,
+            preserve_icc: false,
+            background: None,
+        }
+    }
+}
+
+/// Raster output format along with its encoder settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png(PngOptions),
+    /// Translucent pixels are composited onto [`JpegOptions::background`].
+    Jpeg(JpegOptions),
+    WebP(WebPQuality),
+    /// Only RGB and RGBA images, see [`image_to_gif_data_uri`] for other color types.
+    Gif,
+    Bmp,
+    Tiff,
+    Ico,
+    /// `quality` ranges from 1 to 100, `speed` from 1 (slowest, smallest) to 10.
+    #[cfg(feature = "avif")]
+    Avif {
+        quality: u8,
+        speed: u8,
+    },
+}
+
+impl Format {
+    /// Whether decoding the encoded image gives back its exact pixels. PNG
+    /// is lossless unless quantized, float images aside, which are tone
+    /// mapped to 16 bits.
+    pub fn is_lossless(&self) -> bool {
+        match self {
+            Format::Png(options) => !options.quantize_to_palette,
+            Format::WebP(quality) => *quality == WebPQuality::Lossless,
+            Format::Bmp | Format::Tiff | Format::Ico => true,
+            Format::Jpeg(_) | Format::Gif => false,
+            #[cfg(feature = "avif")]
+            Format::Avif { .. } => false,
+        }
+    }
+
+    pub fn mime(&self) -> mime::Mime {
+        match self {
+            Format::Png(_) => mime::IMAGE_PNG,
+            Format::Jpeg(_) => mime::IMAGE_JPEG,
+            Format::WebP(_) => IMAGE_WEBP.clone(),
+            Format::Gif => mime::IMAGE_GIF,
+            Format::Bmp => mime::IMAGE_BMP,
+            Format::Tiff => IMAGE_TIFF.clone(),
+            Format::Ico => IMAGE_X_ICON.clone(),
+            #[cfg(feature = "avif")]
+            Format::Avif { .. } => IMAGE_AVIF.clone(),
+        }
+    }
+}
+
+/// Images the encoding functions take. Image buffers are encoded straight
+/// from their samples, [`DynamicImage`](image::DynamicImage)s as 8-bit RGBA,
+/// converted first unless they already are. Other views, such as
+/// sub-images, can be copied into an image buffer with `to_image`.
+pub trait EncodableImage: image::GenericImageView<Pixel: PixelWithColorType> {
+    /// Calls `f` with the samples row by row, in native byte order.
+    fn with_samples<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R;
+}
+
+impl<P, C> EncodableImage for image::ImageBuffer<P, C>
+where
+    P: PixelWithColorType,
+    [P::Subpixel]: EncodableLayout,
+    C: std::ops::Deref<Target = [P::Subpixel]>,
+{
+    fn with_samples<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        let len = usize::from(P::CHANNEL_COUNT) * self.width() as usize * self.height() as usize;
+        f(self.as_raw()[..len].as_bytes())
+    }
+}
+
+impl EncodableImage for image::DynamicImage {
+    fn with_samples<R>(&self, f: impl FnOnce(&[u8]) -> R) -> R {
+        match self {
+            image::DynamicImage::ImageRgba8(image) => image.with_samples(f),
+            image => image.to_rgba8().with_samples(f),
+        }
+    }
+}
+
+fn write_image<T, E>(image: &T, encoder: E) -> image::ImageResult<()>
+where
+    T: EncodableImage,
+    E: ImageEncoder,
+{
+    image.with_samples(|samples| {
+        encoder.write_image(
+            samples,
+            image.width(),
+            image.height(),
+            <T as image::GenericImageView>::Pixel::COLOR_TYPE,
+        )
+    })
+}
+
+fn encode_image<T, W>(image: &T, format: Format, writer: W) -> image::ImageResult<()>
+where
+    T: EncodableImage,
+    W: std::io::Write,
+{
+    encode_image_with_icc(image, format, None, writer)
+}
+
+/// Same as [`encode_image`], embedding `icc_profile` when the format options
+/// ask to preserve it.
+fn encode_image_with_icc<T, W>(
+    image: &T,
+    format: Format,
+    icc_profile: Option<&[u8]>,
+    mut writer: W,
+) -> image::ImageResult<()>
+where
+    T: EncodableImage,
+    W: std::io::Write,
+{
+    use image::codecs::*;
+
+    match format {
+        Format::Png(options) if options.preserve_icc && icc_profile.is_some() => {
+            let mut buffer = Vec::new();
+            let png_options = PngOptions {
+                preserve_icc: false,
+                ..options
+            };
+            encode_image(image, Format::Png(png_options), &mut buffer)?;
+            let iccp = icc_profile.and_then(|profile| chunks::insert_iccp(&buffer, profile));
+            writer.write_all(&iccp.unwrap_or(buffer))?
+        }
+        Format::Png(options) if options.strip_metadata => {
+            let mut buffer = Vec::new();
+            let options = PngOptions {
+                strip_metadata: false,
+                ..options
+            };
+            encode_image(image, Format::Png(options), &mut buffer)?;
+            let stripped = chunks::strip_png_metadata(&buffer).unwrap_or(buffer);
+            writer.write_all(&stripped)?
+        }
+        Format::Png(options) if options.interlace => {
+            let mut buffer = Vec::new();
+            let options = PngOptions {
+                interlace: false,
+                ..options
+            };
+            encode_image(image, Format::Png(options), &mut buffer)?;
+            let interlaced = interlace::interlace_png(&buffer, options.compression);
+            writer.write_all(&interlaced.unwrap_or(buffer))?
+        }
+        Format::Png(options)
+            if options.reduce_color_type
+                && !options.quantize_to_palette
+                && <T as image::GenericImageView>::Pixel::COLOR_TYPE.bytes_per_pixel()
+                    == <T as image::GenericImageView>::Pixel::COLOR_TYPE.channel_count() =>
+        {
+            write_image(image, palette::ReducedPngEncoder::new(&mut writer, options))?
+        }
+        Format::Png(options) if options.quantize_to_palette => {
+            write_image(image, palette::PalettePngEncoder::new(&mut writer, options))?
+        }
+        Format::Png(options) => {
+            let encoder =
+                png::PngEncoder::new_with_quality(&mut writer, options.compression, options.filter);
+            match <T as image::GenericImageView>::Pixel::COLOR_TYPE {
+                image::ColorType::Rgb32F | image::ColorType::Rgba32F => {
+                    float::write_float_image(image, encoder)?
+                }
+                _ => write_image(image, encoder)?,
+            }
+        }
+        Format::Jpeg(options) => {
+            let mut encoder = self::jpeg::JpegEncoder::new(&mut writer, options);
+            if options.preserve_icc {
+                encoder = encoder.icc_profile(icc_profile);
+            }
+            write_image(image, encoder)?
+        }
+        Format::WebP(WebPQuality::Lossless) => {
+            write_image(image, webp::WebPEncoder::new_lossless(&mut writer))?
+        }
+        #[cfg(feature = "webp-lossy")]
+        #[allow(deprecated)]
+        Format::WebP(WebPQuality::Lossy(quality)) => write_image(
+            image,
+            webp::WebPEncoder::new_with_quality(&mut writer, webp::WebPQuality::lossy(quality)),
+        )?,
+        Format::Gif => image.with_samples(|samples| {
+            gif::GifEncoder::new(&mut writer).encode(
+                samples,
+                image.width(),
+                image.height(),
+                <T as image::GenericImageView>::Pixel::COLOR_TYPE,
+            )
+        })?,
+        Format::Bmp => write_image(image, bmp::BmpEncoder::new(&mut writer))?,
+        Format::Tiff => {
+            // The TIFF encoder needs to seek back into its output.
+            let mut buffer = std::io::Cursor::new(Vec::new());
+            write_image(image, tiff::TiffEncoder::new(&mut buffer))?;
+            writer.write_all(buffer.get_ref())?
+        }
+        Format::Ico => write_image(image, ico::IcoEncoder::new(&mut writer))?,
+        #[cfg(feature = "avif")]
+        Format::Avif { quality, speed } => write_image(
+            image,
+            avif::AvifEncoder::new_with_speed_quality(&mut writer, speed, quality),
+        )?,
+    }
+    Ok(())
+}
+
+pub fn image_to_data_uri<T>(image: &T, format: Format) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    let mut buffer = Vec::new();
+    encode_image(image, format, &mut buffer)?;
+    Ok(bytes_to_data_uri(&buffer, format.mime()))
+}
+
+pub fn image_to_data_uri_with<T>(
+    image: &T,
+    format: Format,
+    options: &EncodeOptions,
+) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    encode_data_uri(image, format, None, options)
+}
+
+fn encode_data_uri<T>(
+    image: &T,
+    format: Format,
+    icc_profile: Option<&[u8]>,
+    options: &EncodeOptions,
+) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    let mut buffer = Vec::new();
+    encode_image_with_icc(image, format, icc_profile, &mut buffer)?;
+    bytes_to_data_uri_with(&buffer, format.mime(), options)
+}
+
+/// Encodes the image with each candidate format and keeps the shortest data URI.
+///
+/// Candidates that fail to encode the image are skipped, the error of the last
+/// one is returned if none succeeds.
+pub fn image_to_smallest_data_uri<T>(
+    image: &T,
+    candidates: &[Format],
+    options: &EncodeOptions,
+) -> Result<(DataUri, Format)>
+where
+    T: EncodableImage,
+{
+    let mut smallest: Option<(usize, Vec<u8>, Format)> = None;
+    let mut error = None;
+    for &format in candidates {
+        let mut buffer = Vec::new();
+        if let Err(encode_error) = encode_image(image, format, &mut buffer) {
+            error = Some(encode_error);
+            continue;
+        }
+        let header_len = DataUriBuilder::new()
+            .media_type(format.mime())
+            .header()
+            .len();
+        let len = header_len + options.base64_len(buffer.len());
+        if smallest
+            .as_ref()
+            .map_or(true, |(smallest, ..)| len < *smallest)
+        {
+            smallest = Some((len, buffer, format));
+        }
+    }
+    let Some((_, buffer, format)) = smallest else {
+        return Err(error
+            .unwrap_or_else(|| {
+                image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                    image::error::ParameterErrorKind::Generic("no candidate format".to_string()),
+                ))
+            })
+            .into());
+    };
+    let uri = bytes_to_data_uri_with(&buffer, format.mime(), options)?;
+    Ok((uri, format))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailOptions {
+    /// Resampling filter used when downscaling.
+    pub filter: image::imageops::FilterType,
+    pub encode: EncodeOptions,
+}
+
+impl Default for ThumbnailOptions {
+    fn default() -> Self {
+        ThumbnailOptions {
+            filter: image::imageops::FilterType::Lanczos3,
+            encode: EncodeOptions::default(),
+        }
+    }
+}
+
+/// Downscales the image to fit within `max_width` by `max_height`, keeping its
+/// aspect ratio, then encodes it. Smaller images are encoded as is.
+pub fn image_to_thumbnail_data_uri<T>(
+    image: &T,
+    max_width: u32,
+    max_height: u32,
+    format: Format,
+    options: &ThumbnailOptions,
+) -> Result<DataUri>
+where
+    T: EncodableImage,
+    <T as image::GenericImageView>::Pixel: 'static,
+    [<<T as image::GenericImageView>::Pixel as image::Pixel>::Subpixel]: image::EncodableLayout,
+{
+    let (width, height) = image.dimensions();
+    if width <= max_width && height <= max_height {
+        return image_to_data_uri_with(image, format, &options.encode);
+    }
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    );
+    let scaled = |length: u32| ((f64::from(length) * scale).round() as u32).max(1);
+    let thumbnail = image::imageops::resize(image, scaled(width), scaled(height), options.filter);
+    image_to_data_uri_with(&thumbnail, format, &options.encode)
+}
+
+/// Encodes every image with the same format, returning one result per image in
+/// input order. With the `rayon` feature the images are encoded in parallel.
+pub fn encode_batch<'a, I, T>(
+    images: I,
+    format: Format,
+    options: &EncodeOptions,
+) -> Vec<Result<DataUri>>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: EncodableImage + Sync + 'a,
+{
+    let encode = |image: &T| image_to_data_uri_with(image, format, options);
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        let images: Vec<&T> = images.into_iter().collect();
+        images.into_par_iter().map(encode).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        images.into_iter().map(encode).collect()
+    }
+}
+
+/// Length of the data URI [`image_to_data_uri`] would produce.
+///
+/// The image is still encoded, but the output is only counted.
+pub fn estimated_data_uri_len<T>(image: &T, format: Format) -> Result<usize>
+where
+    T: EncodableImage,
+{
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    encode_image(image, format, &mut counter)?;
+    Ok(base64_data_uri_len(format.mime(), counter.0))
+}
+
+/// Streams the data URI into `writer`, base64-encoding the encoder output
+/// on the fly instead of buffering it.
+pub fn write_image_data_uri<T, W>(image: &T, format: Format, mut writer: W) -> Result<()>
+where
+    T: EncodableImage,
+    W: std::io::Write,
+{
+    let header = DataUriBuilder::new().media_type(format.mime()).header();
+    writer.write_all(header.as_bytes())?;
+    let engine = Base64Alphabet::Standard.engine(true);
+    let mut base64_writer = base64::write::EncoderWriter::new(&mut writer, &engine);
+    encode_image(image, format, &mut base64_writer)?;
+    base64_writer.finish()?;
+    Ok(())
+}
+
+pub fn image_to_png_data_uri<T>(image: &T) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Png(PngOptions::default()))
+}
+
+pub fn image_to_jpeg_data_uri<T>(image: &T, quality: u8) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Jpeg(JpegOptions::new(quality)))
+}
+
+pub fn image_to_webp_data_uri<T>(image: &T, quality: WebPQuality) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::WebP(quality))
+}
+
+/// `quality` ranges from 1 to 100, `speed` from 1 (slowest, smallest) to 10.
+#[cfg(feature = "avif")]
+pub fn image_to_avif_data_uri<T>(image: &T, quality: u8, speed: u8) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Avif { quality, speed })
+}
+
+pub fn image_to_bmp_data_uri<T>(image: &T) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Bmp)
+}
+
+pub fn image_to_tiff_data_uri<T>(image: &T) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Tiff)
+}
+
+/// Icons are limited to 256x256 pixels, suitable for inline favicons.
+pub fn image_to_ico_data_uri<T>(image: &T) -> Result<DataUri>
+where
+    T: EncodableImage,
+{
+    image_to_data_uri(image, Format::Ico)
+}
+
+pub fn image_to_gif_data_uri<T>(image: &T) -> Result<DataUri>
+where
+    T: image::GenericImageView,
+    <T as image::GenericImageView>::Pixel: image::Pixel<Subpixel = u8>,
+{
+    let rgba = image::RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        image::Pixel::to_rgba(&image.get_pixel(x, y))
+    });
+    frames_to_gif_data_uri(
+        &[image::Frame::new(rgba)],
+        image::codecs::gif::Repeat::Infinite,
+    )
+}
+
+/// Encodes an animated GIF, each frame carries its own delay and offset.
+pub fn frames_to_gif_data_uri(
+    frames: &[image::Frame],
+    repeat: image::codecs::gif::Repeat,
+) -> Result<DataUri> {
+    let mut buffer = Vec::new();
+    {
+        let mut encoder = image::codecs::gif::GifEncoder::new(&mut buffer);
+        encoder.set_repeat(repeat)?;
+        encoder.encode_frames(frames.iter().cloned())?;
+    }
+    Ok(bytes_to_data_uri(&buffer, &mime::IMAGE_GIF))
+}
+
+pub fn rgba8_to_png_data_uri(bytes: &[u8], width: u32, height: u32) -> Result<DataUri> {
+    pixels_to_png_data_uri(
+        bytes,
+        width,
+        height,
+        width as usize * 4,
+        image::ColorType::Rgba8,
+    )
+}
+
+pub fn rgb8_to_png_data_uri(bytes: &[u8], width: u32, height: u32) -> Result<DataUri> {
+    pixels_to_png_data_uri(
+        bytes,
+        width,
+        height,
+        width as usize * 3,
+        image::ColorType::Rgb8,
+    )
+}
+
+pub fn luma8_to_png_data_uri(bytes: &[u8], width: u32, height: u32) -> Result<DataUri> {
+    pixels_to_png_data_uri(bytes, width, height, width as usize, image::ColorType::L8)
+}
+
+/// Encodes raw pixel rows, such as a GPU readback, as a PNG data URI.
+///
+/// `stride` is the length in bytes of each row including its padding.
+pub fn pixels_to_png_data_uri(
+    bytes: &[u8],
+    width: u32,
+    height: u32,
+    stride: usize,
+    color_type: image::ColorType,
+) -> Result<DataUri> {
+    let row_len = width as usize * usize::from(color_type.bytes_per_pixel());
+    let required = match height as usize {
+        0 => Some(0),
+        height => stride
+            .checked_mul(height - 1)
+            .and_then(|len| len.checked_add(row_len)),
+    };
+    let required = match required {
+        Some(required) if stride >= row_len && bytes.len() >= required => required,
+        _ => {
+            return Err(
+                image::ImageError::Parameter(image::error::ParameterError::from_kind(
+                    image::error::ParameterErrorKind::DimensionMismatch,
+                ))
+                .into(),
+            )
+        }
+    };
+    let pixels: std::borrow::Cow<'_, [u8]> = if stride == row_len {
+        bytes[..required].into()
+    } else {
+        bytes
+            .chunks(stride)
+            .take(height as usize)
+            .flat_map(|row| &row[..row_len])
+            .copied()
+            .collect::<Vec<_>>()
+            .into()
+    };
+    let mut buffer = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut buffer)
+        .write_image(&pixels, width, height, color_type)?;
+    Ok(bytes_to_data_uri(&buffer, &mime::IMAGE_PNG))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// Rotate and flip the pixels according to the EXIF orientation, so
+    /// that the image is upright once its metadata is gone.
+    pub apply_orientation: bool,
+    /// Limits checked before decoding the image, none when `None`.
+    pub policy: Option<DecodePolicy>,
+}
+
+pub fn data_uri_to_image(uri: &str) -> Result<image::DynamicImage> {
+    data_uri_to_image_with(uri, &DecodeOptions::default())
+}
+
+pub fn data_uri_to_image_with(uri: &str, options: &DecodeOptions) -> Result<image::DynamicImage> {
+    decode_image_payload(uri, options).map(|(image, _)| image)
+}
+
+/// [`data_uri_to_image_with`], also returning the decoded payload.
+fn decode_image_payload(
+    uri: &str,
+    options: &DecodeOptions,
+) -> Result<(image::DynamicImage, Vec<u8>)> {
+    let uri = DataUri::parse(uri)?;
+    if uri.mime().type_() != mime::IMAGE {
+        return Err(Error::UnsupportedMediaType(uri.mime().clone()));
+    }
+    let bytes = match &options.policy {
+        Some(policy) => policy.decode(&uri)?,
+        None => uri.decode_payload()?,
+    };
+    let image = match image::ImageFormat::from_mime_type(uri.mime().essence_str()) {
+        Some(format) => image::load_from_memory_with_format(&bytes, format),
+        None => image::load_from_memory(&bytes),
+    }?;
+    let orientation = if options.apply_orientation {
+        metadata::exif_orientation(&bytes)
+    } else {
+        None
+    };
+    let image = match orientation {
+        Some(orientation) => metadata::apply_orientation(image, orientation),
+        None => image,
+    };
+    Ok((image, bytes))
+}
+
+/// Decodes the image in `uri` to straight RGBA pixels, row by row, with its
+/// width and height, the layout GUI toolkits take for textures.
+///
+/// The EXIF orientation is applied, as browsers do.
+pub fn data_uri_to_rgba8(uri: &str) -> Result<(Vec<u8>, u32, u32)> {
+    decode_rgba8(uri, None)
+}
+
+/// [`data_uri_to_rgba8`] within `policy`.
+pub fn data_uri_to_rgba8_with(uri: &str, policy: &DecodePolicy) -> Result<(Vec<u8>, u32, u32)> {
+    decode_rgba8(uri, Some(policy.clone()))
+}
+
+fn decode_rgba8(uri: &str, policy: Option<DecodePolicy>) -> Result<(Vec<u8>, u32, u32)> {
+    let options = DecodeOptions {
+        apply_orientation: true,
+        policy,
+    };
+    let image = data_uri_to_image_with(uri, &options)?.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((image.into_raw(), width, height))
+}
+
+/// [`data_uri_to_rgba8`] with the color channels multiplied by alpha, for
+/// toolkits blending premultiplied textures such as egui's
+/// `ColorImage::from_rgba_premultiplied`.
+pub fn data_uri_to_premultiplied_rgba8(uri: &str) -> Result<(Vec<u8>, u32, u32)> {
+    decode_rgba8(uri, None).map(premultiply)
+}
+
+/// [`data_uri_to_premultiplied_rgba8`] within `policy`.
+pub fn data_uri_to_premultiplied_rgba8_with(
+    uri: &str,
+    policy: &DecodePolicy,
+) -> Result<(Vec<u8>, u32, u32)> {
+    decode_rgba8(uri, Some(policy.clone())).map(premultiply)
+}
+
+fn premultiply((mut pixels, width, height): (Vec<u8>, u32, u32)) -> (Vec<u8>, u32, u32) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = u16::from(pixel[3]);
+        for channel in &mut pixel[..3] {
+            *channel = ((u16::from(*channel) * alpha + 127) / 255) as u8;
+        }
+    }
+    (pixels, width, height)
+}
+
+/// Decodes the image in `uri` and encodes it again as `target`, converting
+/// its color type first when the target format can't store it.
+///
+/// Metadata isn't carried over, so the EXIF orientation is applied to the
+/// pixels. The color profile is kept when the PNG or JPEG options ask for it.
+pub fn transcode_data_uri(uri: &str, target: Format, options: &EncodeOptions) -> Result<DataUri> {
+    transcode(uri, target, options, None)
+}
+
+/// [`transcode_data_uri`] with `uri` decoded within `policy`.
+pub fn transcode_data_uri_with(
+    uri: &str,
+    target: Format,
+    options: &EncodeOptions,
+    policy: &DecodePolicy,
+) -> Result<DataUri> {
+    transcode(uri, target, options, Some(policy.clone()))
+}
+
+fn transcode(
+    uri: &str,
+    target: Format,
+    options: &EncodeOptions,
+    policy: Option<DecodePolicy>,
+) -> Result<DataUri> {
+    use image::DynamicImage::*;
+
+    let decode_options = DecodeOptions {
+        apply_orientation: true,
+        policy,
+    };
+    let (image, payload) = decode_image_payload(uri, &decode_options)?;
+    let icc_profile = match target {
+        Format::Png(PngOptions {
+            preserve_icc: true, ..
+        })
+        | Format::Jpeg(JpegOptions {
+            preserve_icc: true, ..
+        }) => metadata::icc_profile(&payload),
+        _ => None,
+    };
+    let high_depth = !matches!(
+        image,
+        ImageLuma8(_) | ImageLumaA8(_) | ImageRgb8(_) | ImageRgba8(_)
+    );
+    let image = match target {
+        Format::Gif => ImageRgba8(image.to_rgba8()),
+        Format::Png(_) | Format::Tiff => image,
+        _ if high_depth && image.color().has_alpha() => ImageRgba8(image.to_rgba8()),
+        _ if high_depth => ImageRgb8(image.to_rgb8()),
+        _ => image,
+    };
+    encode_dynamic_image(&image, target, icc_profile.as_deref(), options)
+}
+
+/// Encodes `image` keeping its color type.
+pub(crate) fn encode_dynamic_image(
+    image: &image::DynamicImage,
+    target: Format,
+    icc: Option<&[u8]>,
+    options: &EncodeOptions,
+) -> Result<DataUri> {
+    use image::DynamicImage::*;
+
+    match image {
+        ImageLuma8(image) => encode_data_uri(image, target, icc, options),
+        ImageLumaA8(image) => encode_data_uri(image, target, icc, options),
+        ImageRgb8(image) => encode_data_uri(image, target, icc, options),
+        ImageLuma16(image) => encode_data_uri(image, target, icc, options),
+        ImageLumaA16(image) => encode_data_uri(image, target, icc, options),
+        ImageRgb16(image) => encode_data_uri(image, target, icc, options),
+        ImageRgba16(image) => encode_data_uri(image, target, icc, options),
+        ImageRgb32F(image) => encode_data_uri(image, target, icc, options),
+        ImageRgba32F(image) => encode_data_uri(image, target, icc, options),
+        image => encode_data_uri(&image.to_rgba8(), target, icc, options),
+    }
+}
+
+/// Converts samples of any color type to 8-bit RGBA, keeping the most
+/// significant byte of 16-bit samples.
+fn to_rgba8(buf: &[u8], color_type: image::ColorType) -> Vec<u8> {
+    let samples: Vec<u8> = match color_type.bytes_per_pixel() / color_type.channel_count() {
+        1 => buf.to_vec(),
+        2 => buf
+            .chunks_exact(2)
+            .map(|sample| (u16::from_ne_bytes([sample[0], sample[1]]) >> 8) as u8)
+            .collect(),
+        _ => buf
+            .chunks_exact(4)
+            .map(|sample| {
+                let sample = f32::from_ne_bytes([sample[0], sample[1], sample[2], sample[3]]);
+                (sample.clamp(0.0, 1.0) * 255.0).round() as u8
+            })
+            .collect(),
+    };
+    let channels = usize::from(color_type.channel_count());
+    samples
+        .chunks_exact(channels)
+        .flat_map(|pixel| match *pixel {
+            [luma] => [luma, luma, luma, 255],
+            [luma, alpha] => [luma, luma, luma, alpha],
+            [red, green, blue] => [red, green, blue, 255],
+            [red, green, blue, alpha, ..] => [red, green, blue, alpha],
+            _ => unreachable!("pixels have between one and four channels"),
+        })
+        .collect()
+}
+
+/// Reads the dimensions from the image header without decoding the pixels.
+pub(crate) fn image_dimensions(bytes: &[u8], mime: &mime::Mime) -> Option<(u32, u32)> {
+    let mut reader = image::io::Reader::new(std::io::Cursor::new(bytes));
+    match image::ImageFormat::from_mime_type(mime.essence_str()) {
+        Some(format) => reader.set_format(format),
+        None => reader = reader.with_guessed_format().ok()?,
+    }
+    reader.into_dimensions().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn rgba8_pixels() {
+        let image =
+            image::RgbaImage::from_fn(2, 1, |x, _| image::Rgba([200, 100, 0, 255 - x as u8 * 127]));
+        let uri = image_to_png_data_uri(&image).unwrap();
+        let (pixels, width, height) = data_uri_to_rgba8(&uri).unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(pixels, image.as_raw().as_slice());
+        let (pixels, _, _) = data_uri_to_premultiplied_rgba8(&uri).unwrap();
+        assert_eq!(pixels, [200, 100, 0, 255, 100, 50, 0, 128]);
+
+        let gray = image::GrayImage::from_pixel(1, 1, image::Luma([9]));
+        let uri = image_to_png_data_uri(&gray).unwrap();
+        assert_eq!(data_uri_to_rgba8(&uri).unwrap().0, [9, 9, 9, 255]);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let image = image::RgbaImage::from_fn(3, 2, |x, y| image::Rgba([x as u8, y as u8, 7, 255]));
+        let uri = DataUri::parse(&image_to_png_data_uri(&image).unwrap()).unwrap();
+        assert_eq!(uri.mime(), &mime::IMAGE_PNG);
+        let decoded = image::load_from_memory(&uri.decode_payload().unwrap()).unwrap();
+        assert_eq!(decoded.to_rgba8(), image);
+
+        // Samples past the image in a longer container aren't encoded.
+        let padded = image::GrayImage::from_raw(2, 1, vec![1, 2, 3]).unwrap();
+        let uri = image_to_png_data_uri(&padded).unwrap();
+        assert_eq!(
+            data_uri_to_image(&uri).unwrap().into_luma8().into_raw(),
+            [1, 2]
+        );
+    }
+
+    #[test]
+    fn webp_lossless_round_trip() {
+        let image = image::RgbImage::from_fn(4, 4, |x, y| image::Rgb([x as u8 * 60, y as u8, 0]));
+        let uri = image_to_webp_data_uri(&image, WebPQuality::Lossless).unwrap();
+        let uri = DataUri::parse(&uri).unwrap();
+        assert_eq!(uri.mime().as_ref(), "image/webp");
+        let decoded = image::load_from_memory(&uri.decode_payload().unwrap()).unwrap();
+        assert_eq!(decoded.to_rgb8(), image);
+    }
+
+    #[cfg(feature = "avif")]
+    #[test]
+    fn avif_header() {
+        let image = image::RgbImage::from_pixel(8, 8, image::Rgb([10, 200, 30]));
+        let uri = DataUri::parse(&image_to_avif_data_uri(&image, 80, 10).unwrap()).unwrap();
+        assert_eq!(uri.mime().as_ref(), "image/avif");
+        assert_eq!(&uri.decode_payload().unwrap()[4..12], b"ftypavif");
+    }
+
+    #[test]
+    fn bmp_tiff_ico_round_trip() {
+        let image =
+            image::RgbaImage::from_fn(16, 16, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+        for (uri, mime) in [
+            (image_to_bmp_data_uri(&image), "image/bmp"),
+            (image_to_tiff_data_uri(&image), "image/tiff"),
+            (image_to_ico_data_uri(&image), "image/x-icon"),
+        ] {
+            let uri = uri.unwrap();
+            assert_eq!(DataUri::parse(&uri).unwrap().mime().as_ref(), mime);
+            assert_eq!(data_uri_to_image(&uri).unwrap().to_rgba8(), image);
+        }
+
+        let too_large = image::RgbaImage::new(257, 1);
+        assert!(image_to_ico_data_uri(&too_large).is_err());
+    }
+
+    #[test]
+    fn format_dispatch() {
+        let image = image::RgbImage::from_fn(4, 4, |x, y| image::Rgb([x as u8, y as u8, 9]));
+        let compressed = Format::Png(PngOptions {
+            compression: image::codecs::png::CompressionType::Best,
+            filter: image::codecs::png::FilterType::Paeth,
+            ..PngOptions::default()
+        });
+        for format in [compressed, Format::Gif, Format::Bmp, Format::Tiff] {
+            let uri = image_to_data_uri(&image, format).unwrap();
+            assert_eq!(DataUri::parse(&uri).unwrap().mime(), &format.mime());
+            assert_eq!(data_uri_to_image(&uri).unwrap().to_rgb8(), image);
+        }
+        assert_eq!(
+            image_to_data_uri(&image, Format::Jpeg(JpegOptions::new(90))).unwrap(),
+            image_to_jpeg_data_uri(&image, 90).unwrap()
+        );
+    }
+
+    #[test]
+    fn streaming_matches_buffered() {
+        let image = image::RgbaImage::from_fn(9, 7, |x, y| image::Rgba([x as u8, y as u8, 1, 128]));
+        for format in [Format::Png(PngOptions::default()), Format::Tiff] {
+            let mut streamed = Vec::new();
+            write_image_data_uri(&image, format, &mut streamed).unwrap();
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                image_to_data_uri(&image, format).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn size_limit() {
+        let image = image::RgbImage::from_fn(64, 64, |x, y| image::Rgb([(x * y) as u8, 0, 0]));
+        let format = Format::Png(PngOptions::default());
+        let uri = image_to_data_uri(&image, format).unwrap();
+        assert_eq!(estimated_data_uri_len(&image, format).unwrap(), uri.len());
+
+        let fits = EncodeOptions {
+            max_len: Some(uri.len()),
+            ..Default::default()
+        };
+        assert_eq!(image_to_data_uri_with(&image, format, &fits).unwrap(), uri);
+
+        let too_small = EncodeOptions {
+            max_len: Some(100),
+            ..Default::default()
+        };
+        match image_to_data_uri_with(&image, format, &too_small) {
+            Err(Error::TooLarge { actual, limit }) => assert_eq!((actual, limit), (uri.len(), 100)),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn gif_frames() {
+        use image::AnimationDecoder;
+
+        let red = image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let blue = image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255]));
+        let delay = image::Delay::from_numer_denom_ms(100, 1);
+        let frames = [
+            image::Frame::from_parts(red.clone(), 0, 0, delay),
+            image::Frame::from_parts(blue.clone(), 0, 0, delay),
+        ];
+        let uri = frames_to_gif_data_uri(&frames, image::codecs::gif::Repeat::Infinite).unwrap();
+        let bytes = DataUri::parse(&uri).unwrap().decode_payload().unwrap();
+        let decoded = image::codecs::gif::GifDecoder::new(bytes.as_slice())
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].buffer(), &blue);
+
+        let uri = image_to_gif_data_uri(&red).unwrap();
+        assert!(uri.starts_with("data:image/gif;base64,R0lGOD"));
+        assert_eq!(data_uri_to_image(&uri).unwrap().to_rgba8(), red);
+    }
+
+    #[test]
+    fn data_uri_to_image_round_trip() {
+        let image = image::GrayImage::from_fn(5, 3, |x, y| image::Luma([(x * y) as u8]));
+        let uri = image_to_png_data_uri(&image).unwrap();
+        let decoded = data_uri_to_image(&uri).unwrap();
+        assert_eq!(decoded.to_luma8(), image);
+
+        assert!(matches!(
+            data_uri_to_image("data:text/plain,hello"),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            data_uri_to_image("image/png;base64,"),
+            Err(Error::Parse(ParseError::MissingScheme))
+        ));
+        assert!(matches!(
+            data_uri_to_image("data:image/png;base64,AAAA"),
+            Err(Error::Image(_))
+        ));
+    }
+
+    #[test]
+    fn raw_pixels() {
+        let image = image::RgbaImage::from_fn(3, 2, |x, y| image::Rgba([x as u8, y as u8, 7, 255]));
+        let expected = image_to_png_data_uri(&image).unwrap();
+        assert_eq!(
+            rgba8_to_png_data_uri(image.as_raw(), 3, 2).unwrap(),
+            expected
+        );
+
+        let mut padded = Vec::new();
+        for row in image.as_raw().chunks(12) {
+            padded.extend_from_slice(row);
+            padded.extend_from_slice(&[0; 4]);
+        }
+        padded.truncate(padded.len() - 4);
+        let uri = pixels_to_png_data_uri(&padded, 3, 2, 16, image::ColorType::Rgba8).unwrap();
+        assert_eq!(uri, expected);
+
+        assert!(matches!(
+            luma8_to_png_data_uri(&[0; 5], 3, 2),
+            Err(Error::Image(image::ImageError::Parameter(_)))
+        ));
+        assert!(rgb8_to_png_data_uri(&[0; 18], 3, 2).is_ok());
+    }
+
+    #[test]
+    fn transcode() {
+        let image = image::RgbaImage::from_pixel(4, 4, image::Rgba([200, 10, 10, 128]));
+        let png = image_to_png_data_uri(&image).unwrap();
+
+        let jpeg = transcode_data_uri(
+            &png,
+            Format::Jpeg(JpegOptions {
+                background: Some(image::Rgb([0, 0, 0])),
+                ..JpegOptions::new(90)
+            }),
+            &EncodeOptions::default(),
+        )
+        .unwrap();
+        assert!(jpeg.starts_with("data:image/jpeg;base64,"));
+        let decoded = data_uri_to_image(&jpeg).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::Rgb8);
+        assert_eq!((decoded.width(), decoded.height()), (4, 4));
+
+        let webp = transcode_data_uri(
+            &png,
+            Format::WebP(WebPQuality::Lossless),
+            &EncodeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(data_uri_to_image(&webp).unwrap().to_rgba8(), image);
+
+        let limited = EncodeOptions {
+            max_len: Some(10),
+            ..Default::default()
+        };
+        assert!(matches!(
+            transcode_data_uri(&png, Format::Gif, &limited),
+            Err(Error::TooLarge { limit: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn png_palette() {
+        let options = |max_colors| PngOptions {
+            quantize_to_palette: true,
+            max_colors,
+            ..PngOptions::default()
+        };
+        let image = image::RgbaImage::from_fn(5, 3, |x, y| match (x + y) % 3 {
+            0 => image::Rgba([255, 0, 0, 255]),
+            1 => image::Rgba([0, 0, 255, 128]),
+            _ => image::Rgba([0, 0, 0, 0]),
+        });
+        let indexed = image_to_data_uri(&image, Format::Png(options(256))).unwrap();
+        assert!(indexed.len() < image_to_png_data_uri(&image).unwrap().len());
+        assert_eq!(data_uri_to_image(&indexed).unwrap().to_rgba8(), image);
+
+        let gradient = image::RgbImage::from_fn(64, 64, |x, y| {
+            image::Rgb([(x * 4) as u8, (y * 4) as u8, 128])
+        });
+        let quantized = image_to_data_uri(&gradient, Format::Png(options(64))).unwrap();
+        let decoded = data_uri_to_image(&quantized).unwrap().to_rgb8();
+        let mut colors: Vec<_> = decoded.pixels().map(|pixel| pixel.0).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        assert!(colors.len() <= 64);
+        let error = decoded
+            .pixels()
+            .zip(gradient.pixels())
+            .map(|(a, b)| (0..3).map(|c| a[c].abs_diff(b[c]) as u32).sum::<u32>())
+            .max()
+            .unwrap();
+        assert!(error < 64, "quantization error {}", error);
+    }
+
+    #[test]
+    fn reduced_png_color_type() {
+        let options = Format::Png(PngOptions {
+            reduce_color_type: true,
+            ..PngOptions::default()
+        });
+        // The color type is the 10th byte of IHDR.
+        let color_type = |uri: &str| DataUri::parse(uri).unwrap().decode_payload().unwrap()[25];
+
+        let gray = image::RgbaImage::from_fn(256, 8, |x, y| {
+            image::Rgba([(x ^ y) as u8, (x ^ y) as u8, (x ^ y) as u8, 255])
+        });
+        let reduced = image_to_data_uri(&gray, options).unwrap();
+        assert_eq!(color_type(&reduced), 0);
+        assert!(reduced.len() < image_to_png_data_uri(&gray).unwrap().len());
+        assert_eq!(data_uri_to_image(&reduced).unwrap().to_rgba8(), gray);
+
+        let screenshot = image::RgbaImage::from_fn(40, 30, |x, y| match (x / 10 + y / 10) % 3 {
+            0 => image::Rgba([250, 250, 250, 255]),
+            1 => image::Rgba([30, 100, 200, 255]),
+            _ => image::Rgba([200, 40, 40, 255]),
+        });
+        let reduced = image_to_data_uri(&screenshot, options).unwrap();
+        assert_eq!(color_type(&reduced), 3);
+        assert_eq!(data_uri_to_image(&reduced).unwrap().to_rgba8(), screenshot);
+    }
+
+    #[test]
+    fn interlaced_png() {
+        let interlaced = |options: PngOptions| PngOptions {
+            interlace: true,
+            ..options
+        };
+        let image = image::RgbaImage::from_fn(11, 9, |x, y| {
+            image::Rgba([x as u8 * 20, y as u8 * 25, 7, 200])
+        });
+        let uri =
+            image_to_data_uri(&image, Format::Png(interlaced(PngOptions::default()))).unwrap();
+        let png = DataUri::parse(&uri).unwrap().decode_payload().unwrap();
+        // The interlace method is the last byte of IHDR.
+        assert_eq!(png[8 + 8 + 12], 1);
+        assert_eq!(data_uri_to_image(&uri).unwrap().to_rgba8(), image);
+
+        // Two colors pack eight pixels per byte.
+        let checkers =
+            image::RgbImage::from_fn(13, 5, |x, y| image::Rgb([255 * ((x + y) % 2) as u8; 3]));
+        let options = interlaced(PngOptions {
+            quantize_to_palette: true,
+            ..PngOptions::default()
+        });
+        let uri = image_to_data_uri(&checkers, Format::Png(options)).unwrap();
+        let png = DataUri::parse(&uri).unwrap().decode_payload().unwrap();
+        assert_eq!(png[8 + 8 + 8..8 + 8 + 13], [1, 3, 0, 0, 1]);
+        assert_eq!(data_uri_to_image(&uri).unwrap().to_rgb8(), checkers);
+    }
+
+    #[test]
+    fn jpeg_options() {
+        let image = image::RgbImage::from_fn(32, 32, |x, y| {
+            image::Rgb([(x * 8) as u8, (y * 8) as u8, 60])
+        });
+        let len = |options| {
+            image_to_data_uri(&image, Format::Jpeg(options))
+                .unwrap()
+                .len()
+        };
+        let baseline = JpegOptions::new(75);
+        let full_chroma = JpegOptions {
+            subsampling: ChromaSubsampling::Yuv444,
+            ..baseline
+        };
+        let grayscale = JpegOptions {
+            grayscale: true,
+            ..baseline
+        };
+        assert!(len(full_chroma) > len(baseline));
+        assert!(len(grayscale) < len(baseline));
+
+        let progressive = JpegOptions {
+            progressive: true,
+            ..baseline
+        };
+        let uri = image_to_data_uri(&image, Format::Jpeg(progressive)).unwrap();
+        let bytes = DataUri::parse(&uri).unwrap().decode_payload().unwrap();
+        // SOF2 marks a progressive frame.
+        assert!(bytes.windows(2).any(|marker| marker == [0xff, 0xc2]));
+        let decoded = data_uri_to_image(&uri).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 32));
+
+        let uri = image_to_data_uri(&image, Format::Jpeg(grayscale)).unwrap();
+        assert_eq!(
+            data_uri_to_image(&uri).unwrap().color(),
+            image::ColorType::L8
+        );
+
+        let sticker = image::RgbaImage::from_fn(16, 16, |x, _| {
+            image::Rgba([0, 0, 255, if x < 8 { 0 } else { 255 }])
+        });
+        assert!(matches!(
+            image_to_data_uri(&sticker, Format::Jpeg(baseline)),
+            Err(Error::Image(image::ImageError::Parameter(_)))
+        ));
+        let on_white = JpegOptions {
+            background: Some(image::Rgb([255, 255, 255])),
+            subsampling: ChromaSubsampling::Yuv444,
+            ..JpegOptions::new(100)
+        };
+        let uri = image_to_data_uri(&sticker, Format::Jpeg(on_white)).unwrap();
+        let decoded = data_uri_to_image(&uri).unwrap().to_rgb8();
+        assert!(decoded
+            .get_pixel(2, 2)
+            .0
+            .iter()
+            .all(|&channel| channel > 250));
+        assert!(decoded.get_pixel(13, 2)[0] < 5);
+    }
+
+    #[test]
+    fn smallest_format() {
+        let candidates = [
+            Format::Png(PngOptions::default()),
+            Format::Jpeg(JpegOptions::new(80)),
+            Format::Bmp,
+        ];
+        let flat = image::RgbImage::from_pixel(64, 64, image::Rgb([10, 20, 30]));
+        let (uri, format) =
+            image_to_smallest_data_uri(&flat, &candidates, &EncodeOptions::default()).unwrap();
+        assert_eq!(format, candidates[0]);
+        assert_eq!(uri, image_to_png_data_uri(&flat).unwrap());
+
+        let noisy = image::RgbImage::from_fn(64, 64, |x, y| {
+            let noise = (x * 7919 + y * 104729) % 251;
+            image::Rgb([noise as u8, (noise * 3) as u8, (x + y) as u8])
+        });
+        let (uri, format) =
+            image_to_smallest_data_uri(&noisy, &candidates, &EncodeOptions::default()).unwrap();
+        assert_eq!(format, candidates[1]);
+        assert!(uri.starts_with("data:image/jpeg;base64,"));
+
+        let limited = EncodeOptions {
+            max_len: Some(10),
+            ..Default::default()
+        };
+        assert!(matches!(
+            image_to_smallest_data_uri(&flat, &candidates, &limited),
+            Err(Error::TooLarge { limit: 10, .. })
+        ));
+        assert!(image_to_smallest_data_uri(&flat, &[], &EncodeOptions::default()).is_err());
+
+        let unpadded = EncodeOptions {
+            omit_padding: true,
+            ..Default::default()
+        };
+        let (uri, format) = image_to_smallest_data_uri(&flat, &candidates, &unpadded).unwrap();
+        assert_eq!(format, candidates[0]);
+        assert!(!uri.ends_with('='));
+    }
+
+    #[test]
+    fn thumbnail() {
+        let image = image::RgbImage::from_pixel(400, 100, image::Rgb([1, 2, 3]));
+        let options = ThumbnailOptions::default();
+        let png = Format::Png(PngOptions::default());
+        let uri = image_to_thumbnail_data_uri(&image, 100, 100, png, &options).unwrap();
+        let thumbnail = data_uri_to_image(&uri).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 25));
+
+        let uri = image_to_thumbnail_data_uri(&image, 1000, 1000, png, &options).unwrap();
+        assert_eq!(uri, image_to_png_data_uri(&image).unwrap());
+
+        let options = ThumbnailOptions {
+            filter: image::imageops::FilterType::Nearest,
+            ..ThumbnailOptions::default()
+        };
+        let uri = image_to_thumbnail_data_uri(&image, 50, 1, png, &options).unwrap();
+        let thumbnail = data_uri_to_image(&uri).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (4, 1));
+    }
+
+    #[test]
+    fn batch() {
+        let images: Vec<_> = (1..=3)
+            .map(|width| image::GrayImage::new(width, 1))
+            .collect();
+        let png = Format::Png(PngOptions::default());
+        let uris = encode_batch(&images, png, &EncodeOptions::default());
+        assert_eq!(uris.len(), 3);
+        for (uri, image) in uris.into_iter().zip(&images) {
+            assert_eq!(uri.unwrap(), image_to_png_data_uri(image).unwrap());
+        }
+
+        let uris = encode_batch(
+            &images,
+            png,
+            &EncodeOptions {
+                max_len: Some(0),
+                ..Default::default()
+            },
+        );
+        assert!(uris
+            .iter()
+            .all(|uri| matches!(uri, Err(Error::TooLarge { .. }))));
+    }
+
+    #[test]
+    fn preserve_icc_profile() {
+        use super::{chunks, metadata};
+
+        let profile = b"fake ICC profile, not validated by the codecs".repeat(4);
+        let png = image_to_png_data_uri(&image::RgbImage::new(2, 2))
+            .unwrap()
+            .decode_payload()
+            .unwrap();
+        let png = chunks::insert_iccp(&png, &profile).unwrap();
+        assert_eq!(metadata::icc_profile(&png).as_ref(), Some(&profile));
+        let uri = bytes_to_data_uri(&png, &mime::IMAGE_PNG);
+
+        let icc_of = |format| {
+            let uri = transcode_data_uri(&uri, format, &EncodeOptions::default()).unwrap();
+            metadata::icc_profile(&uri.decode_payload().unwrap())
+        };
+        let png_options = PngOptions {
+            preserve_icc: true,
+            ..PngOptions::default()
+        };
+        assert_eq!(icc_of(Format::Png(png_options)).as_ref(), Some(&profile));
+        let jpeg_options = JpegOptions {
+            preserve_icc: true,
+            ..JpegOptions::default()
+        };
+        assert_eq!(icc_of(Format::Jpeg(jpeg_options)).as_ref(), Some(&profile));
+        assert_eq!(icc_of(Format::Jpeg(JpegOptions::default())), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Animated PNG encoding.
+
+use crate::{bytes_to_data_uri, DataUri, Error, Result};
+use image::error::{EncodingError, ImageError, ImageFormatHint};
+use image::{GenericImage, ImageFormat};
+
+/// Encodes an animated PNG, each frame carries its own delay and offset as
+/// for [`frames_to_gif_data_uri`](crate::frames_to_gif_data_uri), without
+/// the GIF palette limit. `plays` is the number of loops, 0 for infinite.
+///
+/// Frames are drawn over the previous ones. The data URI is tagged
+/// `image/png`, which browsers animate and other decoders show as the first
+/// frame.
+///
+/// ```
+/// use image::{Delay, Frame, Rgba, RgbaImage};
+///
+/// let blink = [Rgba([0, 200, 0, 255]), Rgba([0, 0, 0, 0])].map(|color| {
+///     Frame::from_parts(RgbaImage::from_pixel(8, 8, color), 0, 0, Delay::from_numer_denom_ms(500, 1))
+/// });
+/// let uri = data_uri_utils::frames_to_apng_data_uri(&blink, 0)?;
+/// assert!(uri.starts_with("data:image/png;base64,"));
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn frames_to_apng_data_uri(frames: &[image::Frame], plays: u32) -> Result<DataUri> {
+    let (width, height) = frames.iter().fold((0, 0), |(width, height), frame| {
+        let buffer = frame.buffer();
+        (
+            width.max(frame.left() + buffer.width()),
+            height.max(frame.top() + buffer.height()),
+        )
+    });
+    if frames.is_empty() || width == 0 || height == 0 {
+        return Err(Error::InvalidSize { width, height });
+    }
+
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .set_animated(frames.len() as u32, plays)
+        .map_err(encoding_error)?;
+    let mut writer = encoder.write_header().map_err(encoding_error)?;
+    for (index, frame) in frames.iter().enumerate() {
+        // The first frame is also the still image, which has to cover the
+        // whole canvas.
+        let padded;
+        let (image, left, top) = if index == 0 && (frame.buffer().dimensions() != (width, height)) {
+            let mut canvas = image::RgbaImage::new(width, height);
+            canvas.copy_from(frame.buffer(), frame.left(), frame.top())?;
+            padded = canvas;
+            (&padded, 0, 0)
+        } else {
+            (frame.buffer(), frame.left(), frame.top())
+        };
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        let delay_ms = (f64::from(numerator) / f64::from(denominator)).round();
+        writer
+            .set_frame_position(0, 0)
+            .and_then(|()| writer.set_frame_dimension(image.width(), image.height()))
+            .and_then(|()| writer.set_frame_position(left, top))
+            .and_then(|()| writer.set_frame_delay(delay_ms.min(f64::from(u16::MAX)) as u16, 1000))
+            .and_then(|()| writer.set_blend_op(png::BlendOp::Over))
+            .and_then(|()| writer.set_dispose_op(png::DisposeOp::None))
+            .and_then(|()| writer.write_image_data(image.as_raw()))
+            .map_err(encoding_error)?;
+    }
+    writer.finish().map_err(encoding_error)?;
+    Ok(bytes_to_data_uri(&buffer, &mime::IMAGE_PNG))
+}
+
+fn encoding_error(error: png::EncodingError) -> Error {
+    Error::Image(ImageError::Encoding(EncodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Png),
+        error,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use image::{Delay, Frame, Rgba, RgbaImage};
+
+    #[test]
+    fn animated_png() {
+        let frames = [
+            Frame::from_parts(
+                RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255])),
+                1,
+                0,
+                Delay::from_numer_denom_ms(100, 1),
+            ),
+            Frame::from_parts(
+                RgbaImage::from_pixel(1, 3, Rgba([0, 0, 255, 255])),
+                0,
+                0,
+                Delay::from_numer_denom_ms(250, 1),
+            ),
+        ];
+        let uri = frames_to_apng_data_uri(&frames, 3).unwrap();
+        let bytes = DataUri::parse(&uri).unwrap().decode_payload().unwrap();
+        let mut decoder = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+        let info = decoder.info();
+        assert_eq!((info.width, info.height), (3, 3));
+        let control = info.animation_control().unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 3));
+
+        let mut pixels = vec![0; decoder.output_buffer_size()];
+        decoder.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels[..8], [0, 0, 0, 0, 255, 0, 0, 255]);
+        decoder.next_frame(&mut pixels).unwrap();
+        let frame = decoder.info().frame_control().unwrap();
+        assert_eq!((frame.width, frame.height, frame.delay_num), (1, 3, 250));
+
+        assert!(frames_to_apng_data_uri(&[], 0).is_err());
+    }
+}
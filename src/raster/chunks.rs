@@ -0,0 +1,90 @@
+/// Ancillary chunks that affect how the image renders or animates, everything
+/// else ancillary is metadata.
+const RENDERING_CHUNKS: &[&[u8; 4]] = &[
+    b"tRNS", b"gAMA", b"cHRM", b"sRGB", b"iCCP", b"sBIT", b"cICP", b"mDCv", b"cLLi", b"pHYs",
+    b"bKGD", b"acTL", b"fcTL", b"fdAT",
+];
+
+pub(super) const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Drops the metadata chunks of a PNG, such as `tIME`, `tEXt`, `zTXt`, `iTXt`
+/// and `eXIf`, so that identical pixels always encode to identical bytes.
+///
+/// Returns `None` when `png` isn't a well-formed PNG.
+pub fn strip_png_metadata(png: &[u8]) -> Option<Vec<u8>> {
+    let mut rest = png.strip_prefix(SIGNATURE)?;
+    let mut stripped = SIGNATURE.to_vec();
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let chunk = rest.get(..len.checked_add(12)?)?;
+        let kind: &[u8; 4] = chunk[4..8].try_into().ok()?;
+        let critical = kind[0].is_ascii_uppercase();
+        if critical || RENDERING_CHUNKS.contains(&kind) {
+            stripped.extend_from_slice(chunk);
+        }
+        rest = &rest[chunk.len()..];
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Some(stripped)
+}
+
+/// Inserts an `iCCP` chunk holding `profile` right after `IHDR`, replacing
+/// any existing `iCCP` or `sRGB` chunk, which can't coexist with it.
+pub(crate) fn insert_iccp(png: &[u8], profile: &[u8]) -> Option<Vec<u8>> {
+    use std::io::Write;
+
+    let mut data = b"ICC Profile\0\0".to_vec();
+    let mut encoder = flate2::write::ZlibEncoder::new(&mut data, flate2::Compression::best());
+    encoder.write_all(profile).ok()?;
+    encoder.finish().ok()?;
+
+    let mut rest = png.strip_prefix(SIGNATURE)?;
+    let mut inserted = SIGNATURE.to_vec();
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let chunk = rest.get(..len.checked_add(12)?)?;
+        let kind: &[u8; 4] = chunk[4..8].try_into().ok()?;
+        if kind != b"iCCP" && kind != b"sRGB" {
+            inserted.extend_from_slice(chunk);
+        }
+        if kind == b"IHDR" {
+            let mut crc = flate2::Crc::new();
+            crc.update(b"iCCP");
+            crc.update(&data);
+            inserted.extend_from_slice(&u32::try_from(data.len()).ok()?.to_be_bytes());
+            inserted.extend_from_slice(b"iCCP");
+            inserted.extend_from_slice(&data);
+            inserted.extend_from_slice(&crc.sum().to_be_bytes());
+        }
+        rest = &rest[chunk.len()..];
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Some(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn strip_metadata_chunks() {
+        let uri = image_to_png_data_uri(&image::GrayImage::new(1, 1)).unwrap();
+        let png = uri.decode_payload().unwrap();
+        assert_eq!(strip_png_metadata(&png).unwrap(), png);
+
+        // Insert a `tIME` chunk and trailing garbage after IHDR and IEND.
+        let ihdr_end = 8 + 12 + 13;
+        let mut with_time = png[..ihdr_end].to_vec();
+        with_time.extend_from_slice(b"\0\0\0\x07tIME\x07\xea\x0a\x0e\0\0\0\0\0\0\0");
+        with_time.extend_from_slice(&png[ihdr_end..]);
+        with_time.extend_from_slice(b"junk");
+        assert_eq!(strip_png_metadata(&with_time).unwrap(), png);
+
+        assert_eq!(strip_png_metadata(b"GIF89a"), None);
+        assert_eq!(strip_png_metadata(&png[..png.len() - 1]), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Conversion of floating-point images, which PNG can't store, to 16 bits.
+
+use crate::{image_to_png_data_uri, DataUri, Result};
+use image::{DynamicImage, ImageBuffer, ImageEncoder};
+
+/// How [`tone_map_image`] brings floating-point samples, such as HDR or
+/// scientific data, into the 0 to 1 range. Alpha is always clamped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// Clamps samples to 0 to 1, what encoding a float image directly does.
+    #[default]
+    Clamp,
+    /// Stretches the smallest to largest sample of the image to 0 to 1, for
+    /// measurements in arbitrary units.
+    Normalize,
+    /// Compresses highlights with `x / (1 + x)`, for HDR radiance.
+    Reinhard,
+}
+
+/// Converts `Rgb32F` and `Rgba32F` images to 16 bits per channel using
+/// `tone_mapping`, other images are returned as they are.
+pub fn tone_map_image(image: &DynamicImage, tone_mapping: ToneMapping) -> DynamicImage {
+    let (width, height) = (image.width(), image.height());
+    match image {
+        DynamicImage::ImageRgb32F(buffer) => {
+            let samples = to_u16_samples(buffer.as_raw(), 3, tone_mapping);
+            DynamicImage::ImageRgb16(ImageBuffer::from_raw(width, height, samples).unwrap())
+        }
+        DynamicImage::ImageRgba32F(buffer) => {
+            let samples = to_u16_samples(buffer.as_raw(), 4, tone_mapping);
+            DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, samples).unwrap())
+        }
+        image => image.clone(),
+    }
+}
+
+/// A 16-bit PNG of a floating-point image, see [`tone_map_image`].
+///
+/// ```
+/// use data_uri_utils::{float_image_to_png_data_uri, ToneMapping};
+///
+/// let depth = image::Rgb32FImage::from_fn(4, 1, |x, _| image::Rgb([x as f32 * 100.0; 3]));
+/// let uri = float_image_to_png_data_uri(&depth.into(), ToneMapping::Normalize)?;
+/// let decoded = data_uri_utils::data_uri_to_image(&uri)?.into_rgb16();
+/// assert_eq!(decoded.get_pixel(3, 0).0, [65535; 3]);
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn float_image_to_png_data_uri(
+    image: &DynamicImage,
+    tone_mapping: ToneMapping,
+) -> Result<DataUri> {
+    image_to_png_data_uri(&tone_map_image(image, tone_mapping))
+}
+
+/// Writes an `Rgb32F` or `Rgba32F` image to a PNG encoder as 16 bits per
+/// channel, clamped.
+pub(super) fn write_float_image<T, E>(image: &T, encoder: E) -> image::ImageResult<()>
+where
+    T: crate::EncodableImage,
+    E: ImageEncoder,
+{
+    use image::Pixel;
+
+    let samples: Vec<f32> = image.with_samples(|bytes| {
+        bytes
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+            .collect()
+    });
+    let channels = usize::from(<T as image::GenericImageView>::Pixel::CHANNEL_COUNT);
+    let samples = to_u16_samples(&samples, channels, ToneMapping::Clamp);
+    let bytes: Vec<u8> = samples
+        .iter()
+        .flat_map(|sample| sample.to_ne_bytes())
+        .collect();
+    let color_type = if channels == 4 {
+        image::ColorType::Rgba16
+    } else {
+        image::ColorType::Rgb16
+    };
+    encoder.write_image(&bytes, image.width(), image.height(), color_type)
+}
+
+fn to_u16_samples(samples: &[f32], channels: usize, tone_mapping: ToneMapping) -> Vec<u16> {
+    let has_alpha = channels % 2 == 0;
+    let is_color = |index: usize| !has_alpha || index % channels != channels - 1;
+    let (min, max) = samples
+        .iter()
+        .enumerate()
+        .filter(|(index, sample)| is_color(*index) && sample.is_finite())
+        .fold(
+            (f32::INFINITY, f32::NEG_INFINITY),
+            |(min, max), (_, &sample)| (min.min(sample), max.max(sample)),
+        );
+    samples
+        .iter()
+        .enumerate()
+        .map(|(index, &sample)| {
+            let sample = match tone_mapping {
+                _ if !is_color(index) => sample,
+                ToneMapping::Clamp => sample,
+                ToneMapping::Normalize if max > min => (sample - min) / (max - min),
+                ToneMapping::Normalize => 0.0,
+                ToneMapping::Reinhard => sample.max(0.0) / (1.0 + sample.max(0.0)),
+            };
+            // NaN clamps to 0 through the saturating cast.
+            (sample.clamp(0.0, 1.0) * 65535.0).round() as u16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn high_bit_depth() {
+        let luma = image::ImageBuffer::<image::Luma<u16>, _>::from_fn(2, 1, |x, _| {
+            image::Luma([x as u16 * 40000 + 1])
+        });
+        let uri = image_to_png_data_uri(&luma).unwrap();
+        let decoded = data_uri_to_image(&uri).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::L16);
+        assert_eq!(decoded.into_luma16(), luma);
+
+        let hdr = image::Rgba32FImage::from_pixel(1, 1, image::Rgba([0.5, 3.0, -1.0, 2.0]));
+        let uri = image_to_png_data_uri(&hdr).unwrap();
+        let decoded = data_uri_to_image(&uri).unwrap().into_rgba16();
+        assert_eq!(decoded.get_pixel(0, 0).0, [32768, 65535, 0, 65535]);
+
+        let hdr = image::DynamicImage::from(hdr);
+        let mapped = tone_map_image(&hdr, ToneMapping::Reinhard).into_rgba16();
+        assert_eq!(mapped.get_pixel(0, 0).0, [21845, 49151, 0, 65535]);
+        let mapped = tone_map_image(&hdr, ToneMapping::Normalize).into_rgba16();
+        assert_eq!(mapped.get_pixel(0, 0).0, [24576, 65535, 0, 65535]);
+    }
+}
@@ -0,0 +1,157 @@
+//! Adam7 interlacing of encoded PNGs, which the PNG encoders don't write.
+
+use super::chunks::SIGNATURE;
+use image::codecs::png::CompressionType;
+use std::io::Write;
+
+/// Origin and spacing of the pixels of each Adam7 pass, as
+/// `(x, y, x step, y step)`.
+const PASSES: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+/// Re-encodes the image data of `png` as Adam7 passes, so that browsers
+/// render a coarse preview of large images before they are fully loaded.
+///
+/// Other chunks are kept. Returns `None` when `png` can't be decoded.
+pub(super) fn interlace_png(png: &[u8], compression: CompressionType) -> Option<Vec<u8>> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(png::Transformations::IDENTITY);
+    let mut reader = decoder.read_info().ok()?;
+    if reader.info().interlaced || reader.info().is_animated() {
+        return None;
+    }
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut pixels).ok()?;
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    let bits = frame.color_type.samples() * frame.bit_depth as usize;
+
+    let mut filtered = Vec::new();
+    for (x0, y0, x_step, y_step) in PASSES {
+        let pass_width = width.saturating_sub(x0).div_ceil(x_step);
+        let row_len = (pass_width * bits).div_ceil(8);
+        let mut previous = vec![0; row_len];
+        for y in (y0..height).step_by(y_step) {
+            if pass_width == 0 {
+                break;
+            }
+            let source = &pixels[y * frame.line_size..][..frame.line_size];
+            let mut row = vec![0; row_len];
+            for (index, x) in (x0..width).step_by(x_step).enumerate() {
+                copy_pixel(source, x * bits, &mut row, index * bits, bits);
+            }
+            filter_row(&row, &previous, bits.div_ceil(8), &mut filtered);
+            previous = row;
+        }
+    }
+    let level = match compression {
+        CompressionType::Default => flate2::Compression::default(),
+        CompressionType::Best => flate2::Compression::best(),
+        _ => flate2::Compression::fast(),
+    };
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+    encoder.write_all(&filtered).ok()?;
+    let data = encoder.finish().ok()?;
+
+    let mut rest = png.strip_prefix(SIGNATURE)?;
+    let mut interlaced = SIGNATURE.to_vec();
+    let mut idat_written = false;
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let chunk = rest.get(..len.checked_add(12)?)?;
+        let kind: &[u8; 4] = chunk[4..8].try_into().ok()?;
+        match kind {
+            b"IHDR" => {
+                let mut header = chunk[8..8 + len].to_vec();
+                *header.get_mut(12)? = 1;
+                write_chunk(&mut interlaced, kind, &header)?;
+            }
+            b"IDAT" if !idat_written => {
+                write_chunk(&mut interlaced, kind, &data)?;
+                idat_written = true;
+            }
+            b"IDAT" => {}
+            _ => interlaced.extend_from_slice(chunk),
+        }
+        rest = &rest[chunk.len()..];
+        if kind == b"IEND" {
+            break;
+        }
+    }
+    Some(interlaced)
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) -> Option<()> {
+    let mut crc = flate2::Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&u32::try_from(data.len()).ok()?.to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc.sum().to_be_bytes());
+    Some(())
+}
+
+/// Copies the `bits` bits of a pixel, which are either a whole number of
+/// bytes or fit in one.
+fn copy_pixel(source: &[u8], from: usize, target: &mut [u8], to: usize, bits: usize) {
+    if bits % 8 == 0 {
+        let len = bits / 8;
+        target[to / 8..][..len].copy_from_slice(&source[from / 8..][..len]);
+    } else {
+        let mask = (1u8 << bits) - 1;
+        let value = (source[from / 8] >> (8 - bits - from % 8)) & mask;
+        target[to / 8] |= value << (8 - bits - to % 8);
+    }
+}
+
+/// Appends `row` with the filter giving the smallest sum of absolute
+/// differences, the heuristic libpng uses.
+fn filter_row(row: &[u8], previous: &[u8], bpp: usize, filtered: &mut Vec<u8>) {
+    let mut best: Option<(u64, u8, Vec<u8>)> = None;
+    for filter in 0..5u8 {
+        let candidate: Vec<u8> = (0..row.len())
+            .map(|i| {
+                let left = if i >= bpp { row[i - bpp] } else { 0 };
+                let up = previous[i];
+                let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+                let prediction = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                    _ => paeth(left, up, up_left),
+                };
+                row[i].wrapping_sub(prediction)
+            })
+            .collect();
+        let cost = candidate
+            .iter()
+            .map(|&byte| u64::from((byte as i8).unsigned_abs()))
+            .sum();
+        if !matches!(&best, Some((best_cost, _, _)) if *best_cost <= cost) {
+            best = Some((cost, filter, candidate));
+        }
+    }
+    let (_, filter, candidate) = best.expect("there are five filters");
+    filtered.push(filter);
+    filtered.extend_from_slice(&candidate);
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let distance = |value: u8| (estimate - i16::from(value)).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
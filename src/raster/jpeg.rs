@@ -0,0 +1,107 @@
+use super::{to_rgba8, ChromaSubsampling, JpegOptions};
+use image::error::{
+    EncodingError, ImageError, ImageFormatHint, ImageResult, ParameterError, ParameterErrorKind,
+};
+use image::{ColorType, ImageEncoder, ImageFormat};
+use std::io::Write;
+
+/// JPEG encoder exposing the settings `image`'s own encoder lacks.
+pub(super) struct JpegEncoder<'a, W> {
+    writer: W,
+    options: JpegOptions,
+    icc_profile: Option<&'a [u8]>,
+}
+
+impl<'a, W: Write> JpegEncoder<'a, W> {
+    pub(super) fn new(writer: W, options: JpegOptions) -> Self {
+        JpegEncoder {
+            writer,
+            options,
+            icc_profile: None,
+        }
+    }
+
+    pub(super) fn icc_profile(mut self, icc_profile: Option<&'a [u8]>) -> Self {
+        self.icc_profile = icc_profile;
+        self
+    }
+}
+
+impl<W: Write> ImageEncoder for JpegEncoder<'_, W> {
+    fn write_image(
+        self,
+        buf: &[u8],
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+    ) -> ImageResult<()> {
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        };
+        let mut rgba = to_rgba8(buf, color_type);
+        if color_type.has_alpha() {
+            composite(&mut rgba, self.options.background)?;
+        }
+        let (pixels, jpeg_color_type) = if self.options.grayscale || !color_type.has_color() {
+            let luma = rgba
+                .chunks_exact(4)
+                .map(|pixel| {
+                    let [red, green, blue] = [pixel[0], pixel[1], pixel[2]].map(u32::from);
+                    ((red * 299 + green * 587 + blue * 114 + 500) / 1000) as u8
+                })
+                .collect();
+            (luma, jpeg_encoder::ColorType::Luma)
+        } else {
+            (rgba, jpeg_encoder::ColorType::Rgba)
+        };
+
+        let mut encoder =
+            jpeg_encoder::Encoder::new(self.writer, self.options.quality.clamp(1, 100));
+        encoder.set_progressive(self.options.progressive);
+        encoder.set_sampling_factor(match self.options.subsampling {
+            ChromaSubsampling::Yuv444 => jpeg_encoder::SamplingFactor::R_4_4_4,
+            ChromaSubsampling::Yuv422 => jpeg_encoder::SamplingFactor::R_4_2_2,
+            ChromaSubsampling::Yuv420 => jpeg_encoder::SamplingFactor::R_4_2_0,
+        });
+        if let Some(icc_profile) = self.icc_profile {
+            encoder
+                .add_icc_profile(icc_profile)
+                .map_err(encoding_error)?;
+        }
+        encoder
+            .encode(&pixels, width, height, jpeg_color_type)
+            .map_err(encoding_error)
+    }
+}
+
+/// Blends translucent pixels onto `background`, making them opaque.
+fn composite(rgba: &mut [u8], background: Option<image::Rgb<u8>>) -> ImageResult<()> {
+    let Some(background) = background else {
+        if rgba.chunks_exact(4).all(|pixel| pixel[3] == 255) {
+            return Ok(());
+        }
+        return Err(ImageError::Parameter(ParameterError::from_kind(
+            ParameterErrorKind::Generic(
+                "JPEG can't store transparency, set JpegOptions::background".to_string(),
+            ),
+        )));
+    };
+    for pixel in rgba.chunks_exact_mut(4) {
+        let alpha = u32::from(pixel[3]);
+        for (channel, background) in pixel[..3].iter_mut().zip(background.0) {
+            let blended = u32::from(*channel) * alpha + u32::from(background) * (255 - alpha);
+            *channel = ((blended + 127) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+    Ok(())
+}
+
+fn encoding_error(error: jpeg_encoder::EncodingError) -> ImageError {
+    ImageError::Encoding(EncodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Jpeg),
+        error,
+    ))
+}
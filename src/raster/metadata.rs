@@ -0,0 +1,245 @@
+use crate::{DataUri, Error, Result};
+
+/// The EXIF orientation, from 1 (upright) to 8, of a JPEG, PNG, WebP or TIFF
+/// file.
+pub(crate) fn exif_orientation(bytes: &[u8]) -> Option<u16> {
+    let orientation = tiff_orientation(exif(bytes)?)?;
+    (1..=8).contains(&orientation).then_some(orientation)
+}
+
+/// The embedded ICC color profile of a PNG, JPEG, WebP or TIFF file.
+pub(crate) fn icc_profile(bytes: &[u8]) -> Option<Vec<u8>> {
+    use image::codecs::*;
+    use image::ImageDecoder;
+
+    let cursor = std::io::Cursor::new(bytes);
+    match image::guess_format(bytes).ok()? {
+        image::ImageFormat::Png => png::PngDecoder::new(cursor).ok()?.icc_profile(),
+        image::ImageFormat::Jpeg => jpeg::JpegDecoder::new(cursor).ok()?.icc_profile(),
+        image::ImageFormat::WebP => webp::WebPDecoder::new(cursor).ok()?.icc_profile(),
+        image::ImageFormat::Tiff => tiff::TiffDecoder::new(cursor).ok()?.icc_profile(),
+        _ => None,
+    }
+}
+
+/// Rotates and flips the pixels so that the image displays upright without
+/// its EXIF orientation.
+pub(crate) fn apply_orientation(
+    image: image::DynamicImage,
+    orientation: u16,
+) -> image::DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+/// The TIFF structure holding the EXIF tags of the file.
+fn exif(bytes: &[u8]) -> Option<&[u8]> {
+    match bytes {
+        [0xff, 0xd8, ..] => jpeg_segments(bytes)
+            .find_map(|(marker, data)| (marker == 0xe1).then(|| data.strip_prefix(b"Exif\0\0"))?),
+        [0x89, b'P', b'N', b'G', ..] => {
+            png_chunks(bytes).find_map(|(kind, data)| (kind == b"eXIf").then_some(data))
+        }
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => {
+            let data = riff_chunks(&bytes[12..])
+                .find_map(|(kind, data)| (kind == b"EXIF").then_some(data))?;
+            Some(data.strip_prefix(b"Exif\0\0").unwrap_or(data))
+        }
+        [b'I', b'I', b'*', 0, ..] | [b'M', b'M', 0, b'*', ..] => Some(bytes),
+        _ => None,
+    }
+}
+
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        let bytes = tiff.get(offset..offset.checked_add(2)?)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| {
+        let bytes = tiff.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    let ifd = u32_at(4)? as usize;
+    let entries = u16_at(ifd)?;
+    (0..usize::from(entries)).find_map(|index| {
+        // Offsets come from the file, and may overflow on 32-bit targets.
+        let entry = ifd.checked_add(2)?.checked_add(index.checked_mul(12)?)?;
+        const ORIENTATION: u16 = 0x0112;
+        const SHORT: u16 = 3;
+        (u16_at(entry)? == ORIENTATION && u16_at(entry.checked_add(2)?)? == SHORT)
+            .then(|| u16_at(entry.checked_add(8)?))?
+    })
+}
+
+/// The marker and data of the JPEG segments up to the start of scan.
+fn jpeg_segments(jpeg: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    let mut position = 2;
+    std::iter::from_fn(move || {
+        let (marker, data, len) = jpeg_segment(jpeg, position)?;
+        position += len;
+        Some((marker, data))
+    })
+}
+
+/// The segment at `position`, along with its full length including its marker,
+/// or `None` at the start of scan.
+fn jpeg_segment(jpeg: &[u8], position: usize) -> Option<(u8, &[u8], usize)> {
+    let header = jpeg.get(position..position + 4)?;
+    let marker = header[1];
+    if header[0] != 0xff || marker == 0xda {
+        return None;
+    }
+    let len = usize::from(u16::from_be_bytes([header[2], header[3]]));
+    let data = jpeg.get(position + 4..position + 2 + len)?;
+    Some((marker, data, len + 2))
+}
+
+fn png_chunks(png: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = png.get(8..).unwrap_or_default();
+    std::iter::from_fn(move || {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let chunk = rest.get(..len.checked_add(12)?)?;
+        rest = &rest[chunk.len()..];
+        Some((&chunk[4..8], &chunk[8..8 + len]))
+    })
+}
+
+fn riff_chunks(mut rest: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        let len = u32::from_le_bytes(rest.get(4..8)?.try_into().ok()?) as usize;
+        let chunk = rest.get(..len.checked_add(8)?)?;
+        // Chunks are padded to an even length.
+        rest = rest.get(chunk.len() + len % 2..).unwrap_or_default();
+        Some((&chunk[..4], &chunk[8..]))
+    })
+}
+
+/// JPEG segments dropped by [`strip_metadata_data_uri`]: EXIF and XMP
+/// (`APP1`), IPTC (`APP13`) and comments.
+const JPEG_METADATA: &[u8] = &[0xe1, 0xed, 0xfe];
+
+/// Removes EXIF, XMP, IPTC, text and comment metadata from a PNG or JPEG
+/// data URI without re-encoding the pixels, so GPS coordinates, camera
+/// details and embedded thumbnails don't leak.
+///
+/// Color profiles are kept. The EXIF orientation is dropped with the rest,
+/// use [`transcode_data_uri`](crate::transcode_data_uri) to bake it into the
+/// pixels instead.
+pub fn strip_metadata_data_uri(uri: &str) -> Result<DataUri> {
+    let uri = DataUri::parse(uri)?;
+    let bytes = uri.decode_payload()?;
+    let malformed = || {
+        Error::Image(image::ImageError::Decoding(
+            image::error::DecodingError::new(
+                image::error::ImageFormatHint::Name(uri.mime().essence_str().to_string()),
+                "malformed file structure",
+            ),
+        ))
+    };
+    let stripped = match uri.mime().essence_str() {
+        "image/png" => super::chunks::strip_png_metadata(&bytes).ok_or_else(malformed)?,
+        "image/jpeg" => strip_jpeg_metadata(&bytes).ok_or_else(malformed)?,
+        _ => return Err(Error::UnsupportedMediaType(uri.mime().clone())),
+    };
+    Ok(crate::bytes_to_data_uri(&stripped, uri.mime()))
+}
+
+fn strip_jpeg_metadata(jpeg: &[u8]) -> Option<Vec<u8>> {
+    if !jpeg.starts_with(&[0xff, 0xd8]) {
+        return None;
+    }
+    let mut stripped = jpeg[..2].to_vec();
+    let mut position = 2;
+    while let Some((marker, _, len)) = jpeg_segment(jpeg, position) {
+        if !JPEG_METADATA.contains(&marker) {
+            stripped.extend_from_slice(&jpeg[position..position + len]);
+        }
+        position += len;
+    }
+    // The scan and everything after it are kept verbatim.
+    stripped.extend_from_slice(
+        jpeg.get(position..)
+            .filter(|scan| scan.starts_with(&[0xff, 0xda]))?,
+    );
+    Some(stripped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::exif_orientation;
+    use crate::*;
+
+    /// A little-endian TIFF header with a single orientation entry.
+    fn exif_tiff(orientation: u8) -> Vec<u8> {
+        let mut tiff = b"II*\0\x08\0\0\0\x01\0\x12\x01\x03\0\x01\0\0\0".to_vec();
+        tiff.extend_from_slice(&[orientation, 0, 0, 0, 0, 0, 0, 0]);
+        tiff
+    }
+
+    #[test]
+    fn orientation_and_stripping() {
+        let image = image::RgbImage::from_fn(2, 1, |x, _| image::Rgb([x as u8 * 255, 0, 0]));
+        let uri = image_to_jpeg_data_uri(&image, 100).unwrap();
+        let jpeg = uri.decode_payload().unwrap();
+
+        // Insert an EXIF segment saying the image is rotated 90° clockwise.
+        let tiff = exif_tiff(6);
+        let mut rotated = jpeg[..2].to_vec();
+        rotated.extend_from_slice(&[0xff, 0xe1]);
+        rotated.extend_from_slice(&(tiff.len() as u16 + 8).to_be_bytes());
+        rotated.extend_from_slice(b"Exif\0\0");
+        rotated.extend_from_slice(&tiff);
+        rotated.extend_from_slice(&jpeg[2..]);
+        assert_eq!(exif_orientation(&rotated), Some(6));
+        assert_eq!(exif_orientation(&jpeg), None);
+        let mut far = exif_tiff(6);
+        far[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(exif_orientation(&far), None);
+        let rotated = bytes_to_data_uri(&rotated, &mime::IMAGE_JPEG);
+
+        let options = DecodeOptions {
+            apply_orientation: true,
+            ..DecodeOptions::default()
+        };
+        let upright = data_uri_to_image_with(&rotated, &options).unwrap();
+        assert_eq!((upright.width(), upright.height()), (1, 2));
+        let raw = data_uri_to_image(&rotated).unwrap();
+        assert_eq!((raw.width(), raw.height()), (2, 1));
+        let transcoded = transcode_data_uri(
+            &rotated,
+            Format::Png(PngOptions::default()),
+            &EncodeOptions::default(),
+        )
+        .unwrap();
+        let transcoded = data_uri_to_image(&transcoded).unwrap();
+        assert_eq!((transcoded.width(), transcoded.height()), (1, 2));
+
+        let stripped = strip_metadata_data_uri(&rotated).unwrap();
+        assert_eq!(stripped, uri);
+        assert!(matches!(
+            strip_metadata_data_uri("data:image/gif;base64,R0lGODlh"),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+//! Indexed PNG encoding with an optional lossy color reduction, and lossless
+//! reduction to the smallest PNG color type.
+
+use super::{to_rgba8, PngOptions};
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::error::{EncodingError, ImageError, ImageFormatHint, ImageResult};
+use image::{ColorType, ImageEncoder, ImageFormat};
+use std::collections::HashMap;
+use std::io::Write;
+
+pub(super) struct PalettePngEncoder<W> {
+    writer: W,
+    options: PngOptions,
+}
+
+impl<W: Write> PalettePngEncoder<W> {
+    pub(super) fn new(writer: W, options: PngOptions) -> Self {
+        PalettePngEncoder { writer, options }
+    }
+}
+
+impl<W: Write> ImageEncoder for PalettePngEncoder<W> {
+    fn write_image(
+        self,
+        buf: &[u8],
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+    ) -> ImageResult<()> {
+        let rgba = to_rgba8(buf, color_type);
+        let max_colors = usize::from(self.options.max_colors.clamp(2, 256));
+        let (palette, indices) = exact_palette(&rgba, max_colors)
+            .unwrap_or_else(|| quantized_palette(&rgba, max_colors));
+        write_indexed(
+            self.writer,
+            &self.options,
+            width,
+            height,
+            &palette,
+            &indices,
+        )
+    }
+}
+
+/// Writes a PNG of `indices` into `palette`, with as few bits per pixel as
+/// the palette allows.
+fn write_indexed<W: Write>(
+    writer: W,
+    options: &PngOptions,
+    width: u32,
+    height: u32,
+    palette: &[[u8; 4]],
+    indices: &[u8],
+) -> ImageResult<()> {
+    let depth = match palette.len() {
+        0..=2 => png::BitDepth::One,
+        3..=4 => png::BitDepth::Two,
+        5..=16 => png::BitDepth::Four,
+        _ => png::BitDepth::Eight,
+    };
+    let rgb: Vec<u8> = palette
+        .iter()
+        .flat_map(|color| &color[..3])
+        .copied()
+        .collect();
+    let mut alpha: Vec<u8> = palette.iter().map(|color| color[3]).collect();
+    while alpha.last() == Some(&255) {
+        alpha.pop();
+    }
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(depth);
+    encoder.set_palette(rgb);
+    if !alpha.is_empty() {
+        encoder.set_trns(alpha);
+    }
+    encoder.set_compression(match options.compression {
+        CompressionType::Default => png::Compression::Default,
+        CompressionType::Best => png::Compression::Best,
+        _ => png::Compression::Fast,
+    });
+    let (filter, adaptive) = match options.filter {
+        FilterType::NoFilter => (png::FilterType::NoFilter, false),
+        FilterType::Sub => (png::FilterType::Sub, false),
+        FilterType::Up => (png::FilterType::Up, false),
+        FilterType::Avg => (png::FilterType::Avg, false),
+        FilterType::Paeth => (png::FilterType::Paeth, false),
+        _ => (png::FilterType::Sub, true),
+    };
+    encoder.set_filter(filter);
+    encoder.set_adaptive_filter(if adaptive {
+        png::AdaptiveFilterType::Adaptive
+    } else {
+        png::AdaptiveFilterType::NonAdaptive
+    });
+
+    let data = pack(indices, width as usize, depth as u8);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(|error| {
+            ImageError::Encoding(EncodingError::new(
+                ImageFormatHint::Exact(ImageFormat::Png),
+                error,
+            ))
+        })
+}
+
+/// Writes 8-bit images losslessly with the fewest channels they need, or
+/// indexed when they have at most 256 colors and that is smaller.
+pub(super) struct ReducedPngEncoder<W> {
+    writer: W,
+    options: PngOptions,
+}
+
+impl<W: Write> ReducedPngEncoder<W> {
+    pub(super) fn new(writer: W, options: PngOptions) -> Self {
+        ReducedPngEncoder { writer, options }
+    }
+}
+
+impl<W: Write> ImageEncoder for ReducedPngEncoder<W> {
+    fn write_image(
+        mut self,
+        buf: &[u8],
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+    ) -> ImageResult<()> {
+        let rgba = to_rgba8(buf, color_type);
+        let opaque = rgba.chunks_exact(4).all(|pixel| pixel[3] == 255);
+        let gray = rgba
+            .chunks_exact(4)
+            .all(|pixel| pixel[0] == pixel[1] && pixel[1] == pixel[2]);
+        let (channels, color_type): (&[usize], _) = match (gray, opaque) {
+            (true, true) => (&[0], ColorType::L8),
+            (true, false) => (&[0, 3], ColorType::La8),
+            (false, true) => (&[0, 1, 2], ColorType::Rgb8),
+            (false, false) => (&[0, 1, 2, 3], ColorType::Rgba8),
+        };
+        let samples: Vec<u8> = rgba
+            .chunks_exact(4)
+            .flat_map(|pixel| channels.iter().map(|&channel| pixel[channel]))
+            .collect();
+        let mut png = Vec::new();
+        PngEncoder::new_with_quality(&mut png, self.options.compression, self.options.filter)
+            .write_image(&samples, width, height, color_type)?;
+        if let Some((palette, indices)) = exact_palette(&rgba, 256) {
+            let mut indexed = Vec::new();
+            write_indexed(
+                &mut indexed,
+                &self.options,
+                width,
+                height,
+                &palette,
+                &indices,
+            )?;
+            if indexed.len() < png.len() {
+                png = indexed;
+            }
+        }
+        self.writer.write_all(&png).map_err(ImageError::IoError)
+    }
+}
+
+/// Palette of the image when it has few enough distinct colors.
+fn exact_palette(rgba: &[u8], max_colors: usize) -> Option<(Vec<[u8; 4]>, Vec<u8>)> {
+    let mut palette = Vec::new();
+    let mut lookup = HashMap::new();
+    let mut indices = Vec::with_capacity(rgba.len() / 4);
+    for pixel in rgba.chunks_exact(4) {
+        let color = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        let index = match lookup.get(&color) {
+            Some(&index) => index,
+            None if palette.len() == max_colors => return None,
+            None => {
+                let index = palette.len() as u8;
+                palette.push(color);
+                lookup.insert(color, index);
+                index
+            }
+        };
+        indices.push(index);
+    }
+    Some((palette, indices))
+}
+
+fn quantized_palette(rgba: &[u8], max_colors: usize) -> (Vec<[u8; 4]>, Vec<u8>) {
+    let quantizer = color_quant::NeuQuant::new(10, max_colors, rgba);
+    let palette = quantizer
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|color| [color[0], color[1], color[2], color[3]])
+        .collect();
+    let indices = rgba
+        .chunks_exact(4)
+        .map(|pixel| quantizer.index_of(pixel) as u8)
+        .collect();
+    (palette, indices)
+}
+
+/// Packs one index per byte into rows of `depth` bits per pixel.
+fn pack(indices: &[u8], width: usize, depth: u8) -> Vec<u8> {
+    if depth == 8 || width == 0 {
+        return indices.to_vec();
+    }
+    let per_byte = usize::from(8 / depth);
+    indices
+        .chunks(width)
+        .flat_map(|row| {
+            row.chunks(per_byte).map(|indices| {
+                indices
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (position, index)| {
+                        byte | index << (8 - depth * (position as u8 + 1))
+                    })
+            })
+        })
+        .collect()
+}
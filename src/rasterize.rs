@@ -0,0 +1,104 @@
+use crate::{bytes_to_data_uri, DataUri, Error, Result};
+use resvg::tiny_skia::{Pixmap, Transform};
+use resvg::usvg::{fontdb, Options, Tree};
+use std::sync::{Arc, LazyLock};
+
+/// System fonts, loaded once for every rasterization.
+static FONTS: LazyLock<Arc<fontdb::Database>> = LazyLock::new(|| {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    Arc::new(fonts)
+});
+
+/// Rasterizes the SVG to a `width` × `height` PNG data URI, for consumers
+/// that can't render SVG.
+///
+/// The drawing keeps its aspect ratio and is centered, like an `<img>` of
+/// that size would show it. Text is rendered with the system fonts.
+pub fn svg_str_to_png_data_uri(svg: impl AsRef<str>, width: u32, height: u32) -> Result<DataUri> {
+    let tree = parse_svg(svg.as_ref())?;
+    render_png(&tree, width, height)
+}
+
+/// Rasterizes an `image/svg+xml` data URI to a PNG data URI, its intrinsic
+/// size multiplied by `scale`.
+///
+/// Fails with [`Error::UnsupportedMediaType`] for other media types.
+pub fn svg_data_uri_to_png_data_uri(uri: &str, scale: f32) -> Result<DataUri> {
+    let uri = DataUri::parse(uri)?;
+    if uri.mime().essence_str() != mime::IMAGE_SVG.essence_str() {
+        return Err(Error::UnsupportedMediaType(uri.mime().clone()));
+    }
+    let tree = parse_svg(&uri.decode_text()?)?;
+    let size = tree.size();
+    let scaled = |length: f32| (length * scale).ceil().clamp(0.0, u32::MAX as f32) as u32;
+    render_png(&tree, scaled(size.width()), scaled(size.height()))
+}
+
+pub(crate) fn parse_svg(svg: &str) -> Result<Tree> {
+    let options = Options {
+        fontdb: FONTS.clone(),
+        ..Options::default()
+    };
+    Tree::from_str(svg, &options).map_err(Error::Svg)
+}
+
+pub(crate) fn render_png(tree: &Tree, width: u32, height: u32) -> Result<DataUri> {
+    let size = tree.size();
+    let mut pixmap = Pixmap::new(width, height).ok_or(Error::InvalidSize { width, height })?;
+    let scale = (width as f32 / size.width()).min(height as f32 / size.height());
+    let transform = Transform::from_scale(scale, scale).post_translate(
+        (width as f32 - size.width() * scale) / 2.0,
+        (height as f32 - size.height() * scale) / 2.0,
+    );
+    resvg::render(tree, transform, &mut pixmap.as_mut());
+    let png = pixmap.encode_png().map_err(std::io::Error::other)?;
+    Ok(bytes_to_data_uri(&png, &mime::IMAGE_PNG))
+}
+
+#[cfg(all(test, feature = "image"))]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn rasterize_svg() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="2" height="1">
+            <rect width="1" height="1" fill="red"/>
+        </svg>"#;
+        let uri = svg_str_to_png_data_uri(svg, 4, 4).unwrap();
+        assert!(uri.starts_with("data:image/png;base64,"));
+        let image = image::load_from_memory(&data_uri_to_bytes(&uri).unwrap())
+            .unwrap()
+            .to_rgba8();
+        assert_eq!(image.dimensions(), (4, 4));
+        assert_eq!(image.get_pixel(0, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(3, 1).0[3], 0);
+        assert_eq!(image.get_pixel(0, 0).0[3], 0);
+
+        assert!(matches!(
+            svg_str_to_png_data_uri("<svg", 4, 4),
+            Err(Error::Svg(_))
+        ));
+        assert!(matches!(
+            svg_str_to_png_data_uri(svg, 0, 4),
+            Err(Error::InvalidSize { .. })
+        ));
+    }
+
+    #[test]
+    fn rasterize_svg_data_uri() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 5"/>"#;
+        let uri = svg_data_uri_to_png_data_uri(&svg_str_to_data_uri(svg), 1.5).unwrap();
+        let image = data_uri_to_image(&uri).unwrap();
+        assert_eq!((image.width(), image.height()), (15, 8));
+
+        assert!(matches!(
+            svg_data_uri_to_png_data_uri("data:text/plain,hi", 1.0),
+            Err(Error::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            svg_data_uri_to_png_data_uri(&svg_str_to_data_uri(svg), 0.0),
+            Err(Error::InvalidSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::raster::encode_dynamic_image;
+use crate::{
+    data_uri_dimensions, data_uri_to_image, decoded_len, validate_data_uri, DataUri, EncodeOptions,
+    Format,
+};
+use image::DynamicImage;
+
+/// Encodes `image` as a `format` data URI, parses it back and decodes it,
+/// panicking when a step fails or disagrees with the others, for use in
+/// tests:
+///
+/// - the URI parses back to itself and validates, with the media type of
+///   `format`,
+/// - the decoded payload has the length computed from the URI text,
+/// - the dimensions read from the header, when the format has a reader, and
+///   those of the decoded image are those of `image`,
+/// - for [lossless](Format::is_lossless) formats, the decoded pixels are
+///   exactly those of `image`, whatever the color type they decode to.
+///
+/// The color type of `image` is kept, so encoding fails, and this panics,
+/// when `format` can't store it. It returns the decoded image.
+///
+/// ```
+/// use data_uri_utils::{roundtrip, Format, PngOptions};
+///
+/// let image = image::RgbaImage::from_fn(4, 4, |x, y| image::Rgba([x as u8, y as u8, 0, 128]));
+/// roundtrip(&image.into(), Format::Png(PngOptions::default()));
+/// ```
+#[track_caller]
+pub fn roundtrip(image: &DynamicImage, format: Format) -> DynamicImage {
+    let uri = match encode_dynamic_image(image, format, None, &EncodeOptions::default()) {
+        Ok(uri) => uri,
+        Err(error) => panic!("{:?} encoding failed: {}", format, error),
+    };
+    let parsed = DataUri::parse(uri.as_str())
+        .unwrap_or_else(|error| panic!("{:?} data URI doesn't parse: {}", format, error));
+    assert_eq!(parsed.as_str(), uri.as_str(), "{:?} data URI text", format);
+    assert_eq!(*parsed.mime(), format.mime(), "{:?} media type", format);
+    if let Err(error) = validate_data_uri(&uri) {
+        panic!("{:?} data URI isn't valid: {}", format, error);
+    }
+
+    let payload = parsed
+        .decode_payload()
+        .unwrap_or_else(|error| panic!("{:?} payload doesn't decode: {}", format, error));
+    assert_eq!(
+        decoded_len(&uri).ok(),
+        Some(payload.len()),
+        "{:?} decoded length",
+        format
+    );
+    let dimensions = (image.width(), image.height());
+    if let Ok(header) = data_uri_dimensions(&uri) {
+        assert_eq!(header, dimensions, "{:?} header dimensions", format);
+    }
+
+    let decoded = data_uri_to_image(&uri)
+        .unwrap_or_else(|error| panic!("{:?} image doesn't decode: {}", format, error));
+    assert_eq!(
+        (decoded.width(), decoded.height()),
+        dimensions,
+        "{:?} decoded dimensions",
+        format
+    );
+    if format.is_lossless() {
+        let (expected, actual) = (image.to_rgba32f(), decoded.to_rgba32f());
+        if let Some((x, y, pixel)) = expected
+            .enumerate_pixels()
+            .find(|(x, y, pixel)| actual.get_pixel(*x, *y) != *pixel)
+        {
+            panic!(
+                "{:?} pixel ({}, {}) decoded as {:?} instead of {:?}",
+                format,
+                x,
+                y,
+                actual.get_pixel(x, y),
+                pixel
+            );
+        }
+    }
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn lossless_roundtrips() {
+        let rgba = image::RgbaImage::from_fn(16, 9, |x, y| {
+            image::Rgba([x as u8 * 16, y as u8 * 28, 200, 255 - x as u8])
+        });
+        let luma16 = image::DynamicImage::ImageLuma16(image::ImageBuffer::from_fn(5, 3, |x, y| {
+            image::Luma([x as u16 * 12_000 + y as u16])
+        }));
+        let png = Format::Png(PngOptions::default());
+        for format in [
+            png,
+            Format::WebP(WebPQuality::Lossless),
+            Format::Bmp,
+            Format::Tiff,
+            Format::Ico,
+        ] {
+            roundtrip(&rgba.clone().into(), format);
+        }
+        roundtrip(&luma16, png);
+        roundtrip(&luma16, Format::Tiff);
+
+        let jpeg = roundtrip(
+            &image::RgbImage::new(8, 8).into(),
+            Format::Jpeg(JpegOptions::new(90)),
+        );
+        assert_eq!(jpeg.width(), 8);
+        assert!(!Format::Gif.is_lossless());
+    }
+
+    #[test]
+    #[should_panic(expected = "encoding failed")]
+    fn unsupported_color_type() {
+        let image = image::DynamicImage::ImageRgba16(image::ImageBuffer::new(2, 2));
+        roundtrip(&image, Format::Jpeg(JpegOptions::default()));
+    }
+}
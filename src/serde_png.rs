@@ -0,0 +1,48 @@
+//! Serializes a [`DynamicImage`] as a PNG data URI, for use with
+//! `#[serde(with = "data_uri_utils::serde_png")]`.
+//!
+//! Deserialization accepts any image data URI the `image` crate can decode.
+
+use crate::{data_uri_to_image, image_to_png_data_uri};
+use image::DynamicImage;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(image: &DynamicImage, serializer: S) -> Result<S::Ok, S::Error> {
+    let uri = image_to_png_data_uri(image).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&uri)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DynamicImage, D::Error> {
+    let uri = String::deserialize(deserializer)?;
+    data_uri_to_image(&uri).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::DynamicImage;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Thumbnail {
+        #[serde(with = "crate::serde_png")]
+        image: DynamicImage,
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let image = DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            2,
+            1,
+            image::Rgba([1, 2, 3, 4]),
+        ));
+        let json = serde_json::to_string(&Thumbnail {
+            image: image.clone(),
+        })
+        .unwrap();
+        assert!(json.starts_with(r#"{"image":"data:image/png;base64,"#));
+        let thumbnail: Thumbnail = serde_json::from_str(&json).unwrap();
+        assert_eq!(thumbnail.image, image);
+
+        let error = serde_json::from_str::<Thumbnail>(r#"{"image":"data:text/plain,hi"}"#);
+        assert!(error.is_err());
+    }
+}
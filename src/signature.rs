@@ -0,0 +1,187 @@
+use crate::encoding::encode_base64;
+use crate::{Base64Alphabet, DataUri, Error, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// Media type parameter holding the signature.
+const PARAMETER: &str = "sig";
+const BLOCK_LEN: usize = 64;
+
+/// Adds a `sig` parameter to the media type of `uri`, an HMAC-SHA256 of its
+/// media type and decoded payload under `key`, so that edits made while the
+/// data URI passes through untrusted hands are detected by
+/// [`verify_data_uri`].
+///
+/// The signature survives re-encoding the payload, as long as the content
+/// stays the same. An existing signature is replaced.
+///
+/// ```
+/// use data_uri_utils::{sign_data_uri, verify_data_uri, Error};
+///
+/// let signed = sign_data_uri("data:image/svg+xml,%3Csvg/%3E", b"secret")?;
+/// assert!(signed.starts_with("data:image/svg+xml;sig="));
+/// assert_eq!(verify_data_uri(&signed, b"secret")?, "data:image/svg+xml,%3Csvg/%3E");
+///
+/// let edited = signed.replace("svg/", "svg onload='alert(1)'/");
+/// assert!(matches!(verify_data_uri(&edited, b"secret"), Err(Error::InvalidSignature)));
+/// # Ok::<_, Error>(())
+/// ```
+pub fn sign_data_uri(uri: &str, key: &[u8]) -> Result<DataUri> {
+    let (unsigned, _) = split_signature(uri)?;
+    let signature = encode_base64(&mac(&unsigned, key)?, Base64Alphabet::UrlSafe, false);
+    let comma = unsigned.find(',').unwrap_or(unsigned.len());
+    let header = &unsigned[..comma];
+    let at = match header.rfind(';') {
+        Some(split)
+            if ["base64", "base45"]
+                .iter()
+                .any(|marker| header[split + 1..].trim().eq_ignore_ascii_case(marker)) =>
+        {
+            split
+        }
+        _ => comma,
+    };
+    let signed = format!(
+        "{};{}={}{}",
+        &unsigned[..at],
+        PARAMETER,
+        signature,
+        &unsigned[at..]
+    );
+    Ok(DataUri::parse(&signed)?)
+}
+
+/// Checks the signature added by [`sign_data_uri`], returning the data URI
+/// without it.
+///
+/// Fails with [`Error::MissingSignature`] on unsigned data URIs and with
+/// [`Error::InvalidSignature`] when the content or media type changed.
+pub fn verify_data_uri(uri: &str, key: &[u8]) -> Result<DataUri> {
+    let (unsigned, signature) = split_signature(uri)?;
+    let signature = signature.ok_or(Error::MissingSignature)?;
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| Error::InvalidSignature)?;
+    let expected = mac(&unsigned, key)?;
+    // Compared in constant time so that the signature can't be guessed byte
+    // by byte from response times.
+    let difference = signature
+        .iter()
+        .zip(expected)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    if signature.len() != expected.len() || difference != 0 {
+        return Err(Error::InvalidSignature);
+    }
+    Ok(DataUri::parse(&unsigned)?)
+}
+
+/// `uri` without its `sig` parameter, and the parameter value.
+fn split_signature(uri: &str) -> Result<(String, Option<&str>)> {
+    let uri = uri.trim();
+    DataUri::parse(uri)?;
+    let comma = uri.find(',').unwrap_or(uri.len());
+    let mut signature = None;
+    let mut unsigned = String::with_capacity(uri.len());
+    for (index, segment) in uri[..comma].split(';').enumerate() {
+        if let Some((name, value)) = segment.split_once('=') {
+            if index > 0 && name.trim().eq_ignore_ascii_case(PARAMETER) {
+                signature = Some(value.trim().trim_matches('"'));
+                continue;
+            }
+        }
+        if index > 0 {
+            unsigned.push(';');
+        }
+        unsigned.push_str(segment);
+    }
+    unsigned.push_str(&uri[comma..]);
+    Ok((unsigned, signature))
+}
+
+/// HMAC-SHA256 of the normalized media type and decoded payload, hashed as
+/// in [`data_uri_digest`](crate::data_uri_digest).
+fn mac(uri: &str, key: &[u8]) -> Result<[u8; 32]> {
+    let uri = DataUri::parse(uri)?;
+    let payload = uri.decode_payload()?;
+    Ok(hmac_sha256(
+        key,
+        &[uri.normalized_mime().as_bytes(), &[0], &payload],
+    ))
+}
+
+/// RFC 2104 HMAC of the concatenated `message` parts.
+fn hmac_sha256(key: &[u8], message: &[&[u8]]) -> [u8; 32] {
+    let mut block = [0; BLOCK_LEN];
+    if key.len() > BLOCK_LEN {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|key| key ^ byte);
+
+    let mut inner = Sha256::new();
+    inner.update(pad(0x36));
+    for part in message {
+        inner.update(part);
+    }
+    let mut outer = Sha256::new();
+    outer.update(pad(0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hmac_sha256;
+    use crate::*;
+
+    #[test]
+    fn sign_and_verify() {
+        let signed = sign_data_uri("data:;base64,aGk=", b"key").unwrap();
+        assert!(signed.starts_with("data:;sig="));
+        assert!(signed.ends_with(";base64,aGk="));
+        let reencoded = signed.replace(";base64,aGk=", ",hi");
+        assert_eq!(verify_data_uri(&reencoded, b"key").unwrap(), "data:,hi");
+        assert!(matches!(
+            verify_data_uri(&signed, b"other key"),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_data_uri(&signed.replace("data:", "data:text/html"), b"key"),
+            Err(Error::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_data_uri("data:,hi", b"key"),
+            Err(Error::MissingSignature)
+        ));
+        let resigned = sign_data_uri(&signed, b"other key").unwrap();
+        assert_eq!(resigned.matches("sig=").count(), 1);
+        assert!(verify_data_uri(&resigned, b"other key").is_ok());
+    }
+
+    #[test]
+    fn hmac_test_vectors() {
+        // RFC 4231 test cases 2 and 6.
+        let hex = |digest: [u8; 32]| {
+            digest
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        };
+        assert_eq!(
+            hex(hmac_sha256(
+                b"Jefe",
+                &[b"what do ya want ", b"for nothing?"]
+            )),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex(hmac_sha256(
+                &[0xaa; 131],
+                &[b"Test Using Larger Than Block-Size Key - Hash Key First"]
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Base64 of payloads through `base64-simd`, enabled with the `simd` feature,
+//! which picks the widest instructions of the CPU at run time.
+//!
+//! Errors, and the unpadded digits the scalar engine also accepts, are left
+//! to the scalar engine.
+
+use crate::Base64Alphabet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64::{DecodeError, Engine};
+
+/// Appends `bytes` base64-encoded to `out`, as `alphabet.engine(pad)` would.
+pub(crate) fn encode_into(bytes: &[u8], alphabet: Base64Alphabet, pad: bool, out: &mut String) {
+    let base64 = match (alphabet, pad) {
+        (Base64Alphabet::Standard, true) => &base64_simd::STANDARD,
+        (Base64Alphabet::Standard, false) => &base64_simd::STANDARD_NO_PAD,
+        (Base64Alphabet::UrlSafe, _) => &base64_simd::URL_SAFE_NO_PAD,
+    };
+    base64.encode_append(bytes, out);
+}
+
+/// Appends the decoded `digits` to `out` when they are plain standard
+/// base64, as `Base64Alphabet::Standard.engine(true)` would, leaving `out` as
+/// it was on error. Returns `None` for digits with whitespace, percent escapes
+/// or URL-safe digits, leaving them to the scalar engine.
+pub(crate) fn decode_standard(digits: &[u8], out: &mut Vec<u8>) -> Option<Result<(), DecodeError>> {
+    let start = out.len();
+    if base64_simd::STANDARD.decode_append(digits, out).is_ok() {
+        return Some(Ok(()));
+    }
+    out.truncate(start);
+    if digits
+        .iter()
+        .any(|&byte| matches!(byte, b'%' | b'-' | b'_') || byte.is_ascii_whitespace())
+    {
+        return None;
+    }
+    Some(
+        Base64Alphabet::Standard
+            .engine(true)
+            .decode_vec(digits, out)
+            .inspect_err(|_| out.truncate(start)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_standard, encode_into};
+    use crate::Base64Alphabet;
+    use base64::Engine;
+
+    #[test]
+    fn matches_scalar_engine() {
+        let mut state = 0x2545_f491_u32;
+        let bytes: Vec<u8> = (0..1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        let standard = Base64Alphabet::Standard.engine(true);
+        for len in (0..200).chain([999, 1000]) {
+            let bytes = &bytes[..len];
+            for (alphabet, pad) in [
+                (Base64Alphabet::Standard, true),
+                (Base64Alphabet::Standard, false),
+                (Base64Alphabet::UrlSafe, false),
+            ] {
+                let mut encoded = String::from("data:,");
+                encode_into(bytes, alphabet, pad, &mut encoded);
+                assert_eq!(encoded[6..], alphabet.engine(pad).encode(bytes), "{}", len);
+            }
+            let digits = standard.encode(bytes);
+            let mut decoded = vec![7];
+            decode_standard(digits.as_bytes(), &mut decoded)
+                .unwrap()
+                .unwrap();
+            assert_eq!(decoded[1..], *bytes);
+        }
+
+        let digits = standard.encode(&bytes);
+        for (at, invalid) in [(100, b'='), (500, 0xc3), (1330, b'.')] {
+            let mut digits = digits.clone().into_bytes();
+            digits[at] = invalid;
+            let mut decoded = vec![7];
+            assert_eq!(
+                decode_standard(&digits, &mut decoded),
+                Some(Err(standard.decode(&digits).unwrap_err()))
+            );
+            assert_eq!(decoded, [7]);
+        }
+        for (at, other) in [(0, b'-'), (700, b'\n'), (1320, b'%')] {
+            let mut digits = digits.clone().into_bytes();
+            digits[at] = other;
+            let mut decoded = vec![7];
+            assert_eq!(decode_standard(&digits, &mut decoded), None);
+            assert_eq!(decoded, [7]);
+        }
+        for digits in ["QQ", "QR==", "QUI", "QUJ="] {
+            let mut decoded = Vec::new();
+            assert_eq!(
+                decode_standard(digits.as_bytes(), &mut decoded),
+                Some(standard.decode_vec(digits, &mut Vec::new())),
+                "{}",
+                digits
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::markup::{Token, Tokenizer};
+use mime::Mime;
+
+/// Guesses the media type from the payload's leading bytes.
+///
+/// Recognizes PNG, JPEG, GIF, WebP, AVIF, BMP, ICO, TIFF, PDF, SVG and the
+/// WOFF, WOFF2, TrueType and OpenType fonts.
+pub fn sniff_mime(bytes: &[u8]) -> Option<Mime> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\0\0\x01\0", "image/x-icon"),
+        (b"II*\0", "image/tiff"),
+        (b"MM\0*", "image/tiff"),
+        (b"%PDF-", "application/pdf"),
+        (b"wOF2", "font/woff2"),
+        (b"wOFF", "font/woff"),
+        (b"\0\x01\0\0", "font/ttf"),
+        (b"OTTO", "font/otf"),
+        (b"ttcf", "font/collection"),
+    ];
+    let essence = match bytes {
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [_, _, _, _, b'f', b't', b'y', b'p', b'a', b'v', b'i', b'f' | b's', ..] => {
+            Some("image/avif")
+        }
+        // The bytes after the file size are reserved and zero.
+        [b'B', b'M', _, _, _, _, 0, 0, 0, 0, ..] => Some("image/bmp"),
+        _ => SIGNATURES
+            .iter()
+            .find(|(signature, _)| bytes.starts_with(signature))
+            .map(|(_, essence)| *essence),
+    };
+    match essence {
+        Some(essence) => essence.parse().ok(),
+        None => is_svg(bytes).then_some(mime::IMAGE_SVG),
+    }
+}
+
+/// Whether the first element, past the prolog, comments and whitespace, is `<svg>`.
+fn is_svg(bytes: &[u8]) -> bool {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return false;
+    };
+    let text = text.trim_start_matches('\u{FEFF}');
+    for token in Tokenizer::new(text) {
+        match token {
+            Token::StartTag(tag) => return tag.name == "svg" || tag.name.ends_with(":svg"),
+            Token::Text(text) if !text.trim().is_empty() => return false,
+            Token::EndTag { .. } | Token::Cdata(_) => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn sniff_signatures() {
+        let sniff = |bytes: &[u8]| sniff_mime(bytes).map(|mime| mime.essence_str().to_string());
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0").as_deref(), Some("image/png"));
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0").as_deref(), Some("image/jpeg"));
+        assert_eq!(
+            sniff(b"RIFF\x24\0\0\0WEBPVP8L").as_deref(),
+            Some("image/webp")
+        );
+        assert_eq!(
+            sniff(b"\0\0\0\x1cftypavif\0\0\0\0").as_deref(),
+            Some("image/avif")
+        );
+        assert_eq!(
+            sniff(b"BM\x3a\0\0\0\0\0\0\0\x36\0").as_deref(),
+            Some("image/bmp")
+        );
+        assert_eq!(sniff(b"BMW 320i"), None);
+        assert_eq!(sniff(b"%PDF-1.7").as_deref(), Some("application/pdf"));
+        assert_eq!(sniff(b"\0\x01\0\0\0\x10").as_deref(), Some("font/ttf"));
+        assert_eq!(sniff(b"OTTO\0\x0b").as_deref(), Some("font/otf"));
+        assert_eq!(
+            sniff("\u{FEFF}<!-- x -->\n<svg/>".as_bytes()).as_deref(),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff(b"<html><svg/></html>"), None);
+        assert_eq!(sniff(b"RIFF\x24\0\0\0WAVEfmt "), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn sniffed_data_uri() {
+        assert_eq!(
+            bytes_to_data_uri_sniffed(b"GIF89a"),
+            "data:image/gif;base64,R0lGODlh"
+        );
+        assert_eq!(
+            bytes_to_data_uri_sniffed(b"\0\x01"),
+            "data:application/octet-stream;base64,AAE="
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{DataUri, ParseError};
+
+/// Cuts the data URI into parts of at most `chunk_len` bytes, for transports
+/// that cap the length of a single URI. [`join_data_uri`] puts them back.
+///
+/// Parts never split a character, so a part can be shorter than `chunk_len`
+/// when the URI isn't ASCII.
+///
+/// # Panics
+///
+/// Panics if `chunk_len` is smaller than 4, the longest UTF-8 character.
+pub fn split_data_uri(uri: &str, chunk_len: usize) -> Vec<String> {
+    assert!(chunk_len >= 4, "chunk_len must be at least 4");
+    let mut parts = Vec::with_capacity(uri.len().div_ceil(chunk_len));
+    let mut rest = uri;
+    while !rest.is_empty() {
+        let mut end = chunk_len.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (part, tail) = rest.split_at(end);
+        parts.push(part.to_string());
+        rest = tail;
+    }
+    parts
+}
+
+/// Concatenates the parts produced by [`split_data_uri`] and parses the result.
+pub fn join_data_uri<I>(parts: I) -> Result<DataUri, ParseError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut uri = String::new();
+    for part in parts {
+        uri.push_str(part.as_ref());
+    }
+    DataUri::parse(&uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn split_and_join() {
+        let uri = bytes_to_data_uri(&[7; 100], &mime::APPLICATION_OCTET_STREAM);
+        let parts = split_data_uri(&uri, 32);
+        assert_eq!(parts.len(), 6);
+        assert!(parts.iter().all(|part| part.len() <= 32));
+        assert_eq!(join_data_uri(&parts).unwrap(), uri);
+
+        let parts = split_data_uri("data:,h\u{e9}llo", 7);
+        assert_eq!(parts, ["data:,h", "\u{e9}llo"]);
+        assert!(split_data_uri("", 8).is_empty());
+        assert_eq!(join_data_uri(["data:,a", "b"]).unwrap(), "data:,ab");
+        assert_eq!(
+            join_data_uri(["image/png", ";base64,"]),
+            Err(ParseError::MissingScheme)
+        );
+    }
+}
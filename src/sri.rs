@@ -0,0 +1,74 @@
+use crate::encoding::encode_base64;
+use crate::{Base64Alphabet, DataUri, Result};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// Hash function of a subresource integrity string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SriAlgorithm {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// The subresource integrity string of `bytes`, such as `sha256-...`.
+///
+/// ```
+/// use data_uri_utils::{sri_hash, SriAlgorithm};
+///
+/// assert_eq!(
+///     sri_hash(b"hi", SriAlgorithm::Sha256),
+///     "sha256-j0NDRmSPa5bfid2pAcUXaxCm2Dlh3TwayItZstwyeqQ="
+/// );
+/// ```
+pub fn sri_hash(bytes: &[u8], algorithm: SriAlgorithm) -> String {
+    let (name, digest) = match algorithm {
+        SriAlgorithm::Sha256 => ("sha256", Sha256::digest(bytes).to_vec()),
+        SriAlgorithm::Sha384 => ("sha384", Sha384::digest(bytes).to_vec()),
+        SriAlgorithm::Sha512 => ("sha512", Sha512::digest(bytes).to_vec()),
+    };
+    format!(
+        "{}-{}",
+        name,
+        encode_base64(&digest, Base64Alphabet::Standard, true)
+    )
+}
+
+/// The subresource integrity string of the decoded payload, which is what a
+/// browser checks once the asset is served from its own file.
+pub fn data_uri_sri_hash(uri: &str, algorithm: SriAlgorithm) -> Result<String> {
+    let payload = DataUri::parse(uri)?.decode_payload()?;
+    Ok(sri_hash(&payload, algorithm))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn integrity_attributes() {
+        let uri = "data:text/plain,hi";
+        assert_eq!(
+            data_uri_sri_hash(uri, SriAlgorithm::Sha384).unwrap(),
+            sri_hash(b"hi", SriAlgorithm::Sha384)
+        );
+        assert!(sri_hash(b"", SriAlgorithm::Sha512).starts_with("sha512-z4PhNX7vuL3xVChQ1m2AB9Yg"));
+
+        let svg = svg_str_to_data_uri("<svg width='2' height='1'/>");
+        let tag =
+            data_uri_to_img_tag_with_integrity(&svg, "", None, None, Default::default()).unwrap();
+        assert_eq!(
+            tag,
+            format!(
+                "<img src=\"{}\" alt=\"\" width=\"2\" height=\"1\" integrity=\"{}\">",
+                svg,
+                data_uri_sri_hash(&svg, SriAlgorithm::Sha256).unwrap()
+            )
+        );
+        let tag =
+            data_uri_to_object_tag_with_integrity(uri, None, None, SriAlgorithm::Sha256).unwrap();
+        assert!(tag.ends_with(
+            " integrity=\"sha256-j0NDRmSPa5bfid2pAcUXaxCm2Dlh3TwayItZstwyeqQ=\"></object>"
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::DataUri;
+use std::borrow::Cow;
+use std::fmt;
+
+/// The header of a data URI followed by the size of its payload, and its
+/// SHA-256 prefix with the `sha2` feature, instead of the payload itself.
+///
+/// `{:#}` and `{:#?}` format a [`DataUri`] this way. Strings that aren't data
+/// URIs are returned as they are.
+///
+/// ```
+/// let uri = data_uri_utils::bytes_to_data_uri(&[0; 35021], &mime::IMAGE_PNG);
+/// let summary = data_uri_utils::summarize(&uri);
+/// assert!(summary.starts_with("data:image/png;base64,<34.2 KiB payload"));
+/// assert_eq!(format!("{:#}", uri), summary);
+/// ```
+pub fn summarize(uri: &str) -> String {
+    match DataUri::parse(uri) {
+        Ok(uri) => format!("{:#}", uri),
+        Err(_) => uri.to_string(),
+    }
+}
+
+/// Replaces every data URI in `text`, such as a log line or an error
+/// message, with its [`summarize`] form.
+///
+/// ```
+/// let line = r#"rendering <img src="data:image/gif;base64,R0lGODlh"> failed"#;
+/// assert_eq!(
+///     data_uri_utils::redact_data_uris(line).split(" payload").next(),
+///     Some(r#"rendering <img src="data:image/gif;base64,<6 B"#)
+/// );
+/// ```
+pub fn redact_data_uris(text: &str) -> Cow<'_, str> {
+    let mut redacted = String::new();
+    let mut position = 0;
+    let mut search = 0;
+    while let Some(found) = find_scheme(&text[search..]) {
+        let start = search + found;
+        let len = text[start..]
+            .find(|c: char| c.is_whitespace() || c.is_control() || "\"'`<>()".contains(c))
+            .unwrap_or(text.len() - start);
+        search = start + len.max(5);
+        let Ok(uri) = DataUri::parse(&text[start..start + len]) else {
+            continue;
+        };
+        redacted.push_str(&text[position..start]);
+        redacted.push_str(&format!("{:#}", uri));
+        position = start + len;
+    }
+    if position == 0 {
+        return Cow::Borrowed(text);
+    }
+    redacted.push_str(&text[position..]);
+    Cow::Owned(redacted)
+}
+
+/// Offset of the next `data:` not preceded by a letter or digit.
+fn find_scheme(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    (0..bytes.len().saturating_sub(4)).find(|&i| {
+        bytes[i..i + 5].eq_ignore_ascii_case(b"data:")
+            && (i == 0 || !bytes[i - 1].is_ascii_alphanumeric())
+    })
+}
+
+pub(crate) fn write_summary(uri: &DataUri, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let header = &uri.as_str()[..uri.as_str().len() - uri.payload().len()];
+    let len = uri.payload_len();
+    write!(f, "{}<", header)?;
+    match len {
+        0..=1023 => write!(f, "{} B", len)?,
+        1024..=1048575 => write!(f, "{:.1} KiB", len as f64 / 1024.0)?,
+        _ => write!(f, "{:.1} MiB", len as f64 / (1024.0 * 1024.0))?,
+    }
+    write!(f, " payload")?;
+    #[cfg(feature = "sha2")]
+    if let Ok(payload) = uri.decode_payload() {
+        use sha2::Digest;
+        let digest = sha2::Sha256::digest(payload);
+        write!(f, ", sha256:{:02x}{:02x}…", digest[0], digest[1])?;
+    }
+    write!(f, ">")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn summaries() {
+        let uri = DataUri::parse("data:text/plain;charset=utf-8,hello%20world").unwrap();
+        let summary = if cfg!(feature = "sha2") {
+            "data:text/plain;charset=utf-8,<11 B payload, sha256:b94d…>"
+        } else {
+            "data:text/plain;charset=utf-8,<11 B payload>"
+        };
+        assert_eq!(format!("{:#}", uri), summary);
+        assert_eq!(format!("{:#?}", uri), format!("DataUri({:?})", summary));
+        assert_eq!(uri.to_string(), uri.as_str());
+        assert_eq!(summarize("not a data URI"), "not a data URI");
+
+        let log = format!("a metadata:{uri} (url({uri})) data:oops");
+        assert_eq!(
+            redact_data_uris(&log),
+            format!("a metadata:{summary} (url({summary})) data:oops")
+        );
+        assert!(matches!(
+            redact_data_uris("clean"),
+            std::borrow::Cow::Borrowed("clean")
+        ));
+    }
+}
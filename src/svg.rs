@@ -0,0 +1,312 @@
+use crate::encoding::{encode_svg_segment, trim_byte_order_mark};
+use crate::{DataUri, DataUriBuilder, EncodeSet, Encoding};
+use std::fmt;
+use std::sync::LazyLock;
+
+#[cfg(feature = "roxmltree")]
+mod check;
+mod decode;
+mod inline;
+mod minify;
+mod protected;
+mod recolor;
+mod sanitize;
+mod size;
+mod whitespace;
+
+#[cfg(feature = "roxmltree")]
+pub use check::{check_svg, try_svg_str_to_data_uri_with};
+pub use decode::{svg_bytes_to_data_uri, svg_bytes_to_data_uri_with};
+pub use inline::inline_svg_images;
+pub use recolor::recolor_svg;
+pub(crate) use sanitize::has_active_content;
+pub use sanitize::sanitize_svg;
+pub(crate) use size::svg_dimensions;
+pub use size::{set_svg_size, svg_size, SvgSize};
+pub use whitespace::SvgWhitespace;
+use whitespace::{collapse_svg_whitespace, for_each_segment};
+
+static IMAGE_SVG_UTF_8: LazyLock<mime::Mime> =
+    LazyLock::new(|| "image/svg+xml;charset=utf-8".parse().unwrap());
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SvgEncodeOptions {
+    pub encode_set: EncodeSet,
+    /// Strip comments, the XML prolog, doctypes without entity declarations,
+    /// `<metadata>`, editor namespaces such as Inkscape's and insignificant
+    /// whitespace.
+    pub minify: bool,
+    /// Swap double quotes for single quotes so they don't need escaping.
+    ///
+    /// Skipped when the SVG already contains single quotes.
+    pub single_quotes: bool,
+    /// Declare `charset=utf-8`. Without it readers fall back to XML's own
+    /// detection, which also defaults to UTF-8 unless the prolog says otherwise.
+    pub utf8_charset: bool,
+    /// Override the root `width` and `height`, see [`set_svg_size`].
+    pub size: Option<(String, String)>,
+    /// Run [`sanitize_svg`] first, for SVGs from untrusted sources.
+    pub sanitize: bool,
+    /// Replace `fill` and `stroke` colors, see [`recolor_svg`].
+    pub recolor: Option<String>,
+    /// Where runs of whitespace are collapsed.
+    pub whitespace: SvgWhitespace,
+    /// Replace bytes that aren't valid in the file's charset with U+FFFD
+    /// instead of failing, see [`svg_bytes_to_data_uri_with`].
+    pub lossy_decoding: bool,
+}
+
+impl SvgEncodeOptions {
+    /// Minimal escaping and single quotes, the smallest output.
+    pub fn mini() -> Self {
+        SvgEncodeOptions {
+            encode_set: EncodeSet::Minimal,
+            minify: false,
+            single_quotes: true,
+            utf8_charset: false,
+            size: None,
+            sanitize: false,
+            recolor: None,
+            whitespace: SvgWhitespace::Collapse,
+            lossy_decoding: false,
+        }
+    }
+
+    /// Emits the icon in `color`, e.g. `currentColor` or `#0af`.
+    pub fn recolor(mut self, color: impl Into<String>) -> Self {
+        self.recolor = Some(color.into());
+        self
+    }
+}
+
+pub fn svg_str_to_data_uri(svg: impl AsRef<str>) -> DataUri {
+    svg_str_to_data_uri_with(svg, &SvgEncodeOptions::default())
+}
+
+pub fn svg_str_to_data_uri_with(svg: impl AsRef<str>, options: &SvgEncodeOptions) -> DataUri {
+    let svg = svg.as_ref();
+    let mut uri = String::with_capacity(svg.len() * 2);
+    encode_svg::<()>(svg, options, |part| {
+        uri.push_str(part);
+        Ok(())
+    })
+    .unwrap();
+    let mime = if options.utf8_charset {
+        IMAGE_SVG_UTF_8.clone()
+    } else {
+        mime::IMAGE_SVG
+    };
+    DataUri::from_parts(uri, mime, Encoding::Percent)
+}
+
+/// Writes the data URI [`svg_str_to_data_uri`] would return into `out`.
+pub fn fmt_svg_data_uri(svg: &str, out: &mut impl fmt::Write) -> fmt::Result {
+    fmt_svg_data_uri_with(svg, &SvgEncodeOptions::default(), out)
+}
+
+/// Writes the data URI [`svg_str_to_data_uri_with`] would return into `out`,
+/// encoding the SVG on the fly. Nothing is allocated unless `minify`, `size`,
+/// `sanitize` or `recolor` is set.
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// let mut html = String::new();
+/// for icon in ["<svg/>", "<svg>\n  <g/>\n</svg>"] {
+///     html.push_str("<img src=\"");
+///     data_uri_utils::fmt_svg_data_uri(icon, &mut html).unwrap();
+///     html.push_str("\">");
+/// }
+/// assert!(html.starts_with("<img src=\"data:image/svg+xml,%3Csvg%2F%3E\">"));
+/// ```
+pub fn fmt_svg_data_uri_with(
+    svg: &str,
+    options: &SvgEncodeOptions,
+    out: &mut impl fmt::Write,
+) -> fmt::Result {
+    encode_svg(svg, options, |part| out.write_str(part))
+}
+
+fn encode_svg<E>(
+    svg: &str,
+    options: &SvgEncodeOptions,
+    mut emit: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), E> {
+    let svg = trim_byte_order_mark(svg);
+    let sanitized;
+    let svg = if options.sanitize {
+        sanitized = sanitize_svg(svg);
+        sanitized.as_str()
+    } else {
+        svg
+    };
+    let recolored;
+    let svg = match &options.recolor {
+        Some(color) => {
+            recolored = recolor_svg(svg, color);
+            recolored.as_str()
+        }
+        None => svg,
+    };
+    let resized;
+    let svg = match &options.size {
+        Some((width, height)) => {
+            resized = set_svg_size(svg, width, height);
+            resized.as_ref()
+        }
+        None => svg,
+    };
+    let minified;
+    let svg = if options.minify {
+        minified = minify::minify(svg);
+        minified.as_str()
+    } else {
+        svg
+    };
+    emit(if options.utf8_charset {
+        "data:image/svg+xml;charset=utf-8,"
+    } else {
+        "data:image/svg+xml,"
+    })?;
+    let swap_quotes = options.single_quotes && svg.contains('"') && !svg.contains('\'');
+    for_each_segment(svg.trim(), options.whitespace, |segment, collapse| {
+        encode_svg_segment(
+            segment,
+            options.encode_set,
+            swap_quotes,
+            collapse,
+            &mut emit,
+        )
+    })
+}
+
+/// An SVG formatted as a data URI when displayed, without an intermediate
+/// `String`.
+///
+/// ```
+/// let icon = data_uri_utils::SvgDataUri::new("<svg/>");
+/// assert_eq!(format!("<img src=\"{}\">", icon), r#"<img src="data:image/svg+xml,%3Csvg%2F%3E">"#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SvgDataUri<'a> {
+    svg: &'a str,
+    options: SvgEncodeOptions,
+}
+
+impl<'a> SvgDataUri<'a> {
+    pub fn new(svg: &'a str) -> Self {
+        Self::with_options(svg, SvgEncodeOptions::default())
+    }
+
+    pub fn with_options(svg: &'a str, options: SvgEncodeOptions) -> Self {
+        SvgDataUri { svg, options }
+    }
+
+    pub(crate) fn to_uri(&self) -> DataUri {
+        svg_str_to_data_uri_with(self.svg, &self.options)
+    }
+}
+
+impl fmt::Display for SvgDataUri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_svg_data_uri_with(self.svg, &self.options, f)
+    }
+}
+
+/// Base64 variant of [`svg_str_to_data_uri`], for consumers that reject
+/// percent-encoded payloads.
+pub fn svg_str_to_data_uri_base64(svg: impl AsRef<str>, utf8_charset: bool) -> DataUri {
+    let svg = trim_byte_order_mark(svg.as_ref()).trim();
+    let mut builder = DataUriBuilder::new().media_type(&mime::IMAGE_SVG);
+    if utf8_charset {
+        builder = builder.charset("utf-8");
+    }
+    builder.build_str(&collapse_svg_whitespace(svg, SvgWhitespace::Collapse))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn full_test() {
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 50 50">
+                <path d="M22 38V51L32 32l19-19v12C44 26 43 10 38 0 52 15 49 39 22 38z"/>
+            </svg>"##;
+        let expected = r#"data:image/svg+xml,%3Csvg%20xmlns%3D%22http%3A%2F%2Fwww%2Ew3%2Eorg%2F2000%2Fsvg%22%20viewBox%3D%220%200%2050%2050%22%3E%20%3Cpath%20d%3D%22M22%2038V51L32%2032l19%2D19v12C44%2026%2043%2010%2038%200%2052%2015%2049%2039%2022%2038z%22%2F%3E%20%3C%2Fsvg%3E"#;
+        let result = svg_str_to_data_uri(svg);
+        assert_eq!(result, expected);
+        assert_eq!(svg_str_to_data_uri(format!("\u{FEFF}{}", svg)), expected);
+    }
+
+    #[test]
+    fn whitespace_collapsing() {
+        assert!(matches!(
+            collapse_whitespace("<g> <path/> </g>"),
+            Cow::Borrowed("<g> <path/> </g>")
+        ));
+        assert_eq!(collapse_whitespace("a  b\tc \n\u{a0} d "), "a b c d ");
+        assert_eq!(collapse_whitespace("\r\n<svg>\r\n"), " <svg> ");
+        assert_eq!(collapse_whitespace(""), "");
+    }
+
+    #[test]
+    fn mini_svg() {
+        let svg = r##"
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 50 50">
+                <path fill="#000" d="M22 38V51L32 32l19-19v12C44 26 43 10 38 0 52 15 49 39 22 38z"/>
+            </svg>"##;
+        let expected = r#"data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 50 50'%3E %3Cpath fill='%23000' d='M22 38V51L32 32l19-19v12C44 26 43 10 38 0 52 15 49 39 22 38z'/%3E %3C/svg%3E"#;
+        let result = svg_str_to_data_uri_with(svg, &SvgEncodeOptions::mini());
+        assert_eq!(result, expected);
+        let lazy = SvgDataUri::with_options(svg, SvgEncodeOptions::mini());
+        assert_eq!(lazy.to_string(), expected);
+        let decoded = DataUri::parse(&result).unwrap().decode_payload().unwrap();
+        assert!(String::from_utf8(decoded).unwrap().contains("fill='#000'"));
+
+        let quoted = r#"<text font-family="'Open Sans'">é</text>"#;
+        let result = svg_str_to_data_uri_with(quoted, &SvgEncodeOptions::mini());
+        assert_eq!(
+            result,
+            "data:image/svg+xml,%3Ctext font-family=%22'Open Sans'%22%3E%C3%A9%3C/text%3E"
+        );
+    }
+
+    #[test]
+    fn minified_svg() {
+        let svg = "<?xml version=\"1.0\"?>\n<!-- icon -->\n<svg>\n  <path d=\" M0 0 \"/>\n</svg>\n";
+        let options = SvgEncodeOptions {
+            minify: true,
+            ..SvgEncodeOptions::mini()
+        };
+        assert_eq!(
+            svg_str_to_data_uri_with(svg, &options),
+            "data:image/svg+xml,%3Csvg%3E%3Cpath d='M0 0'/%3E%3C/svg%3E"
+        );
+
+        let options = SvgEncodeOptions {
+            utf8_charset: true,
+            ..options
+        };
+        let uri = svg_str_to_data_uri_with(svg, &options);
+        assert!(uri.starts_with("data:image/svg+xml;charset=utf-8,%3Csvg%3E"));
+        assert_eq!(uri, DataUri::parse(&uri).unwrap());
+    }
+
+    #[test]
+    fn svg_base64() {
+        let svg = "\n<svg>\n  <text>é</text>\n</svg>\n";
+        assert_eq!(
+            svg_str_to_data_uri_base64(svg, false),
+            "data:image/svg+xml;base64,PHN2Zz4gPHRleHQ+w6k8L3RleHQ+IDwvc3ZnPg=="
+        );
+        let uri = DataUri::parse(&svg_str_to_data_uri_base64(svg, true)).unwrap();
+        assert_eq!(uri.mime().get_param(mime::CHARSET).unwrap(), "utf-8");
+        assert_eq!(
+            uri.decode_payload().unwrap(),
+            "<svg> <text>é</text> </svg>".as_bytes()
+        );
+    }
+}
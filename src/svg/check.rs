@@ -0,0 +1,70 @@
+use crate::encoding::trim_byte_order_mark;
+use crate::{svg_str_to_data_uri_with, DataUri, SvgEncodeOptions, ValidationError};
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// Checks that `svg` is well-formed XML with an `<svg>` root element, the
+/// cases where browsers show a broken image instead of the drawing:
+/// unbalanced tags, unknown entities or a stray `&`, a root in another
+/// namespace.
+///
+/// ```
+/// assert!(data_uri_utils::check_svg("<svg><g/></svg>").is_ok());
+/// let error = data_uri_utils::check_svg("<svg><g></svg>").unwrap_err();
+/// assert!(error.to_string().starts_with("malformed SVG: "));
+/// ```
+pub fn check_svg(svg: &str) -> Result<(), ValidationError> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let document = roxmltree::Document::parse_with_options(trim_byte_order_mark(svg), options)
+        .map_err(ValidationError::MalformedSvg)?;
+    let root = document.root_element().tag_name();
+    match root.namespace() {
+        None | Some(SVG_NAMESPACE) if root.name() == "svg" => Ok(()),
+        _ => Err(ValidationError::NotSvg {
+            root: root.name().to_string(),
+        }),
+    }
+}
+
+/// [`svg_str_to_data_uri_with`] after [`check_svg`], for SVGs that didn't
+/// come from a trusted editor.
+pub fn try_svg_str_to_data_uri_with(
+    svg: impl AsRef<str>,
+    options: &SvgEncodeOptions,
+) -> Result<DataUri, ValidationError> {
+    let svg = svg.as_ref();
+    check_svg(svg)?;
+    Ok(svg_str_to_data_uri_with(svg, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn reject_malformed() {
+        let svg = "\u{FEFF}<!DOCTYPE svg [<!ENTITY c \"#fff\">]><svg xmlns=\"http://www.w3.org/2000/svg\" fill=\"&c;\"/>";
+        assert_eq!(check_svg(svg), Ok(()));
+        assert_eq!(
+            try_svg_str_to_data_uri_with("<svg/>", &SvgEncodeOptions::mini()).unwrap(),
+            "data:image/svg+xml,%3Csvg/%3E"
+        );
+
+        for malformed in ["<svg><g></svg>", "<svg><text>a & b</text></svg>", "<svg>"] {
+            assert!(matches!(
+                check_svg(malformed),
+                Err(ValidationError::MalformedSvg(_))
+            ));
+        }
+        assert_eq!(
+            check_svg("<html><svg/></html>"),
+            Err(ValidationError::NotSvg {
+                root: "html".to_string()
+            })
+        );
+        assert!(check_svg("<svg xmlns=\"http://www.w3.org/1999/xhtml\"/>").is_err());
+    }
+}
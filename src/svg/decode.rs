@@ -0,0 +1,136 @@
+use crate::markup::offset_in;
+use crate::{
+    strip_bom, svg_str_to_data_uri_with, Charset, DataUri, Error, Result, SvgEncodeOptions,
+};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// [`svg_bytes_to_data_uri_with`] with the default options.
+pub fn svg_bytes_to_data_uri(bytes: &[u8]) -> Result<DataUri> {
+    svg_bytes_to_data_uri_with(bytes, &SvgEncodeOptions::default())
+}
+
+/// Encodes an SVG file as read from disk.
+///
+/// The charset comes from the byte order mark, the UTF-16 layout of the
+/// leading `<`, or the `encoding` of the XML declaration, UTF-8 otherwise.
+/// Line endings are normalized to `\n` and the declaration is updated to
+/// UTF-8, which the payload is always written in.
+///
+/// Fails with [`Error::InvalidText`] on undecodable bytes unless
+/// [`SvgEncodeOptions::lossy_decoding`] is set, and with
+/// [`Error::UnsupportedCharset`] on unknown declared encodings.
+///
+/// ```
+/// let svg = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>\r\n<svg><text>\xe9</text></svg>";
+/// let uri = data_uri_utils::svg_bytes_to_data_uri(svg)?;
+/// assert_eq!(uri.decode_text()?, "<?xml version=\"1.0\" encoding=\"UTF-8\"?> <svg><text>é</text></svg>");
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub fn svg_bytes_to_data_uri_with(bytes: &[u8], options: &SvgEncodeOptions) -> Result<DataUri> {
+    let svg = decode_svg(bytes, options.lossy_decoding)?;
+    Ok(svg_str_to_data_uri_with(svg, options))
+}
+
+fn decode_svg(bytes: &[u8], lossy: bool) -> Result<String> {
+    let (bytes, charset) = match strip_bom(bytes) {
+        (bytes, Some(charset)) => (bytes, charset),
+        (bytes, None) => (bytes, sniff_charset(bytes)?),
+    };
+    let svg = if lossy {
+        charset.decode_lossy(bytes)
+    } else {
+        charset.decode(bytes)?
+    };
+    let mut svg = normalize_line_endings(svg).into_owned();
+    if let Some(range) = declared_encoding(&svg) {
+        if !svg[range.clone()].eq_ignore_ascii_case("utf-8") {
+            svg.replace_range(range, "UTF-8");
+        }
+    }
+    Ok(svg)
+}
+
+/// The charset of a file without byte order mark.
+fn sniff_charset(bytes: &[u8]) -> Result<Charset> {
+    match bytes {
+        [b'<', 0, ..] => return Ok(Charset::Utf16Le),
+        [0, b'<', ..] => return Ok(Charset::Utf16Be),
+        _ => {}
+    }
+    // The declaration is ASCII in every charset it can name.
+    let ascii = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap(),
+    };
+    match declared_encoding(ascii) {
+        Some(range) => {
+            let label = &ascii[range];
+            Charset::from_label(label).ok_or_else(|| Error::UnsupportedCharset(label.to_string()))
+        }
+        None => Ok(Charset::Utf8),
+    }
+}
+
+/// Byte range of the `encoding` value in the XML declaration.
+fn declared_encoding(svg: &str) -> Option<Range<usize>> {
+    let declaration = svg.strip_prefix("<?xml")?;
+    let declaration = &declaration[..declaration.find("?>")?];
+    let at = declaration.find("encoding")? + "encoding".len();
+    let value = declaration[at..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let len = value[1..].find(quote)?;
+    let start = offset_in(svg, value) + 1;
+    Some(start..start + len)
+}
+
+/// Turns `\r\n` and lone `\r` into `\n`, as XML parsers do.
+fn normalize_line_endings(svg: Cow<'_, str>) -> Cow<'_, str> {
+    if !svg.contains('\r') {
+        return svg;
+    }
+    Cow::Owned(svg.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn decode_files() {
+        let utf16: Vec<u8> = "\u{FEFF}<svg>\r\n<text>ü</text></svg>"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let options = SvgEncodeOptions::mini();
+        assert_eq!(
+            svg_bytes_to_data_uri_with(&utf16, &options).unwrap(),
+            "data:image/svg+xml,%3Csvg%3E %3Ctext%3E%C3%BC%3C/text%3E%3C/svg%3E"
+        );
+        assert_eq!(
+            svg_bytes_to_data_uri_with(&utf16[2..], &options).unwrap(),
+            "data:image/svg+xml,%3Csvg%3E %3Ctext%3E%C3%BC%3C/text%3E%3C/svg%3E"
+        );
+
+        let invalid = b"<svg><text>\xff\r\rb</text></svg>";
+        assert!(matches!(
+            svg_bytes_to_data_uri(invalid),
+            Err(Error::InvalidText { charset }) if charset == "utf-8"
+        ));
+        let options = SvgEncodeOptions {
+            lossy_decoding: true,
+            ..SvgEncodeOptions::mini()
+        };
+        assert_eq!(
+            svg_bytes_to_data_uri_with(invalid, &options).unwrap(),
+            "data:image/svg+xml,%3Csvg%3E%3Ctext%3E%EF%BF%BD%0A%0Ab%3C/text%3E%3C/svg%3E"
+        );
+        assert!(matches!(
+            svg_bytes_to_data_uri(b"<?xml version='1.0' encoding='x-unknown'?><svg/>"),
+            Err(Error::UnsupportedCharset(label)) if label == "x-unknown"
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::css::resolve_data_uri;
+use crate::markup::{offset_in, unescape, Token, Tokenizer};
+use crate::EncodeOptions;
+
+/// Makes an SVG document self-contained by replacing the `href` and
+/// `xlink:href` of its `<image>` elements with data URIs.
+///
+/// `resolver` receives each reference, a relative path or a remote URL, with
+/// character references decoded and returns its content, or `None` to leave it
+/// untouched. Existing data URIs and `#fragment` references are skipped.
+pub fn inline_svg_images(svg: &str, resolver: impl Fn(&str) -> Option<Vec<u8>>) -> String {
+    let mut inlined = String::with_capacity(svg.len());
+    let mut position = 0;
+    for token in Tokenizer::new(svg) {
+        let Token::StartTag(tag) = token else {
+            continue;
+        };
+        if local_name(tag.name) != "image" {
+            continue;
+        }
+        for attribute in &tag.attributes {
+            let Some(value) = attribute
+                .value
+                .filter(|_| local_name(attribute.name) == "href")
+            else {
+                continue;
+            };
+            let url = unescape(value);
+            let Some(uri) = resolve_data_uri(&url, &resolver, &EncodeOptions::default()) else {
+                continue;
+            };
+            let start = offset_in(svg, value);
+            inlined.push_str(&svg[position..start]);
+            inlined.push_str(&uri);
+            position = start + value.len();
+        }
+    }
+    inlined.push_str(&svg[position..]);
+    inlined
+}
+
+/// Name without its namespace prefix, so that `svg:image` matches `image`.
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn inline_image_hrefs() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+<image href="icons/a.png" width="8"/><image xlink:href='https://example.com/b.svg?x=1&amp;y=2'/>
+<svg:image href="missing.png"/><use href="#shape"/><a href="icons/a.png"/><image href="#local"/>
+</svg>"##;
+        let inlined = inline_svg_images(svg, |url| match url {
+            "icons/a.png" => Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+            "https://example.com/b.svg?x=1&y=2" => Some(b"<svg/>".to_vec()),
+            "missing.png" => None,
+            url => panic!("unexpected url {}", url),
+        });
+        assert!(inlined.contains(r#"<image href="data:image/png;base64,iVBORw0KGgo=" width="8"/>"#));
+        assert!(inlined.contains("<image xlink:href='data:image/svg+xml;base64,PHN2Zy8+'/>"));
+        assert!(inlined.contains(r##"<svg:image href="missing.png"/><use href="#shape"/><a href="icons/a.png"/><image href="#local"/>"##));
+    }
+}
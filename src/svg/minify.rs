@@ -0,0 +1,178 @@
+use crate::collapse_whitespace;
+use crate::markup::{offset_in, Tag, Token, Tokenizer};
+
+use super::protected::protected_ranges;
+
+/// Namespaces only meaningful to the editor that exported the file, or to the
+/// `<metadata>` block.
+const DROPPED_NAMESPACES: &[&str] = &[
+    "http://www.inkscape.org/namespaces/inkscape",
+    "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+    "http://www.bohemiancoding.com/sketch/ns",
+    "http://ns.adobe.com/AdobeIllustrator/10.0/",
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+    "http://creativecommons.org/ns#",
+    "http://purl.org/dc/elements/1.1/",
+];
+const DROPPED_PREFIXES: &[&str] = &["inkscape", "sodipodi", "sketch"];
+
+/// Strips comments, the XML prolog, doctypes without an internal subset,
+/// metadata and editor specific markup, and whitespace that doesn't affect
+/// rendering.
+pub(crate) fn minify(svg: &str) -> String {
+    let prefixes = dropped_prefixes(svg);
+    let is_dropped = |name: &str| is_dropped(name, &prefixes);
+    // Whitespace-only text is kept where `SvgWhitespace::Collapse` keeps it.
+    let protected = protected_ranges(svg);
+    let is_protected = |text: &str| {
+        let start = offset_in(svg, text);
+        let index = protected.partition_point(|range| range.end <= start);
+        protected
+            .get(index)
+            .is_some_and(|range| range.start <= start)
+    };
+
+    let mut minified = String::with_capacity(svg.len());
+    let mut skipped_depth = 0usize;
+    for token in Tokenizer::new(svg) {
+        if skipped_depth > 0 {
+            match token {
+                Token::StartTag(tag) if !tag.self_closing => skipped_depth += 1,
+                Token::EndTag { .. } => skipped_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match token {
+            Token::Comment(_) => {}
+            Token::Declaration(source) if !has_internal_subset(source) => {}
+            Token::ProcessingInstruction(source) if source.starts_with("<?xml ") => {}
+            Token::StartTag(tag) if tag.name == "metadata" || is_dropped(tag.name) => {
+                if !tag.self_closing {
+                    skipped_depth = 1;
+                }
+            }
+            Token::StartTag(tag) => write_tag(&tag, &is_dropped, &mut minified),
+            Token::Text(text) if text.trim().is_empty() && !is_protected(text) => {}
+            token => minified.push_str(token.source()),
+        }
+    }
+    minified
+}
+
+/// Whether a doctype declares entities, which the document may reference, in
+/// an internal subset `[...]`.
+fn has_internal_subset(declaration: &str) -> bool {
+    let mut quote = None;
+    for c in declaration.chars() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '[' => return true,
+            None => {}
+        }
+    }
+    false
+}
+
+fn dropped_prefixes(svg: &str) -> Vec<&str> {
+    let mut prefixes = DROPPED_PREFIXES.to_vec();
+    for token in Tokenizer::new(svg) {
+        if let Token::StartTag(tag) = token {
+            for attribute in tag.attributes {
+                if let (Some(prefix), Some(namespace)) =
+                    (attribute.name.strip_prefix("xmlns:"), attribute.value)
+                {
+                    if DROPPED_NAMESPACES.contains(&namespace) {
+                        prefixes.push(prefix);
+                    }
+                }
+            }
+        }
+    }
+    prefixes
+}
+
+/// Whether an element or attribute name belongs to, or declares, a dropped namespace.
+fn is_dropped(name: &str, prefixes: &[&str]) -> bool {
+    name.split_once(':').is_some_and(|(prefix, local)| {
+        prefixes.contains(&prefix) || (prefix == "xmlns" && prefixes.contains(&local))
+    })
+}
+
+fn write_tag(tag: &Tag<'_>, is_dropped: &impl Fn(&str) -> bool, out: &mut String) {
+    let values: Vec<_> = tag
+        .attributes
+        .iter()
+        .filter(|attribute| !is_dropped(attribute.name))
+        .map(|attribute| {
+            let value = attribute.value.unwrap_or_default();
+            (attribute.name, collapse_whitespace(value.trim()))
+        })
+        .collect();
+    tag.write_with(
+        values.iter().map(|(name, value)| (*name, value.as_ref())),
+        out,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minify_inkscape_export() {
+        let svg = r##"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<!-- Created with Inkscape (http://www.inkscape.org/) -->
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<svg
+   xmlns:dc="http://purl.org/dc/elements/1.1/"
+   xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+   xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape"
+   xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd"
+   xmlns="http://www.w3.org/2000/svg"
+   viewBox="0 0 10 10"
+   inkscape:version="1.0">
+  <sodipodi:namedview pagecolor="#ffffff" inkscape:zoom="1"/>
+  <metadata id="metadata5">
+    <rdf:RDF><dc:format>image/svg+xml</dc:format></rdf:RDF>
+  </metadata>
+  <g inkscape:label="Layer 1" inkscape:groupmode="layer">
+    <path d="  M 0,0
+             L 10,10  " sodipodi:nodetypes="cc"/>
+    <text> <tspan>a</tspan> <tspan>b</tspan> </text>
+  </g>
+</svg>
+"##;
+        assert_eq!(
+            minify(svg),
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10"><g><path d="M 0,0 L 10,10"/><text> <tspan>a</tspan> <tspan>b</tspan> </text></g></svg>"#
+        );
+    }
+
+    #[test]
+    fn keep_protected_whitespace() {
+        for svg in [
+            "<svg:text><svg:tspan>a</svg:tspan> <svg:tspan>b</svg:tspan></svg:text>",
+            "<pre><b>a</b>   <b>b</b></pre>",
+            "<g xml:space=\"preserve\"><tspan>a</tspan> <tspan>b</tspan></g>",
+        ] {
+            assert_eq!(minify(svg), svg);
+        }
+        assert_eq!(
+            minify("<text xml:space=\"default\"><tspan>a</tspan> <tspan>b</tspan></text>"),
+            "<text xml:space=\"default\"><tspan>a</tspan><tspan>b</tspan></text>"
+        );
+    }
+
+    #[test]
+    fn keep_internal_subset() {
+        let svg = r#"<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd" [
+  <!ENTITY ns_svg "http://www.w3.org/2000/svg">
+]>
+<svg xmlns="&ns_svg;"/>"#;
+        assert_eq!(minify(svg), svg.replace("\n<svg", "<svg"));
+        assert_eq!(minify("<!DOCTYPE svg><svg/>"), "<svg/>");
+    }
+}
@@ -0,0 +1,96 @@
+//! The regions of an SVG whose whitespace is significant. Like
+//! `crate::escape`, it is compiled into `data-uri-utils-macros` too, so it
+//! only depends on `crate::markup`.
+
+use crate::markup::{offset_in, Token, Tokenizer};
+use std::ops::Range;
+
+/// Elements whose text keeps its whitespace, local names compared without prefix.
+const PROTECTED_ELEMENTS: &[&str] = &[
+    "text", "tspan", "textPath", "style", "script", "pre", "textarea",
+];
+
+/// Calls `segment` with consecutive slices of `svg` and whether their
+/// whitespace may be collapsed, anywhere unless `protect` is set.
+pub(crate) fn for_each_region<E>(
+    svg: &str,
+    protect: bool,
+    mut segment: impl FnMut(&str, bool) -> Result<(), E>,
+) -> Result<(), E> {
+    let protected = if protect {
+        protected_ranges(svg)
+    } else {
+        Vec::new()
+    };
+    let mut position = 0;
+    for range in protected {
+        if position < range.start {
+            segment(&svg[position..range.start], true)?;
+        }
+        segment(&svg[range.clone()], false)?;
+        position = range.end;
+    }
+    if position < svg.len() {
+        segment(&svg[position..], true)?;
+    }
+    Ok(())
+}
+
+/// Sorted, non-overlapping byte ranges whose whitespace is significant.
+pub(crate) fn protected_ranges(svg: &str) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    if !may_protect(svg) {
+        return ranges;
+    }
+    // Open elements, and whether their content is protected.
+    let mut open: Vec<(&str, bool)> = Vec::new();
+    for token in Tokenizer::new(svg) {
+        let protected = match &token {
+            Token::Text(_) => open.last().is_some_and(|(_, preserve)| *preserve),
+            Token::Cdata(_) => true,
+            Token::StartTag(tag) if !tag.self_closing => {
+                let inherited = open.last().is_some_and(|(_, preserve)| *preserve);
+                let preserve = match tag.attribute("xml:space") {
+                    Some("preserve") => true,
+                    Some("default") => false,
+                    _ => inherited || PROTECTED_ELEMENTS.contains(&local_name(tag.name)),
+                };
+                open.push((tag.name, preserve));
+                false
+            }
+            Token::EndTag { name, .. } => {
+                if let Some(index) = open.iter().rposition(|(open, _)| open == name) {
+                    open.truncate(index);
+                }
+                false
+            }
+            _ => false,
+        };
+        if protected {
+            let source = token.source();
+            let start = offset_in(svg, source);
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end += source.len(),
+                _ => ranges.push(start..start + source.len()),
+            }
+        }
+    }
+    ranges
+}
+
+/// Cheap check that `svg` may contain a protected region, which spares
+/// tokenizing most icons.
+fn may_protect(svg: &str) -> bool {
+    svg.contains("xml:space")
+        || svg.match_indices('<').any(|(index, _)| {
+            let rest = &svg[index + 1..];
+            let name = rest
+                .find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+                .map_or(rest, |end| &rest[..end]);
+            rest.starts_with("![CDATA[") || PROTECTED_ELEMENTS.contains(&local_name(name))
+        })
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
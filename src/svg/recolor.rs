@@ -0,0 +1,106 @@
+use crate::markup::{Token, Tokenizer};
+use std::borrow::Cow;
+
+/// Replaces the `fill` and `stroke` colors of every element, in attributes
+/// and `style` declarations, with `color`, e.g. `currentColor`.
+///
+/// `none` and `url()` paint references such as gradients are kept. The root
+/// element gets a `fill` when it has none, so shapes relying on the default
+/// black are recolored too. Rules in `<style>` elements are left untouched.
+///
+/// ```
+/// let svg = r##"<svg><path fill="#000" stroke="none"/><g style="stroke: red"/></svg>"##;
+/// assert_eq!(
+///     data_uri_utils::recolor_svg(svg, "currentColor"),
+///     r#"<svg fill="currentColor"><path fill="currentColor" stroke="none"/><g style="stroke:currentColor"/></svg>"#
+/// );
+/// ```
+pub fn recolor_svg(svg: &str, color: &str) -> String {
+    let mut recolored = String::with_capacity(svg.len());
+    let mut is_root = true;
+    for token in Tokenizer::new(svg) {
+        let Token::StartTag(tag) = token else {
+            recolored.push_str(token.source());
+            continue;
+        };
+        let mut attributes: Vec<(&str, Cow<'_, str>)> = tag
+            .attributes
+            .iter()
+            .map(|attribute| {
+                let value = attribute.value.unwrap_or_default();
+                let value = match attribute.name {
+                    "fill" | "stroke" => paint(value, color),
+                    "style" => recolor_style(value, color),
+                    _ => Cow::Borrowed(value),
+                };
+                (attribute.name, value)
+            })
+            .collect();
+        if is_root && tag.attribute("fill").is_none() {
+            attributes.push(("fill", Cow::Borrowed(color)));
+        }
+        is_root = false;
+        tag.write_with(
+            attributes
+                .iter()
+                .map(|(name, value)| (*name, value.as_ref())),
+            &mut recolored,
+        );
+    }
+    recolored
+}
+
+fn paint<'a>(value: &'a str, color: &'a str) -> Cow<'a, str> {
+    let trimmed = value.trim();
+    let is_kept = trimmed.eq_ignore_ascii_case("none")
+        || trimmed
+            .get(..4)
+            .is_some_and(|function| function.eq_ignore_ascii_case("url("));
+    Cow::Borrowed(if is_kept { value } else { color })
+}
+
+fn recolor_style<'a>(style: &'a str, color: &str) -> Cow<'a, str> {
+    let mut changed = false;
+    let declarations: Vec<Cow<'_, str>> = style
+        .split(';')
+        .map(|declaration| match declaration.split_once(':') {
+            Some((property, value)) if matches!(property.trim(), "fill" | "stroke") => {
+                match paint(value, color) {
+                    Cow::Borrowed(paint) if paint == value => Cow::Borrowed(declaration),
+                    paint => {
+                        changed = true;
+                        Cow::Owned(format!("{}:{}", property.trim(), paint))
+                    }
+                }
+            }
+            _ => Cow::Borrowed(declaration),
+        })
+        .collect();
+    if changed {
+        Cow::Owned(declarations.join(";"))
+    } else {
+        Cow::Borrowed(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn recolor_icon() {
+        let svg = r##"<?xml version="1.0"?>
+<svg fill="none" viewBox="0 0 8 8"><path stroke="#123" style="fill:url(#g); stroke:blue"/><circle/></svg>"##;
+        assert_eq!(
+            recolor_svg(svg, "#f00"),
+            r##"<?xml version="1.0"?>
+<svg fill="none" viewBox="0 0 8 8"><path stroke="#f00" style="fill:url(#g);stroke:#f00"/><circle/></svg>"##
+        );
+
+        let options = SvgEncodeOptions::mini().recolor("currentColor");
+        assert_eq!(
+            svg_str_to_data_uri_with("<svg><path fill='red'/></svg>", &options),
+            "data:image/svg+xml,%3Csvg fill='currentColor'%3E%3Cpath fill='currentColor'/%3E%3C/svg%3E"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::markup::{unescape, Attribute, Token, Tokenizer};
+
+/// Elements that can run scripts or embed arbitrary HTML.
+const DROPPED_ELEMENTS: &[&str] = &["script", "foreignObject"];
+
+/// Removes what could run scripts or fetch external content when the SVG is
+/// opened as a document: `<script>` and `<foreignObject>` elements, `on*`
+/// event handler attributes, `javascript:` and `vbscript:` URLs, doctypes
+/// with their entity declarations, and processing instructions such as
+/// `<?xml-stylesheet?>`.
+///
+/// ```
+/// let svg = r#"<svg onload="alert(1)"><a href=" JavaScript:alert(2)"><script>alert(3)</script><path/></a></svg>"#;
+/// assert_eq!(data_uri_utils::sanitize_svg(svg), r#"<svg><a><path/></a></svg>"#);
+/// ```
+pub fn sanitize_svg(svg: &str) -> String {
+    let mut sanitized = String::with_capacity(svg.len());
+    let mut skipped_depth = 0usize;
+    for token in Tokenizer::new(svg) {
+        if skipped_depth > 0 {
+            match token {
+                Token::StartTag(tag) if !tag.self_closing => skipped_depth += 1,
+                Token::EndTag { .. } => skipped_depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match token {
+            Token::Declaration(_) => {}
+            Token::ProcessingInstruction(source) if !source.starts_with("<?xml ") => {}
+            Token::StartTag(tag) if is_dropped_element(tag.name) => {
+                if !tag.self_closing {
+                    skipped_depth = 1;
+                }
+            }
+            Token::StartTag(tag) if tag.attributes.iter().any(is_unsafe) => {
+                let attributes = tag
+                    .attributes
+                    .iter()
+                    .filter(|attribute| !is_unsafe(attribute))
+                    .map(|attribute| (attribute.name, attribute.value.unwrap_or_default()));
+                tag.write_with(attributes, &mut sanitized);
+            }
+            Token::EndTag { name, .. } if is_dropped_element(name) => {}
+            // The rest of a tag left open, which parsers could read otherwise.
+            Token::Text(text) => sanitized.push_str(&text.replace('<', "&lt;")),
+            token => sanitized.push_str(token.source()),
+        }
+    }
+    sanitized
+}
+
+/// Whether `svg` can run scripts when opened as a document: it has
+/// `<script>` or `<foreignObject>` elements, event handlers, script URLs, an
+/// `<?xml-stylesheet?>`, which may be XSLT, or a doctype declaring external
+/// entities or entities holding markup.
+///
+/// Unlike comparing with [`sanitize_svg`], doctypes, the XML declaration and
+/// other processing instructions are otherwise accepted.
+pub(crate) fn has_active_content(svg: &str) -> bool {
+    Tokenizer::new(svg).any(|token| match token {
+        Token::Declaration(source) => declares_active_entities(source),
+        Token::ProcessingInstruction(source) => source.starts_with("<?xml-stylesheet"),
+        Token::StartTag(tag) => {
+            is_dropped_element(tag.name) || tag.attributes.iter().any(is_unsafe)
+        }
+        Token::EndTag { name, .. } => is_dropped_element(name),
+        _ => false,
+    })
+}
+
+/// Entities of the internal subset that are external, which parsers may
+/// fetch, or whose value has markup or character references, which
+/// referencing them would insert.
+fn declares_active_entities(declaration: &str) -> bool {
+    let mut subset = false;
+    let mut quote = None;
+    let mut literal_start = 0;
+    let mut unquoted = String::new();
+    for (index, c) in declaration.char_indices() {
+        match quote {
+            Some(open) if c == open => {
+                if subset && declaration[literal_start..index].contains(['<', '&']) {
+                    return true;
+                }
+                quote = None;
+            }
+            Some(_) => {}
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                literal_start = index + 1;
+            }
+            None if c == '[' => subset = true,
+            None if subset => unquoted.push(c),
+            None => {}
+        }
+    }
+    unquoted.contains("SYSTEM") || unquoted.contains("PUBLIC")
+}
+
+fn is_dropped_element(name: &str) -> bool {
+    let local = name.rsplit_once(':').map_or(name, |(_, local)| local);
+    DROPPED_ELEMENTS
+        .iter()
+        .any(|dropped| local.eq_ignore_ascii_case(dropped))
+}
+
+/// Event handlers, and attributes holding or animating to a script URL.
+fn is_unsafe(attribute: &Attribute<'_>) -> bool {
+    let local = attribute
+        .name
+        .rsplit_once(':')
+        .map_or(attribute.name, |(_, local)| local);
+    if local.len() > 2 && local.as_bytes()[..2].eq_ignore_ascii_case(b"on") {
+        return true;
+    }
+    let Some(value) = attribute.value else {
+        return false;
+    };
+    // `values` lists the frames of an animation.
+    unescape(value).split(';').any(|url| {
+        let url: String = url
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .take(11)
+            .collect::<String>()
+            .to_ascii_lowercase();
+        url.starts_with("javascript:") || url.starts_with("vbscript:")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn strip_active_content() {
+        let svg = r##"<?xml version="1.0"?>
+<!DOCTYPE svg [<!ENTITY xxe SYSTEM "file:///etc/passwd">]>
+<?xml-stylesheet href="https://example.com/a.css"?>
+<svg xmlns="http://www.w3.org/2000/svg" ONCLICK='x()'>
+<SCRIPT type="text/javascript"><![CDATA[ alert(1) ]]></SCRIPT><svg:script href="a.js"/>
+<foreignObject><body><iframe src="https://example.com"/></body></foreignObject>
+<a xlink:href="jav&#x09;ascript:alert(1)" href="https://example.com"><text>&amp;</text></a>
+<set attributeName="href" values="#a;javascript:alert(1)"/><use href="#shape"/>
+</svg>"##;
+        assert_eq!(
+            sanitize_svg(svg),
+            r##"<?xml version="1.0"?>
+
+
+<svg xmlns="http://www.w3.org/2000/svg">
+
+
+<a href="https://example.com"><text>&amp;</text></a>
+<set attributeName="href"/><use href="#shape"/>
+</svg>"##
+        );
+
+        let options = SvgEncodeOptions {
+            sanitize: true,
+            ..SvgEncodeOptions::mini()
+        };
+        assert_eq!(
+            svg_str_to_data_uri_with("<svg onload='x()'><script>x()</script></svg>", &options),
+            "data:image/svg+xml,%3Csvg%3E%3C/svg%3E"
+        );
+    }
+
+    #[test]
+    fn unterminated_tags() {
+        assert_eq!(
+            sanitize_svg("<svg>1 < 2<a b=\"<script>x()</script>"),
+            "<svg>1 &lt; 2&lt;a b=\"&lt;script>x()&lt;/script>"
+        );
+        // Linear on hostile input, which took seconds when every `<` was
+        // reparsed to the end.
+        let svg = "<a b=\"".repeat(200_000);
+        assert_eq!(sanitize_svg(&svg).len(), svg.len() + 3 * 200_000);
+        let svg = "<".repeat(1_000_000);
+        assert_eq!(sanitize_svg(&svg).len(), 4 * svg.len());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::markup::{offset_in, Tag, Token, Tokenizer};
+use std::borrow::Cow;
+
+/// The sizing attributes of the root `<svg>` element.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SvgSize {
+    /// `min-x`, `min-y`, `width` and `height` of the `viewBox`.
+    pub view_box: Option<[f64; 4]>,
+    /// The `width` attribute as written, units included.
+    pub width: Option<String>,
+    pub height: Option<String>,
+}
+
+/// Reads the `viewBox`, `width` and `height` of the root element, `None`
+/// when there is no root element.
+///
+/// ```
+/// let size = data_uri_utils::svg_size(r#"<svg width="2em" viewBox="0 0 24 24"/>"#).unwrap();
+/// assert_eq!(size.width.as_deref(), Some("2em"));
+/// assert_eq!(size.height, None);
+/// assert_eq!(size.view_box, Some([0.0, 0.0, 24.0, 24.0]));
+/// ```
+pub fn svg_size(svg: &str) -> Option<SvgSize> {
+    let root = root_tag(svg)?;
+    Some(SvgSize {
+        view_box: root.attribute("viewBox").and_then(parse_view_box),
+        width: root.attribute("width").map(str::to_string),
+        height: root.attribute("height").map(str::to_string),
+    })
+}
+
+/// Sets the `width` and `height` of the root element, replacing any
+/// existing ones.
+///
+/// When the SVG has no `viewBox`, one is added from the original pixel size
+/// so the drawing scales to the new size instead of being cropped.
+///
+/// ```
+/// let svg = data_uri_utils::set_svg_size(r#"<svg width="16" height="16"><g/></svg>"#, "24", "24");
+/// assert_eq!(svg, r#"<svg viewBox="0 0 16 16" width="24" height="24"><g/></svg>"#);
+/// ```
+pub fn set_svg_size<'a>(svg: &'a str, width: &str, height: &str) -> Cow<'a, str> {
+    let Some(root) = root_tag(svg) else {
+        return Cow::Borrowed(svg);
+    };
+    let view_box = match (
+        root.attribute("viewBox"),
+        length(&root, "width"),
+        length(&root, "height"),
+    ) {
+        (None, Some(width), Some(height)) => Some(format!("0 0 {} {}", width, height)),
+        _ => None,
+    };
+    let attributes = root
+        .attributes
+        .iter()
+        .filter(|attribute| !matches!(attribute.name, "width" | "height"))
+        .map(|attribute| (attribute.name, attribute.value.unwrap_or_default()))
+        .chain(view_box.as_deref().map(|view_box| ("viewBox", view_box)))
+        .chain([("width", width), ("height", height)]);
+
+    let start = offset_in(svg, root.source);
+    let mut resized = String::with_capacity(svg.len() + 32);
+    resized.push_str(&svg[..start]);
+    root.write_with(attributes, &mut resized);
+    resized.push_str(&svg[start + root.source.len()..]);
+    Cow::Owned(resized)
+}
+
+/// Intrinsic size of the SVG, from the root `width` and `height` or the `viewBox`.
+///
+/// Lengths in relative units such as `%` or `em` are ignored.
+pub(crate) fn svg_dimensions(svg: &str) -> Option<(f64, f64)> {
+    let root = root_tag(svg)?;
+    let view_box = root
+        .attribute("viewBox")
+        .and_then(parse_view_box)
+        .map(|[_, _, width, height]| (width, height));
+    match (length(&root, "width"), length(&root, "height"), view_box) {
+        (Some(width), Some(height), _) => Some((width, height)),
+        (Some(width), None, Some((w, h))) => Some((width, width * h / w)),
+        (None, Some(height), Some((w, h))) => Some((height * w / h, height)),
+        (_, _, view_box) => view_box,
+    }
+}
+
+fn root_tag(svg: &str) -> Option<Tag<'_>> {
+    Tokenizer::new(svg).find_map(|token| match token {
+        Token::StartTag(tag) => Some(tag),
+        _ => None,
+    })
+}
+
+/// A positive `viewBox`, whose values may be separated by commas.
+fn parse_view_box(view_box: &str) -> Option<[f64; 4]> {
+    let values: Vec<f64> = view_box
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    match values[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => Some([x, y, width, height]),
+        _ => None,
+    }
+}
+
+/// A length attribute of the root in pixels.
+fn length(root: &Tag<'_>, name: &str) -> Option<f64> {
+    let length = root.attribute(name)?.trim();
+    let length = length.strip_suffix("px").unwrap_or(length);
+    length
+        .parse()
+        .ok()
+        .filter(|length: &f64| length.is_finite() && *length > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::svg_dimensions;
+    use crate::*;
+
+    #[test]
+    fn dimensions() {
+        assert_eq!(
+            svg_dimensions(r#"<svg width="20px" height="10" viewBox="0 0 2 1"/>"#),
+            Some((20.0, 10.0))
+        );
+        assert_eq!(
+            svg_dimensions(r#"<?xml version="1.0"?><svg viewBox="0,0 , 40 30"/>"#),
+            Some((40.0, 30.0))
+        );
+        assert_eq!(
+            svg_dimensions(r#"<svg width="80" viewBox="0 0 40 30"/>"#),
+            Some((80.0, 60.0))
+        );
+        assert_eq!(svg_dimensions(r#"<svg width="100%" height="100%"/>"#), None);
+    }
+
+    #[test]
+    fn override_size() {
+        let svg = "<?xml version=\"1.0\"?>\n<svg viewBox='0 0 8 8' width=\"1em\">\n<g/></svg>";
+        assert_eq!(
+            set_svg_size(svg, "24", "24"),
+            "<?xml version=\"1.0\"?>\n<svg viewBox=\"0 0 8 8\" width=\"24\" height=\"24\">\n<g/></svg>"
+        );
+        assert_eq!(set_svg_size("no markup", "1", "1"), "no markup");
+        assert_eq!(svg_size("text"), None);
+
+        let options = SvgEncodeOptions {
+            size: Some(("24".to_string(), "24".to_string())),
+            ..SvgEncodeOptions::mini()
+        };
+        assert_eq!(
+            svg_str_to_data_uri_with("<svg width='12' height='6'/>", &options),
+            "data:image/svg+xml,%3Csvg viewBox='0 0 12 6' width='24' height='24'/%3E"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use super::protected::for_each_region;
+use crate::collapse_whitespace;
+
+/// Where [`svg_str_to_data_uri_with`](crate::svg_str_to_data_uri_with)
+/// collapses runs of whitespace to a single space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SvgWhitespace {
+    /// Everywhere but in the content of text elements, `<style>`, `<script>`,
+    /// `<pre>` and elements with `xml:space="preserve"`, and in CDATA sections.
+    #[default]
+    Collapse,
+    /// Everywhere, which only keeps the rendering intact for SVGs without
+    /// text or embedded stylesheets.
+    Aggressive,
+}
+
+/// Calls `segment` with consecutive slices of `svg` and whether their
+/// whitespace may be collapsed.
+pub(crate) fn for_each_segment<E>(
+    svg: &str,
+    whitespace: SvgWhitespace,
+    segment: impl FnMut(&str, bool) -> Result<(), E>,
+) -> Result<(), E> {
+    for_each_region(svg, whitespace == SvgWhitespace::Collapse, segment)
+}
+
+/// [`collapse_whitespace`] outside of the regions [`SvgWhitespace`] protects.
+pub(crate) fn collapse_svg_whitespace(svg: &str, whitespace: SvgWhitespace) -> String {
+    let mut collapsed = String::with_capacity(svg.len());
+    for_each_segment::<()>(svg, whitespace, |segment, collapse| {
+        if collapse {
+            collapsed.push_str(&collapse_whitespace(segment));
+        } else {
+            collapsed.push_str(segment);
+        }
+        Ok(())
+    })
+    .unwrap();
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collapse_svg_whitespace;
+    use crate::*;
+
+    #[test]
+    fn keep_significant_whitespace() {
+        let svg = "<svg>\n  <text x=\"1\">a  b\n c</text>\n  <g xml:space=\"preserve\"><tspan> d  e </tspan>\t<path/>  </g>\n  <style><![CDATA[\n  a  { }\n]]></style>\n  <svg:text> f  </svg:text><g>  <pre/>  </g>\n</svg>";
+        assert_eq!(
+            collapse_svg_whitespace(svg, SvgWhitespace::Collapse),
+            "<svg> <text x=\"1\">a  b\n c</text> <g xml:space=\"preserve\"><tspan> d  e </tspan>\t<path/>  </g> <style><![CDATA[\n  a  { }\n]]></style> <svg:text> f  </svg:text><g> <pre/> </g> </svg>"
+        );
+        assert_eq!(
+            collapse_svg_whitespace(svg, SvgWhitespace::Aggressive),
+            collapse_whitespace(svg)
+        );
+
+        let svg = "<svg><text>a  b</text>\n</svg>";
+        assert_eq!(
+            svg_str_to_data_uri_with(svg, &SvgEncodeOptions::mini()),
+            "data:image/svg+xml,%3Csvg%3E%3Ctext%3Ea  b%3C/text%3E %3C/svg%3E"
+        );
+        let options = SvgEncodeOptions {
+            whitespace: SvgWhitespace::Aggressive,
+            ..SvgEncodeOptions::mini()
+        };
+        assert_eq!(
+            svg_str_to_data_uri_with(svg, &options),
+            "data:image/svg+xml,%3Csvg%3E%3Ctext%3Ea b%3C/text%3E %3C/svg%3E"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Adapters for template engines, so a server-rendered page can inline an
+//! asset with `{{ logo | data_uri }}`.
+
+use crate::{file_to_data_uri, DataUri, EncodeOptions, Result, ToDataUri};
+use std::path::{Path, PathBuf};
+
+/// Values the template filters turn into data URIs: strings and paths are
+/// files to read, as [`file_to_data_uri`] does, and bytes are encoded with
+/// their media type sniffed.
+pub trait TemplateAsset {
+    fn to_template_data_uri(&self) -> Result<DataUri>;
+}
+
+impl<T: TemplateAsset + ?Sized> TemplateAsset for &T {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        (**self).to_template_data_uri()
+    }
+}
+
+impl TemplateAsset for str {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        file_to_data_uri(self)
+    }
+}
+
+impl TemplateAsset for String {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        file_to_data_uri(self)
+    }
+}
+
+impl TemplateAsset for Path {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        file_to_data_uri(self)
+    }
+}
+
+impl TemplateAsset for PathBuf {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        file_to_data_uri(self)
+    }
+}
+
+impl TemplateAsset for [u8] {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        self.to_data_uri(&EncodeOptions::default())
+    }
+}
+
+impl TemplateAsset for Vec<u8> {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        self.as_slice().to_template_data_uri()
+    }
+}
+
+impl TemplateAsset for DataUri {
+    fn to_template_data_uri(&self) -> Result<DataUri> {
+        Ok(self.clone())
+    }
+}
+
+/// Tera filter taking a path, or an array of bytes.
+///
+/// ```no_run
+/// let mut tera = tera::Tera::default();
+/// tera.register_filter("data_uri", data_uri_utils::tera_data_uri);
+/// tera.add_raw_template("page", r#"<img src="{{ "logo.png" | data_uri }}">"#)?;
+/// # Ok::<_, tera::Error>(())
+/// ```
+#[cfg(feature = "tera")]
+pub fn tera_data_uri(
+    value: &tera::Value,
+    _args: &std::collections::HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let uri = match value {
+        tera::Value::String(path) => path.to_template_data_uri(),
+        tera::Value::Array(values) => {
+            let bytes = values
+                .iter()
+                .map(|value| value.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(|| tera::Error::msg("data_uri takes an array of bytes"))?;
+            bytes.to_template_data_uri()
+        }
+        _ => {
+            return Err(tera::Error::msg(
+                "data_uri takes a path or an array of bytes",
+            ))
+        }
+    };
+    uri.map(|uri| tera::Value::String(uri.into()))
+        .map_err(|error| tera::Error::msg(format!("data_uri: {}", error)))
+}
+
+/// Askama filters, in scope of a template once imported as `filters`.
+///
+/// ```
+/// use data_uri_utils::askama_filters as filters;
+///
+/// #[derive(askama::Template)]
+/// #[template(source = r#"<img src="{{ logo|data_uri }}">"#, ext = "html")]
+/// struct Page<'a> {
+///     logo: &'a [u8],
+/// }
+///
+/// let page = Page { logo: b"GIF89a" };
+/// assert_eq!(askama::Template::render(&page)?, r#"<img src="data:image/gif;base64,R0lGODlh">"#);
+/// # Ok::<_, askama::Error>(())
+/// ```
+#[cfg(feature = "askama")]
+pub mod askama_filters {
+    use super::TemplateAsset;
+
+    pub fn data_uri(value: impl TemplateAsset) -> askama::Result<String> {
+        value
+            .to_template_data_uri()
+            .map(String::from)
+            .map_err(|error| askama::Error::Custom(Box::new(error)))
+    }
+}
+
+/// Renders the URI HTML-escaped, for `img src=(uri)`.
+#[cfg(feature = "maud")]
+impl maud::Render for DataUri {
+    fn render_to(&self, buffer: &mut String) {
+        use std::fmt::Write;
+
+        let _ = maud::Escaper::new(buffer).write_str(self.as_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TemplateAsset;
+    use crate::*;
+
+    fn logo_path() -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("data-uri-templates-{}.gif", std::process::id()));
+        std::fs::write(&path, b"GIF89a").unwrap();
+        path
+    }
+
+    #[test]
+    fn template_assets() {
+        let path = logo_path();
+        let expected = "data:image/gif;base64,R0lGODlh";
+        assert_eq!(path.to_template_data_uri().unwrap(), expected);
+        assert_eq!(
+            path.to_str().unwrap().to_template_data_uri().unwrap(),
+            expected
+        );
+        assert_eq!(b"GIF89a".to_vec().to_template_data_uri().unwrap(), expected);
+        assert!("missing.gif".to_template_data_uri().is_err());
+    }
+
+    #[cfg(feature = "tera")]
+    #[test]
+    fn tera_filter() {
+        let mut tera = tera::Tera::default();
+        tera.register_filter("data_uri", tera_data_uri);
+        tera.add_raw_template(
+            "page",
+            "<img src=\"{{ logo | data_uri }}\">{{ gif | data_uri }}",
+        )
+        .unwrap();
+        let mut context = tera::Context::new();
+        context.insert("logo", &logo_path());
+        context.insert("gif", b"GIF89a");
+        assert_eq!(
+            tera.render("page", &context).unwrap(),
+            "<img src=\"data:image/gif;base64,R0lGODlh\">data:image/gif;base64,R0lGODlh"
+        );
+
+        context.insert("gif", &[256]);
+        assert!(tera.render("page", &context).is_err());
+    }
+
+    #[cfg(feature = "askama")]
+    #[test]
+    fn askama_filter() {
+        use crate::askama_filters as filters;
+
+        #[derive(askama::Template)]
+        #[template(source = "<img src=\"{{ logo|data_uri }}\">", ext = "html")]
+        struct Page {
+            logo: std::path::PathBuf,
+        }
+
+        let page = Page { logo: logo_path() };
+        assert_eq!(
+            askama::Template::render(&page).unwrap(),
+            "<img src=\"data:image/gif;base64,R0lGODlh\">"
+        );
+        let page = Page {
+            logo: "missing.gif".into(),
+        };
+        assert!(askama::Template::render(&page).is_err());
+    }
+
+    #[cfg(feature = "maud")]
+    #[test]
+    fn maud_render() {
+        let svg = svg_str_to_data_uri_with("<svg a=\"&\"/>", &SvgEncodeOptions::mini());
+        let markup = maud::html! { img src=(svg); };
+        assert_eq!(
+            markup.into_string(),
+            "<img src=\"data:image/svg+xml,%3Csvg a='&amp;'/%3E\">"
+        );
+    }
+}
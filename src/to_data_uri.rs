@@ -0,0 +1,151 @@
+use crate::{
+    bytes_to_data_uri_with, sniff_mime, wrap_data_uri, DataUri, EncodeOptions, Error, Result,
+    SvgDataUri,
+};
+
+/// Types that know how to encode themselves as a data URI.
+///
+/// Implement it for your own types, such as charts or QR codes, so generic
+/// code can take `impl ToDataUri`:
+///
+/// ```
+/// use data_uri_utils::{DataUri, EncodeOptions, Result, ToDataUri};
+///
+/// struct Csv(Vec<[u32; 2]>);
+///
+/// impl ToDataUri for Csv {
+///     fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+///         let csv: String = self.0.iter().map(|[x, y]| format!("{},{}\n", x, y)).collect();
+///         let mime = "text/csv".parse().unwrap();
+///         data_uri_utils::bytes_to_data_uri_with(csv.as_bytes(), &mime, options)
+///     }
+/// }
+///
+/// fn link(target: &impl ToDataUri) -> Result<String> {
+///     Ok(format!("<a href=\"{}\">", target.to_data_uri(&EncodeOptions::default())?))
+/// }
+///
+/// assert_eq!(link(&Csv(vec![[1, 2]]))?, "<a href=\"data:text/csv;base64,MSwyCg==\">");
+/// # Ok::<_, data_uri_utils::Error>(())
+/// ```
+pub trait ToDataUri {
+    /// Fails with [`Error::TooLarge`] when the data URI would exceed
+    /// [`EncodeOptions::max_len`].
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri>;
+}
+
+impl<T: ToDataUri + ?Sized> ToDataUri for &T {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        (**self).to_data_uri(options)
+    }
+}
+
+/// Base64 with the media type guessed by [`sniff_mime`], falling back to
+/// `application/octet-stream`.
+impl ToDataUri for [u8] {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        let mime = sniff_mime(self).unwrap_or(mime::APPLICATION_OCTET_STREAM);
+        bytes_to_data_uri_with(self, &mime, options)
+    }
+}
+
+impl ToDataUri for Vec<u8> {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        self.as_slice().to_data_uri(options)
+    }
+}
+
+/// Percent-encoded with the wrapper's own [`SvgEncodeOptions`](crate::SvgEncodeOptions),
+/// only the limit and line width of `options` apply.
+impl ToDataUri for SvgDataUri<'_> {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        check_len(self.to_uri(), options)
+    }
+}
+
+impl ToDataUri for DataUri {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        check_len(self.clone(), options)
+    }
+}
+
+/// Encoded as a PNG with the default [`PngOptions`](crate::PngOptions).
+#[cfg(feature = "image")]
+impl ToDataUri for image::DynamicImage {
+    fn to_data_uri(&self, options: &EncodeOptions) -> Result<DataUri> {
+        let format = crate::Format::Png(crate::PngOptions::default());
+        crate::image_to_data_uri_with(self, format, options)
+    }
+}
+
+/// Encoded as a PNG, like [`ToDataUri`] with the default options.
+#[cfg(feature = "image")]
+impl TryFrom<&image::DynamicImage> for DataUri {
+    type Error = Error;
+
+    fn try_from(image: &image::DynamicImage) -> Result<Self> {
+        image.to_data_uri(&EncodeOptions::default())
+    }
+}
+
+fn check_len(uri: DataUri, options: &EncodeOptions) -> Result<DataUri> {
+    let uri = match options.line_width {
+        Some(width) => DataUri::from_parts(
+            wrap_data_uri(&uri, width),
+            uri.mime().clone(),
+            uri.encoding(),
+        ),
+        None => uri,
+    };
+    match options.max_len {
+        Some(limit) if uri.len() > limit => Err(Error::TooLarge {
+            actual: uri.len(),
+            limit,
+        }),
+        _ => Ok(uri),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn generic_encoding() {
+        fn encode(value: impl ToDataUri, max_len: Option<usize>) -> Result<DataUri> {
+            value.to_data_uri(&EncodeOptions {
+                max_len,
+                ..Default::default()
+            })
+        }
+        assert_eq!(
+            encode(&b"\x89PNG\r\n\x1a\n"[..], None).unwrap(),
+            "data:image/png;base64,iVBORw0KGgo="
+        );
+        assert_eq!(
+            encode(vec![1, 2], None).unwrap(),
+            "data:application/octet-stream;base64,AQI="
+        );
+        let icon = SvgDataUri::with_options("<svg/>", SvgEncodeOptions::mini());
+        assert_eq!(
+            encode(&icon, Some(29)).unwrap(),
+            "data:image/svg+xml,%3Csvg/%3E"
+        );
+        assert!(matches!(
+            encode(icon.to_data_uri(&Default::default()).unwrap(), Some(28)),
+            Err(Error::TooLarge {
+                actual: 29,
+                limit: 28
+            })
+        ));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn dynamic_image() {
+        let image = image::DynamicImage::new_rgba8(1, 1);
+        let uri = image.to_data_uri(&EncodeOptions::default()).unwrap();
+        assert!(uri.starts_with("data:image/png;base64,"));
+        assert_eq!(DataUri::try_from(&image).unwrap(), uri);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{svg_str_to_data_uri, DataUri, ParseError};
+
+/// Same as [`svg_str_to_data_uri`](crate::svg_str_to_data_uri) but returns a [`url::Url`].
+pub fn svg_str_to_data_url(svg: impl AsRef<str>) -> url::Url {
+    url::Url::parse(&svg_str_to_data_uri(svg)).expect("percent-encoded data URI is a valid URL")
+}
+
+impl TryFrom<&DataUri> for url::Url {
+    type Error = url::ParseError;
+
+    fn try_from(uri: &DataUri) -> Result<Self, Self::Error> {
+        url::Url::parse(uri.as_str())
+    }
+}
+
+impl TryFrom<DataUri> for url::Url {
+    type Error = url::ParseError;
+
+    fn try_from(uri: DataUri) -> Result<Self, Self::Error> {
+        url::Url::try_from(&uri)
+    }
+}
+
+impl TryFrom<&url::Url> for DataUri {
+    type Error = ParseError;
+
+    fn try_from(url: &url::Url) -> Result<Self, Self::Error> {
+        DataUri::parse(url.as_str())
+    }
+}
+
+impl TryFrom<url::Url> for DataUri {
+    type Error = ParseError;
+
+    fn try_from(url: url::Url) -> Result<Self, Self::Error> {
+        DataUri::try_from(&url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn url_round_trip() {
+        let url = svg_str_to_data_url("<svg/>");
+        assert_eq!(url.scheme(), "data");
+        assert_eq!(url.as_str(), svg_str_to_data_uri("<svg/>"));
+
+        let uri = DataUri::try_from(&url).unwrap();
+        assert_eq!(uri.decode_payload().unwrap(), b"<svg/>");
+        assert_eq!(url::Url::try_from(uri).unwrap(), url);
+
+        let https = url::Url::parse("https://example.com/logo.svg").unwrap();
+        assert_eq!(
+            DataUri::try_from(https).unwrap_err(),
+            ParseError::MissingScheme
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::sniff::sniff_mime;
+use crate::{DataUri, Encoding, ParseError};
+use mime::Mime;
+use std::fmt;
+
+/// What [`validate_data_uri`] learned about a well-formed data URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUriInfo {
+    pub mime: Mime,
+    pub encoding: Encoding,
+    /// Size of the payload once decoded.
+    pub decoded_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    Parse(ParseError),
+    /// A character outside of the RFC 2396 `uric` set, at the given byte offset.
+    InvalidCharacter {
+        index: usize,
+        character: char,
+    },
+    /// A `%` not followed by two hexadecimal digits.
+    InvalidPercentEscape {
+        index: usize,
+    },
+    /// The payload content doesn't match the declared media type, both are
+    /// given without parameters.
+    MediaTypeMismatch {
+        declared: String,
+        sniffed: String,
+    },
+    /// The decoded payload is larger than the limit, in bytes. A gzip
+    /// payload is only decompressed up to the limit, so `actual` is then
+    /// `limit + 1`.
+    TooLarge {
+        actual: usize,
+        limit: usize,
+    },
+    /// The media type, given without parameters, isn't in the allowlist.
+    MediaTypeNotAllowed(String),
+    /// The image is larger than the limit, in pixels.
+    TooManyPixels {
+        width: u32,
+        height: u32,
+        limit: u64,
+    },
+    /// The SVG can run scripts, with `<script>` elements or event handlers
+    /// for instance.
+    ActiveSvgContent,
+    /// The SVG isn't well-formed XML, see [`check_svg`](crate::check_svg).
+    #[cfg(feature = "roxmltree")]
+    MalformedSvg(roxmltree::Error),
+    /// The root element of the SVG isn't an `<svg>` element.
+    #[cfg(feature = "roxmltree")]
+    NotSvg {
+        root: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Parse(error) => error.fmt(f),
+            ValidationError::InvalidCharacter { index, character } => {
+                write!(f, "invalid character {:?} at offset {}", character, index)
+            }
+            ValidationError::InvalidPercentEscape { index } => {
+                write!(f, "invalid percent escape at offset {}", index)
+            }
+            ValidationError::MediaTypeMismatch { declared, sniffed } => write!(
+                f,
+                "declared media type `{}` but the payload looks like `{}`",
+                declared, sniffed
+            ),
+            ValidationError::TooLarge { actual, limit } => write!(
+                f,
+                "payload is {} bytes long, exceeding the {} bytes limit",
+                actual, limit
+            ),
+            ValidationError::MediaTypeNotAllowed(media_type) => {
+                write!(f, "media type `{}` is not allowed", media_type)
+            }
+            ValidationError::TooManyPixels {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "image of {}x{} pixels exceeds the limit of {} pixels",
+                width, height, limit
+            ),
+            ValidationError::ActiveSvgContent => write!(f, "SVG contains scripts"),
+            #[cfg(feature = "roxmltree")]
+            ValidationError::MalformedSvg(error) => write!(f, "malformed SVG: {}", error),
+            #[cfg(feature = "roxmltree")]
+            ValidationError::NotSvg { root } => {
+                write!(f, "root element is `{}` instead of `svg`", root)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ValidationError::Parse(error) => Some(error),
+            #[cfg(feature = "roxmltree")]
+            ValidationError::MalformedSvg(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for ValidationError {
+    fn from(error: ParseError) -> Self {
+        ValidationError::Parse(error)
+    }
+}
+
+/// Strictly checks a data URI, typically one supplied by a user.
+///
+/// Unlike [`DataUri::parse`], the whole URI is checked against the RFC 2397
+/// grammar, so literal spaces such as those left by
+/// [`EncodeSet::Minimal`](crate::EncodeSet::Minimal) are rejected. The payload
+/// is decoded and, when its format is recognized, compared to the declared
+/// media type.
+pub fn validate_data_uri(uri: &str) -> Result<DataUriInfo, ValidationError> {
+    check_characters(uri)?;
+    let parsed = DataUri::parse(uri)?;
+    let payload = parsed.decode_payload()?;
+    check_sniffed_mime(parsed.mime(), &payload)?;
+    Ok(DataUriInfo {
+        mime: parsed.mime().clone(),
+        encoding: parsed.encoding(),
+        decoded_len: payload.len(),
+    })
+}
+
+/// Fails when the payload is recognized as another type than `declared`.
+pub(crate) fn check_sniffed_mime(declared: &Mime, payload: &[u8]) -> Result<(), ValidationError> {
+    match sniff_mime(payload) {
+        Some(sniffed)
+            if declared.essence_str() != sniffed.essence_str()
+                && *declared != mime::APPLICATION_OCTET_STREAM =>
+        {
+            Err(ValidationError::MediaTypeMismatch {
+                declared: declared.essence_str().to_string(),
+                sniffed: sniffed.essence_str().to_string(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_characters(uri: &str) -> Result<(), ValidationError> {
+    let bytes = uri.as_bytes();
+    for (index, character) in uri.char_indices() {
+        match character {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => {}
+            // reserved and mark characters from RFC 2396
+            ';' | '/' | '?' | ':' | '@' | '&' | '=' | '+' | '$' | ',' => {}
+            '-' | '_' | '.' | '!' | '~' | '*' | '\'' | '(' | ')' => {}
+            '%' => {
+                let escape = bytes.get(index + 1..index + 3);
+                if !escape.is_some_and(|escape| escape.iter().all(u8::is_ascii_hexdigit)) {
+                    return Err(ValidationError::InvalidPercentEscape { index });
+                }
+            }
+            character => return Err(ValidationError::InvalidCharacter { index, character }),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn valid_uris() {
+        let info = validate_data_uri("data:text/plain;charset=utf-8;base64,aGVsbG8=").unwrap();
+        assert_eq!(info.mime.essence_str(), "text/plain");
+        assert_eq!(info.encoding, Encoding::Base64);
+        assert_eq!(info.decoded_len, 5);
+
+        let svg = r#"<?xml version="1.0"?><!-- logo --><svg xmlns="http://www.w3.org/2000/svg"/>"#;
+        let info = validate_data_uri(&svg_str_to_data_uri(svg)).unwrap();
+        assert_eq!(info.mime, mime::IMAGE_SVG);
+        assert_eq!(info.decoded_len, svg.len());
+
+        let png = bytes_to_data_uri(b"\x89PNG\r\n\x1a\n", &mime::IMAGE_PNG);
+        assert!(validate_data_uri(&png).is_ok());
+    }
+
+    #[test]
+    fn invalid_uris() {
+        assert_eq!(
+            validate_data_uri("data:,a b"),
+            Err(ValidationError::InvalidCharacter {
+                index: 7,
+                character: ' '
+            })
+        );
+        assert_eq!(
+            validate_data_uri("data:,100%"),
+            Err(ValidationError::InvalidPercentEscape { index: 9 })
+        );
+        assert!(matches!(
+            validate_data_uri("data:image/png;base64,!!!!"),
+            Err(ValidationError::Parse(ParseError::InvalidBase64(_)))
+        ));
+        assert_eq!(
+            validate_data_uri(&svg_str_to_data_uri("<svg/>").replace("svg+xml", "png")),
+            Err(ValidationError::MediaTypeMismatch {
+                declared: "image/png".to_string(),
+                sniffed: "image/svg+xml".to_string()
+            })
+        );
+    }
+}
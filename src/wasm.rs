@@ -0,0 +1,37 @@
+//! Browser interop, enabled with the `wasm` feature.
+
+use crate::svg_str_to_data_uri;
+use js_sys::JsString;
+use web_sys::HtmlImageElement;
+
+pub fn svg_str_to_js_string(svg: impl AsRef<str>) -> JsString {
+    JsString::from(svg_str_to_data_uri(svg).as_str())
+}
+
+/// Points the `<img>` element at the SVG, without a round trip through a blob URL.
+pub fn set_image_src_svg(element: &HtmlImageElement, svg: impl AsRef<str>) {
+    element.set_src(&svg_str_to_data_uri(svg));
+}
+
+#[cfg(feature = "image")]
+pub fn image_to_js_string<T>(image: &T, format: crate::Format) -> crate::Result<JsString>
+where
+    T: crate::EncodableImage,
+{
+    Ok(JsString::from(
+        crate::image_to_data_uri(image, format)?.as_str(),
+    ))
+}
+
+#[cfg(feature = "image")]
+pub fn set_image_src<T>(
+    element: &HtmlImageElement,
+    image: &T,
+    format: crate::Format,
+) -> crate::Result<()>
+where
+    T: crate::EncodableImage,
+{
+    element.set_src(&crate::image_to_data_uri(image, format)?);
+    Ok(())
+}
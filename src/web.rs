@@ -0,0 +1,192 @@
+use crate::validate::check_sniffed_mime;
+use crate::{DataUri, DecodePolicy, MediaType, ValidationError};
+use mime::Mime;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Limits an [`Upload`] is checked against, declared on a marker type so that
+/// the upload can be deserialized by any framework's JSON or form extractor.
+///
+/// ```
+/// use data_uri_utils::{MediaType, Upload, UploadPolicy};
+///
+/// struct Avatar;
+///
+/// impl UploadPolicy for Avatar {
+///     const MAX_LEN: usize = 512 * 1024;
+///     const ALLOWED: &'static [MediaType] = &[MediaType::Png, MediaType::Jpeg];
+/// }
+///
+/// #[derive(serde::Deserialize)]
+/// struct Profile {
+///     avatar: Upload<Avatar>,
+/// }
+///
+/// let json = r#"{"avatar": "data:image/png;base64,iVBORw0KGgo="}"#;
+/// let profile: Profile = serde_json::from_str(json)?;
+/// assert_eq!(profile.avatar.mime(), &mime::IMAGE_PNG);
+/// assert_eq!(profile.avatar.bytes(), b"\x89PNG\r\n\x1a\n");
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+pub trait UploadPolicy {
+    /// Largest accepted payload once decoded, in bytes.
+    const MAX_LEN: usize;
+    /// Accepted media types, compared without parameters. Empty accepts any.
+    const ALLOWED: &'static [MediaType];
+    /// Accept SVGs that can run scripts, as [`DecodePolicy::allow_svg_scripts`].
+    const ALLOW_SVG_SCRIPTS: bool = false;
+    /// Largest image area, read from the image header before decoding, as
+    /// [`DecodePolicy::max_pixels`].
+    const MAX_PIXELS: Option<u64> = Some(8192 * 8192);
+}
+
+/// The decoded payload of a data URI accepted by the policy `P`, typically a
+/// field of a request body carrying an uploaded file.
+pub struct Upload<P> {
+    mime: Mime,
+    bytes: Vec<u8>,
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<P: UploadPolicy> Upload<P> {
+    /// Checks `uri` against the policy and decodes it.
+    ///
+    /// The size and image dimensions are checked before decoding. Payloads
+    /// whose content is recognized as another type than the declared one are
+    /// rejected, as with [`validate_data_uri`](crate::validate_data_uri), and
+    /// so are SVGs that can run scripts, unless the policy allows them.
+    pub fn parse(uri: &str) -> Result<Self, ValidationError> {
+        let uri = DataUri::parse(uri)?;
+        let policy = DecodePolicy {
+            max_payload_bytes: Some(P::MAX_LEN),
+            allowed_mimes: P::ALLOWED.to_vec(),
+            allow_svg_scripts: P::ALLOW_SVG_SCRIPTS,
+            max_pixels: P::MAX_PIXELS,
+        };
+        policy.check(&uri)?;
+        let bytes = uri.decode_payload()?;
+        check_sniffed_mime(uri.mime(), &bytes)?;
+        policy.check_svg_scripts(&uri, &bytes)?;
+        Ok(Upload {
+            mime: uri.mime().clone(),
+            bytes,
+            policy: PhantomData,
+        })
+    }
+}
+
+impl<P> Upload<P> {
+    pub fn mime(&self) -> &Mime {
+        &self.mime
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        MediaType::from(&self.mime)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_parts(self) -> (Mime, Vec<u8>) {
+        (self.mime, self.bytes)
+    }
+}
+
+impl<P> fmt::Debug for Upload<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upload")
+            .field("mime", &self.mime)
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl<P> Clone for Upload<P> {
+    fn clone(&self) -> Self {
+        Upload {
+            mime: self.mime.clone(),
+            bytes: self.bytes.clone(),
+            policy: PhantomData,
+        }
+    }
+}
+
+impl<'de, P: UploadPolicy> serde::Deserialize<'de> for Upload<P> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uri = String::deserialize(deserializer)?;
+        Upload::parse(&uri).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    struct Icon;
+
+    impl UploadPolicy for Icon {
+        const MAX_LEN: usize = 16;
+        const ALLOWED: &'static [MediaType] = &[MediaType::Png, MediaType::SvgXml];
+    }
+
+    struct Any;
+
+    impl UploadPolicy for Any {
+        const MAX_LEN: usize = 1024;
+        const ALLOWED: &'static [MediaType] = &[];
+    }
+
+    #[test]
+    fn check_uploads() {
+        let upload = Upload::<Icon>::parse("data:image/svg+xml;charset=utf-8,%3Csvg/%3E").unwrap();
+        assert_eq!(upload.media_type().essence(), "image/svg+xml");
+        assert_eq!(upload.into_parts().1, b"<svg/>");
+
+        assert_eq!(
+            Upload::<Icon>::parse("data:image/gif;base64,R0lGODlh").unwrap_err(),
+            ValidationError::MediaTypeNotAllowed("image/gif".to_string())
+        );
+        let large = bytes_to_data_uri(&[0; 17], MediaType::Png);
+        assert_eq!(
+            Upload::<Icon>::parse(&large).unwrap_err(),
+            ValidationError::TooLarge {
+                actual: 17,
+                limit: 16
+            }
+        );
+        assert!(matches!(
+            Upload::<Icon>::parse("data:image/png;base64,R0lGODlh").unwrap_err(),
+            ValidationError::MediaTypeMismatch { .. }
+        ));
+        let error = serde_json::from_str::<Upload<Icon>>("\"data:text/plain,hi\"").unwrap_err();
+        assert!(error.to_string().contains("`text/plain` is not allowed"));
+    }
+
+    #[test]
+    fn reject_active_uploads() {
+        let scripted = "data:image/svg+xml,%3Csvg onload='x()'/%3E";
+        assert_eq!(
+            Upload::<Any>::parse(scripted).unwrap_err(),
+            ValidationError::ActiveSvgContent
+        );
+
+        struct Trusted;
+
+        impl UploadPolicy for Trusted {
+            const MAX_LEN: usize = 1024;
+            const ALLOWED: &'static [MediaType] = &[];
+            const ALLOW_SVG_SCRIPTS: bool = true;
+            const MAX_PIXELS: Option<u64> = None;
+        }
+
+        assert!(Upload::<Trusted>::parse(scripted).is_ok());
+        // A GIF header claiming 65535×65535 pixels.
+        let bomb = bytes_to_data_uri(b"GIF89a\xff\xff\xff\xff", &mime::IMAGE_GIF);
+        assert!(matches!(
+            Upload::<Any>::parse(&bomb).unwrap_err(),
+            ValidationError::TooManyPixels { .. }
+        ));
+        assert!(Upload::<Trusted>::parse(&bomb).is_ok());
+    }
+}